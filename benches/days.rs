@@ -0,0 +1,121 @@
+//! Benchmarks every day compiled into this build: parsing, part 1 and part 2,
+//! on the real input when it is available and on a generated input.
+//!
+//! ```bash
+//! cargo bench                              # every day
+//! cargo bench -- day06/                    # one day
+//! AOC_BENCH_SIZE=300 cargo bench -- day11/generated
+//! ```
+
+use aoc2024::{generators, get_solution, real_input, Input, Params, Solution, SolveError};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Generated inputs are this size unless `AOC_BENCH_SIZE` says otherwise
+const GENERATED_SIZE: usize = 100;
+const SEED: u64 = 0;
+/// Steps slower than this take the minimum number of samples, so the slowest
+/// days finish in minutes rather than hours
+const SLOW: Duration = Duration::from_millis(50);
+
+type Step<'a> = (&'static str, Box<dyn Fn() -> Result<(), SolveError> + 'a>);
+
+/// The steps worth timing: parsing when the day parses separately, and each
+/// part that answers rather than errors
+fn steps<'a>(solution: &'a dyn Solution, input: &'a Input, params: &'a Params) -> Vec<Step<'a>> {
+    let mut steps: Vec<Step> = Vec::new();
+    if solution.try_parse(input.text_for(1), params).is_some() {
+        steps.push((
+            "parse",
+            Box::new(|| {
+                solution
+                    .try_parse(black_box(input.text_for(1)), params)
+                    .unwrap()
+            }),
+        ));
+    }
+    let (part1, part2) = (params.for_part(1), params.for_part(2));
+    steps.push((
+        "part1",
+        Box::new(move || {
+            solution
+                .try_part1_with(black_box(input.text_for(1)), &part1)
+                .map(drop)
+        }),
+    ));
+    steps.push((
+        "part2",
+        Box::new(move || {
+            solution
+                .try_part2_with(black_box(input.text_for(2)), &part2)
+                .map(drop)
+        }),
+    ));
+    steps
+}
+
+fn bench_input(c: &mut Criterion, day: u8, solution: &dyn Solution, name: &str, input: &Input) {
+    let group_name = format!("day{:02}/{}", day, name);
+    let params = match input.params_for(solution) {
+        Ok(params) => params,
+        Err(e) => {
+            eprintln!("skipping {}: {}", group_name, e);
+            return;
+        }
+    };
+
+    let steps = steps(solution, input, &params);
+    if !steps.iter().any(|(step, _)| *step == "parse") {
+        eprintln!("skipping {}/parse: the day parses as it solves", group_name);
+    }
+
+    // One run of each step first, to leave out failing steps and size the group
+    let mut slowest = Duration::ZERO;
+    let steps: Vec<Step> = steps
+        .into_iter()
+        .filter(|(step, run)| {
+            let start = Instant::now();
+            let result = run();
+            slowest = slowest.max(start.elapsed());
+            if let Err(e) = &result {
+                eprintln!("skipping {}/{}: {}", group_name, step, e);
+            }
+            result.is_ok()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group(group_name);
+    if slowest > SLOW {
+        group.sample_size(10);
+    }
+    for (step, run) in &steps {
+        group.bench_function(*step, |b| b.iter(run));
+    }
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    // Time the solving, not the diagnostics
+    aoc2024::set_logging(false);
+    let size = std::env::var("AOC_BENCH_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(GENERATED_SIZE);
+
+    for day in 1..=25 {
+        let Some(solution) = get_solution(day) else {
+            continue;
+        };
+        if let Some(input) = real_input(day) {
+            bench_input(c, day, solution.as_ref(), "real", &input);
+        }
+        if let Some(input) = generators::generate(day, size, SEED) {
+            let name = format!("generated-{}", size);
+            bench_input(c, day, solution.as_ref(), &name, &input);
+        }
+    }
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
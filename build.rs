@@ -0,0 +1,45 @@
+//! With the `embed-inputs` feature, generates the table of input files that
+//! `src/embed.rs` compiles into the binary.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.is_dir() {
+            collect(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn main() {
+    let root = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let mut files = Vec::new();
+    if std::env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_some() {
+        // Picks up added, changed and removed files anywhere under inputs/
+        println!("cargo:rerun-if-changed=inputs");
+        collect(&root.join("inputs"), &mut files);
+        files.sort();
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut table = String::from("pub static FILES: &[(&str, &[u8])] = &[\n");
+    for path in files {
+        let relative = path.strip_prefix(&root).unwrap();
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        writeln!(table, "    ({:?}, include_bytes!({:?})),", name, path).unwrap();
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out.join("embedded_inputs.rs"), table).unwrap();
+}
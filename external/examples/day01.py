@@ -0,0 +1,23 @@
+#!/usr/bin/env python3
+"""Day 1 in Python, as an example external solver. Register it with
+external/01-python.solver containing:
+
+    command = python3 external/examples/day01.py
+"""
+
+import os
+import sys
+from collections import Counter
+
+left, right = [], []
+for line in sys.stdin:
+    if line.strip():
+        a, b = line.split()
+        left.append(int(a))
+        right.append(int(b))
+
+if os.environ["AOC_PART"] == "1":
+    print(sum(abs(a - b) for a, b in zip(sorted(left), sorted(right))))
+else:
+    counts = Counter(right)
+    print(sum(a * counts[a] for a in left))
@@ -0,0 +1,25 @@
+#![no_main]
+
+use aoc2024::parse;
+use libfuzzer_sys::fuzz_target;
+
+// Every combinator must return an error rather than panic, whatever the text
+fuzz_target!(|input: &str| {
+    let _ = parse::grid(input);
+    let _ = parse::sections::<2>(input);
+    let _ = parse::ints(input);
+    let _ = parse::parse_lines(input, |line| line.ints::<3>());
+
+    for mut line in parse::lines(input) {
+        let _ = line.clone().digit();
+        let _ = line.clone().list(",", |l| l.skip_whitespace().word());
+        let _ = line
+            .label(":")
+            .and_then(|_| line.skip_whitespace().int::<i32>())
+            .and_then(|_| line.end());
+    }
+    for block in parse::blocks(input) {
+        let _ = block.exact_lines::<3>();
+        let _ = block.grid_with(|c| c.to_digit(10));
+    }
+});
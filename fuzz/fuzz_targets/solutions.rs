@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the day, the rest is the puzzle input. Errors are
+// fine; a panic is a crash.
+fuzz_target!(|data: &[u8]| {
+    let Some((&day, input)) = data.split_first() else {
+        return;
+    };
+    let Ok(input) = std::str::from_utf8(input) else {
+        return;
+    };
+    let Some(solution) = aoc2024::get_solution(day % 25 + 1) else {
+        return;
+    };
+    let params = solution.default_params();
+    let _ = solution.try_part1_with(input, &params);
+    let _ = solution.try_part2_with(input, &params);
+});
@@ -0,0 +1,62 @@
+//! Day 1 as a plugin, solving part 2 by binary search over the sorted right
+//! list instead of counting. Build it with `cargo build --release` here, then
+//! from the repository root:
+//!
+//! ```text
+//! cargo run -- --day 1 --plugin plugins/example/target/release/libaoc2024_plugin_example.so
+//! ```
+
+use aoc2024::{Params, Solution, SolveError};
+
+struct Day01Sorted;
+
+impl Day01Sorted {
+    fn lists(input: &str) -> Result<(Vec<i64>, Vec<i64>), SolveError> {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for (i, line) in input
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            let ids: Vec<i64> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|e| SolveError::unsolvable(format!("line {}: {}", i + 1, e)))?;
+            let [l, r] = ids[..] else {
+                return Err(SolveError::unsolvable(format!(
+                    "line {}: expected two ids",
+                    i + 1
+                )));
+            };
+            left.push(l);
+            right.push(r);
+        }
+        left.sort_unstable();
+        right.sort_unstable();
+        Ok((left, right))
+    }
+}
+
+impl Solution for Day01Sorted {
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (left, right) = Self::lists(input)?;
+        let distance: i64 = left.iter().zip(&right).map(|(a, b)| (a - b).abs()).sum();
+        Ok(distance.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (left, right) = Self::lists(input)?;
+        let score: i64 = left
+            .iter()
+            .map(|id| {
+                let count = right.partition_point(|r| r <= id) - right.partition_point(|r| r < id);
+                id * count as i64
+            })
+            .sum();
+        Ok(score.to_string())
+    }
+}
+
+aoc2024::export_plugin!(day: 1, name: "sorted", parts: [1, 2], solution: Day01Sorted);
@@ -1,28 +1,29 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 use std::collections::HashMap;
 
 pub struct Day01;
 
 impl Day01 {
-    fn parse_input(&self, input: &str) -> (Vec<i64>, Vec<i64>) {
-        let mut left = Vec::new();
-        let mut right = Vec::new();
-
-        for line in input.lines() {
-            let mut parts = line.split_whitespace();
-            let l: i64 = parts.next().unwrap().parse().unwrap();
-            let r: i64 = parts.next().unwrap().parse().unwrap();
-            left.push(l);
-            right.push(r);
-        }
-
-        (left, right)
+    /// Ids are read as `i32` so distances and scores can't overflow an `i64`
+    fn parse_input(&self, input: &str) -> ParseResult<(Vec<i64>, Vec<i64>)> {
+        let pairs = parse::parse_lines(input, |line| {
+            let l: i32 = line.int()?;
+            let r: i32 = line.skip_whitespace().int()?;
+            line.end()?;
+            Ok((l as i64, r as i64))
+        })?;
+        Ok(pairs.into_iter().unzip())
     }
 }
 
 impl Solution for Day01 {
-    fn part1(&self, input: &str) -> String {
-        let (mut left, mut right) = self.parse_input(input);
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(self.parse_input(input).map(drop).map_err(Into::into))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (mut left, mut right) = self.parse_input(input)?;
 
         // Sort both lists independently
         left.sort_unstable();
@@ -35,11 +36,11 @@ impl Solution for Day01 {
             .map(|(a, b)| (a - b).abs())
             .sum();
 
-        total_distance.to_string()
+        Ok(total_distance.to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let (left, right) = self.parse_input(input);
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (left, right) = self.parse_input(input)?;
 
         // Count occurrences in right list
         let right_counts: HashMap<i64, i64> = right.iter().fold(HashMap::new(), |mut map, &num| {
@@ -53,7 +54,7 @@ impl Solution for Day01 {
             .map(|&num| num * right_counts.get(&num).copied().unwrap_or(0))
             .sum();
 
-        similarity_score.to_string()
+        Ok(similarity_score.to_string())
     }
 }
 
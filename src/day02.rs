@@ -1,53 +1,76 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 pub struct Day02;
 
 impl Solution for Day02 {
-    fn part1(&self, input: &str) -> String {
-        input
-            .lines()
-            .filter(|line| is_safe_sequence(line, false))
-            .count()
-            .to_string()
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_reports(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        input
-            .lines()
-            .filter(|line| is_safe_sequence(line, true))
-            .count()
-            .to_string()
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let reports = parse_reports(input)?;
+        let safe = reports
+            .iter()
+            .filter(|report| safety(report) == Safety::Safe)
+            .count();
+        Ok(safe.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let reports = parse_reports(input)?;
+        let safe = reports
+            .iter()
+            .enumerate()
+            .filter(|(i, report)| match safety(report) {
+                Safety::Safe => true,
+                Safety::Dampened(level) => {
+                    crate::explain!(
+                        "level_removed",
+                        report = i + 1,
+                        level = level + 1,
+                        value = report[level]
+                    );
+                    true
+                }
+                Safety::Unsafe => false,
+            })
+            .count();
+        Ok(safe.to_string())
     }
 }
 
-fn is_safe_sequence(line: &str, allow_remove_one: bool) -> bool {
-    let numbers: Vec<i32> = line
-        .split_whitespace()
-        .filter_map(|s| s.parse().ok())
-        .collect();
+/// Levels are small enough as `i16` that their differences can't overflow
+fn parse_reports(input: &str) -> ParseResult<Vec<Vec<i32>>> {
+    parse::parse_lines(input, |line| {
+        let levels = line.list(" ", |l| l.int::<i16>().map(i32::from))?;
+        line.end()?;
+        Ok(levels)
+    })
+}
 
-    if numbers.len() < 2 {
-        return false;
-    }
+/// How a report fares with the Problem Dampener
+#[derive(Debug, PartialEq, Eq)]
+enum Safety {
+    Safe,
+    /// Safe once the level at this index is removed
+    Dampened(usize),
+    Unsafe,
+}
 
-    // For part 2, try removing each number once and check if sequence becomes valid
-    if allow_remove_one {
-        for skip_idx in 0..numbers.len() {
-            let mut test_sequence = Vec::new();
-            for (i, &num) in numbers.iter().enumerate() {
-                if i != skip_idx {
-                    test_sequence.push(num);
-                }
-            }
-            if is_valid_sequence(&test_sequence) {
-                return true;
-            }
-        }
-        // If no single removal makes it valid, check if it's valid as is
-        is_valid_sequence(&numbers)
-    } else {
-        is_valid_sequence(&numbers)
+fn safety(numbers: &[i32]) -> Safety {
+    if is_valid_sequence(numbers) {
+        return Safety::Safe;
     }
+
+    // Try removing each number once and check if sequence becomes valid
+    (0..numbers.len())
+        .find(|&skip_idx| {
+            let mut test_sequence = numbers.to_vec();
+            test_sequence.remove(skip_idx);
+            is_valid_sequence(&test_sequence)
+        })
+        .map_or(Safety::Unsafe, Safety::Dampened)
 }
 
 fn is_valid_sequence(numbers: &[i32]) -> bool {
@@ -110,4 +133,19 @@ mod tests {
 1 3 6 7 9";
         assert_eq!(Day02.part2(input), "4");
     }
+
+    #[test]
+    fn test_explains_removed_levels() {
+        let input = crate::read_input(2, true);
+        let events = crate::explain::explained(|| {
+            Day02.part2(&input);
+        });
+        assert_eq!(
+            events,
+            [
+                "level_removed: report=4, level=2, value=3",
+                "level_removed: report=5, level=3, value=4"
+            ]
+        );
+    }
 }
@@ -1,119 +1,101 @@
-use crate::Solution;
+use crate::{Params, Solution, SolveError};
 
 pub struct Day03;
 
 impl Solution for Day03 {
-    fn part1(&self, input: &str) -> String {
-        process_part1(input)
+    /// Corrupted memory is the whole point, so any text is valid input
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        instructions(input);
+        Some(Ok(()))
     }
 
-    fn part2(&self, input: &str) -> String {
-        process_part2(input)
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(process_part1(input))
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(process_part2(input))
     }
 }
 
-fn process_part1(input: &str) -> String {
-    let mut total = 0;
-    let mut i = 0;
+/// An instruction that survived the corruption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(i32, i32),
+    Do,
+    Dont,
+}
+
+/// Every instruction in the memory, in order. A valid `mul(...)` is skipped
+/// as a whole; anything else moves on by one character.
+fn instructions(input: &str) -> Vec<Instruction> {
     let chars: Vec<char> = input.chars().collect();
+    let at = |i: usize, word: &str| chars[i..].iter().copied().take(word.len()).eq(word.chars());
+    let mut found = Vec::new();
+    let mut i = 0;
 
     while i < chars.len() {
-        if i + 3 < chars.len() && chars[i..i + 4].iter().collect::<String>() == "mul(" {
-            // Found potential mul instruction, look for closing parenthesis
-            let start = i + 4;
-            let mut end = start;
-            let mut found_valid = false;
-            let mut depth = 1;
-
-            while end < chars.len() && depth > 0 {
-                if chars[end] == '(' {
-                    depth += 1;
-                } else if chars[end] == ')' {
-                    depth -= 1;
-                    if depth == 0 {
-                        // We found a matching closing parenthesis
-                        let inside = &chars[start..end].iter().collect::<String>();
-                        if let Some((x, y)) = parse_mul_args(inside) {
-                            total += x * y;
-                            found_valid = true;
-                        }
-                    }
-                }
-                end += 1;
-            }
-
-            if found_valid {
+        if at(i, "mul(") {
+            if let Some((x, y, end)) = mul_args(&chars, i + 4) {
+                found.push(Instruction::Mul(x, y));
                 i = end;
                 continue;
             }
+        } else if at(i, "don't") {
+            found.push(Instruction::Dont);
+        } else if at(i, "do(") {
+            found.push(Instruction::Do);
         }
         i += 1;
     }
 
-    total.to_string()
+    found
 }
 
-fn process_part2(input: &str) -> String {
-    let mut total = 0;
-    let mut i = 0;
-    let chars: Vec<char> = input.chars().collect();
-    let mut mul_enabled = true;
-
-    while i < chars.len() {
-        if i + 4 < chars.len() {
-            let next_four: String = chars[i..i + 4].iter().collect();
-            let next_five: String = if i + 5 < chars.len() {
-                chars[i..i + 5].iter().collect()
-            } else {
-                String::new()
-            };
-
-            if next_four == "mul(" {
-                // Found potential mul instruction
-                let start = i + 4;
-                let mut end = start;
-                let mut found_valid = false;
-                let mut depth = 1;
-
-                while end < chars.len() && depth > 0 {
-                    if chars[end] == '(' {
-                        depth += 1;
-                    } else if chars[end] == ')' {
-                        depth -= 1;
-                        if depth == 0 && mul_enabled {
-                            // We found a matching closing parenthesis
-                            let inside = &chars[start..end].iter().collect::<String>();
-                            if let Some((x, y)) = parse_mul_args(inside) {
-                                total += x * y;
-                                found_valid = true;
-                            }
-                        }
-                    }
-                    end += 1;
+/// The arguments of a `mul(` whose arguments start at `start`, if they are
+/// valid, and the index just past its matching closing parenthesis
+fn mul_args(chars: &[char], start: usize) -> Option<(i32, i32, usize)> {
+    let mut depth = 1;
+    for end in start..chars.len() {
+        match chars[end] {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    let inside: String = chars[start..end].iter().collect();
+                    let (x, y) = parse_mul_args(&inside)?;
+                    return Some((x, y, end + 1));
                 }
-
-                if found_valid {
-                    i = end;
-                    continue;
-                }
-            } else if next_five == "don't" {
-                mul_enabled = false;
-            } else if next_three_chars(&chars, i) == "do(" {
-                mul_enabled = true;
             }
+            _ => {}
         }
-        i += 1;
     }
+    None
+}
 
+fn process_part1(input: &str) -> String {
+    let total: i64 = instructions(input)
+        .into_iter()
+        .map(|instruction| match instruction {
+            Instruction::Mul(x, y) => (x * y) as i64,
+            _ => 0,
+        })
+        .sum();
     total.to_string()
 }
 
-fn next_three_chars(chars: &[char], start: usize) -> String {
-    if start + 3 <= chars.len() {
-        chars[start..start + 3].iter().collect()
-    } else {
-        String::new()
+fn process_part2(input: &str) -> String {
+    let mut total: i64 = 0;
+    let mut mul_enabled = true;
+    for instruction in instructions(input) {
+        match instruction {
+            Instruction::Mul(x, y) if mul_enabled => total += (x * y) as i64,
+            Instruction::Mul(..) => {}
+            Instruction::Do => mul_enabled = true,
+            Instruction::Dont => mul_enabled = false,
+        }
     }
+    total.to_string()
 }
 
 fn parse_mul_args(args: &str) -> Option<(i32, i32)> {
@@ -136,19 +118,19 @@ fn parse_mul_args(args: &str) -> Option<(i32, i32)> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use crate::load_input;
 
     #[test]
     fn test_part1_sample() {
-        let input = fs::read_to_string("inputs/sample/03.txt").unwrap();
-        let result = process_part1(&input);
+        let input = load_input(3, true);
+        let result = process_part1(input.text_for(1));
         assert_eq!(result, "161");
     }
 
     #[test]
     fn test_part2_sample() {
-        let input = fs::read_to_string("inputs/sample/03_2.txt").unwrap();
-        let result = process_part2(&input);
+        let input = load_input(3, true);
+        let result = process_part2(input.text_for(2));
         assert_eq!(result, "48");
     }
 }
@@ -1,55 +1,53 @@
-use crate::Solution;
+use crate::grid::{Grid, ALL_DIRECTIONS};
+use crate::parse;
+use crate::{Params, Solution, SolveError};
 
 pub struct Day04;
 
 impl Solution for Day04 {
-    fn part1(&self, input: &str) -> String {
-        process_part1(input)
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse::grid(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        process_part2(input).to_string()
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(process_part1(&parse::grid(input)?).to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(process_part2(&parse::grid(input)?).to_string())
     }
 }
 
-fn check_word_at_position(
-    grid: &[Vec<char>],
-    row: usize,
-    col: usize,
-    row_delta: i32,
-    col_delta: i32,
-) -> bool {
-    let rows = grid.len() as i32;
-    let cols = grid[0].len() as i32;
+fn check_word_at_position(grid: &Grid<char>, pos: (usize, usize), delta: (isize, isize)) -> bool {
     let word = ['X', 'M', 'A', 'S'];
 
-    for i in 0..4 {
-        let new_row = row as i32 + i * row_delta;
-        let new_col = col as i32 + i * col_delta;
-
-        if new_row < 0 || new_row >= rows || new_col < 0 || new_col >= cols {
-            return false;
-        }
-
-        if grid[new_row as usize][new_col as usize] != word[i as usize] {
-            return false;
+    let mut current = Some(pos);
+    for &letter in &word {
+        match current {
+            Some(p) if grid[p] == letter => current = grid.offset(p, delta),
+            _ => return false,
         }
     }
 
     true
 }
 
-fn check_x_mas_at_position(grid: &[Vec<char>], row: usize, col: usize) -> bool {
+fn check_x_mas_at_position(grid: &Grid<char>, pos: (usize, usize)) -> bool {
     // First check if center is 'A'
-    if grid[row][col] != 'A' {
+    if grid[pos] != 'A' {
         return false;
     }
 
-    // Get the characters in each diagonal
-    let top_left = grid[row - 1][col - 1];
-    let top_right = grid[row - 1][col + 1];
-    let bottom_left = grid[row + 1][col - 1];
-    let bottom_right = grid[row + 1][col + 1];
+    // Get the characters in each diagonal, bailing out at the edges
+    let corner = |delta| grid.offset(pos, delta).map(|p| grid[p]);
+    let (Some(top_left), Some(top_right), Some(bottom_left), Some(bottom_right)) = (
+        corner((-1, -1)),
+        corner((-1, 1)),
+        corner((1, -1)),
+        corner((1, 1)),
+    ) else {
+        return false;
+    };
 
     // Check diagonal 1 (top-left to bottom-right)
     let diag1_m_count = [top_left, bottom_right]
@@ -75,61 +73,22 @@ fn check_x_mas_at_position(grid: &[Vec<char>], row: usize, col: usize) -> bool {
     diag1_m_count == 1 && diag1_s_count == 1 && diag2_m_count == 1 && diag2_s_count == 1
 }
 
-fn process_part1(input: &str) -> String {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
-    let rows = grid.len();
-    if rows == 0 {
-        return "0".to_string();
-    }
-    let cols = grid[0].len();
-
-    let mut count = 0;
-
-    // Define all possible directions to check
-    let directions = [
-        (0, 1),   // right
-        (0, -1),  // left
-        (1, 0),   // down
-        (-1, 0),  // up
-        (1, 1),   // down-right
-        (-1, 1),  // up-right
-        (1, -1),  // down-left
-        (-1, -1), // up-left
-    ];
-
-    // Check all possible starting positions
-    for row in 0..rows {
-        for col in 0..cols {
-            // Try each direction from this position
-            for &(row_delta, col_delta) in &directions {
-                if check_word_at_position(&grid, row, col, row_delta, col_delta) {
-                    count += 1;
-                }
-            }
-        }
-    }
-
-    count.to_string()
+fn process_part1(grid: &Grid<char>) -> usize {
+    // Check every direction from every starting position
+    grid.positions()
+        .map(|pos| {
+            ALL_DIRECTIONS
+                .iter()
+                .filter(|&&delta| check_word_at_position(grid, pos, delta))
+                .count()
+        })
+        .sum()
 }
 
-fn process_part2(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
-    let rows = grid.len();
-    let cols = grid[0].len();
-    let mut count = 0;
-
-    // We need at least a 3x3 grid and need to stay 1 away from edges
-    for row in 1..rows - 1 {
-        for col in 1..cols - 1 {
-            if check_x_mas_at_position(&grid, row, col) {
-                count += 1;
-            }
-        }
-    }
-
-    count
+fn process_part2(grid: &Grid<char>) -> usize {
+    grid.positions()
+        .filter(|&pos| check_x_mas_at_position(grid, pos))
+        .count()
 }
 
 #[cfg(test)]
@@ -149,7 +108,7 @@ mod tests {
                     MAMMMXMMMM\n\
                     MXMXAXMASX";
 
-        assert_eq!(process_part1(input), "18");
+        assert_eq!(Day04.part1(input), "18");
     }
 
     #[test]
@@ -165,6 +124,13 @@ mod tests {
                     M.M.M.M.M.\n\
                     ..........";
 
-        assert_eq!(process_part2(input), 9);
+        assert_eq!(Day04.part2(input), "9");
+    }
+
+    #[test]
+    fn test_narrow_grids() {
+        assert_eq!(Day04.part2("A"), "0");
+        assert_eq!(Day04.part2("MS\nAA"), "0");
+        assert_eq!(Day04.part1(""), "0");
     }
 }
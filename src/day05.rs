@@ -1,52 +1,44 @@
-use crate::Solution;
+use crate::graph::Graph;
+use crate::parse::{self, Line, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 #[derive(Debug)]
 struct PrintRules {
-    rules: Vec<(u32, u32)>, // (before, after) pairs
+    rules: Graph<u32>, // edge from each page to the pages that must come after it
     updates: Vec<Vec<u32>>,
 }
 
 impl PrintRules {
-    fn split_input(input: &str) -> (&str, &str) {
-        // Try splitting by double newline, handling both CRLF and LF
-        if input.contains("\r\n\r\n") {
-            let parts: Vec<&str> = input.split("\r\n\r\n").collect();
-            (parts[0], parts[1])
-        } else {
-            let parts: Vec<&str> = input.split("\n\n").collect();
-            (parts[0], parts[1])
+    /// Reads `before|after` rules, a blank line, then comma-separated updates
+    fn parse(input: &str) -> ParseResult<Self> {
+        let [rules_block, updates_block] = parse::sections(input)?;
+
+        let mut rules = Graph::directed();
+        for mut line in rules_block.lines() {
+            let before = Self::page(&mut line)?;
+            let after = Self::page(line.literal("|")?)?;
+            line.end()?;
+            rules.add_edge(before, after);
         }
-    }
-
-    fn parse(input: &str) -> Self {
-        let (rules_str, updates_str) = Self::split_input(input);
 
-        let rules: Vec<(u32, u32)> = rules_str
+        let updates = updates_block
             .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| {
-                let mut nums = line.trim().split('|');
-                let before = nums.next().unwrap_or("").trim();
-                let after = nums.next().unwrap_or("").trim();
-                (before.parse().unwrap(), after.parse().unwrap())
+            .map(|mut line| {
+                let update = line.list(",", Self::page)?;
+                line.end()?;
+                Ok(update)
             })
-            .collect();
+            .collect::<ParseResult<_>>()?;
 
-        let updates: Vec<Vec<u32>> = updates_str
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| {
-                line.trim()
-                    .split(',')
-                    .map(|n| n.trim().parse().unwrap())
-                    .collect()
-            })
-            .collect();
+        Ok(PrintRules { rules, updates })
+    }
 
-        PrintRules { rules, updates }
+    fn page(line: &mut Line) -> ParseResult<u32> {
+        line.int::<u16>().map(u32::from)
     }
 
-    fn is_valid_order(&self, update: &[u32]) -> bool {
+    /// The first rule the update's order breaks, as `(before, after)`
+    fn broken_rule(&self, update: &[u32]) -> Option<(u32, u32)> {
         // For each pair of numbers in the update
         for i in 0..update.len() {
             for j in i + 1..update.len() {
@@ -54,84 +46,88 @@ impl PrintRules {
                 let b = update[j];
 
                 // Check if there's a rule saying b should come before a
-                if self
-                    .rules
-                    .iter()
-                    .any(|&(before, after)| before == b && after == a)
-                {
-                    return false;
+                if self.rules.connects(&b, &a) {
+                    return Some((b, a));
                 }
             }
         }
-        true
+        None
     }
 
     fn get_middle_number(&self, update: &[u32]) -> u32 {
         update[update.len() / 2]
     }
 
-    fn sort_update(&self, update: &[u32]) -> Vec<u32> {
-        let mut sorted = update.to_vec();
-        let mut changed = true;
-        while changed {
-            changed = false;
-            for i in 0..sorted.len() {
-                for j in i + 1..sorted.len() {
-                    let a = sorted[i];
-                    let b = sorted[j];
-                    // If there's a rule saying b should come before a, swap them
-                    if self
-                        .rules
-                        .iter()
-                        .any(|&(before, after)| before == b && after == a)
-                    {
-                        sorted.swap(i, j);
-                        changed = true;
-                    }
-                }
+    fn sort_update(&self, update: &[u32]) -> Result<Vec<u32>, SolveError> {
+        // The full rule set is cyclic, but the rules between one update's pages are not
+        let mut pages = self.rules.induced(update);
+        for &page in update {
+            pages.add_node(page); // pages without any rules
+        }
+        match pages.toposort() {
+            Ok(order) => Ok(order.into_iter().map(|id| *pages.key(id)).collect()),
+            Err(cycle) => {
+                let cycle: Vec<u32> = cycle.into_iter().map(|id| *pages.key(id)).collect();
+                Err(SolveError::Unsolvable(format!(
+                    "rules for update {:?} contain a cycle: {:?}",
+                    update, cycle
+                )))
             }
         }
-        sorted
     }
 }
 
 pub struct Day05;
 
 impl Solution for Day05 {
-    fn part1(&self, input: &str) -> String {
-        let rules = PrintRules::parse(input);
-
-        let sum: u32 = rules
-            .updates
-            .iter()
-            .filter(|update| rules.is_valid_order(update))
-            .map(|update| rules.get_middle_number(update))
-            .sum();
-
-        sum.to_string()
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(PrintRules::parse(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        let rules = PrintRules::parse(input);
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let rules = PrintRules::parse(input)?;
 
-        // First find all initially invalid updates
-        let invalid_updates: Vec<Vec<u32>> = rules
+        let sum: u64 = rules
             .updates
             .iter()
-            .filter(|update| !rules.is_valid_order(update))
-            .cloned()
-            .collect();
-
-        // Now process only those invalid updates
-        let sum: u32 = invalid_updates
-            .iter()
-            .map(|update| {
-                let sorted = rules.sort_update(update);
-                rules.get_middle_number(&sorted)
+            .enumerate()
+            .filter(|(i, update)| match rules.broken_rule(update) {
+                Some((before, after)) => {
+                    crate::explain!(
+                        "rule_violated",
+                        update = i + 1,
+                        rule = format!("{}|{}", before, after)
+                    );
+                    false
+                }
+                None => true,
             })
+            .map(|(_, update)| u64::from(rules.get_middle_number(update)))
             .sum();
 
-        sum.to_string()
+        Ok(sum.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let rules = PrintRules::parse(input)?;
+
+        // Only the initially invalid updates count
+        let mut sum: u64 = 0;
+        for (i, update) in rules.updates.iter().enumerate() {
+            let Some((before, after)) = rules.broken_rule(update) else {
+                continue;
+            };
+            let sorted = rules.sort_update(update)?;
+            crate::explain!(
+                "reordered",
+                update = i + 1,
+                rule = format!("{}|{}", before, after),
+                order = sorted
+            );
+            sum += u64::from(rules.get_middle_number(&sorted));
+        }
+
+        Ok(sum.to_string())
     }
 }
 
@@ -151,4 +147,20 @@ mod tests {
         let input = read_input(5, true);
         assert_eq!(Day05.part2(&input), "123");
     }
+
+    #[test]
+    fn test_explains_broken_rules() {
+        let input = read_input(5, true);
+        let events = crate::explain::explained(|| {
+            Day05.part1(&input);
+        });
+        assert_eq!(
+            events,
+            [
+                "rule_violated: update=4, rule=97|75",
+                "rule_violated: update=5, rule=29|13",
+                "rule_violated: update=6, rule=75|13"
+            ]
+        );
+    }
 }
@@ -1,111 +1,132 @@
-use crate::Solution;
+use crate::debugger::{Debugger, Session, Steppable};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::parse;
+use crate::{Params, Solution, SolveError};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
+pub struct Day06;
+
+/// The guard's walk until it leaves the map, one move or turn per step
+struct Patrol {
+    map: Grid<char>,
+    /// Every position and heading so far, in order
+    path: Vec<(Point, Direction)>,
+    states: HashSet<(Point, Direction)>,
+    /// How many times each visited cell appears in `path`
+    visits: HashMap<Point, usize>,
 }
 
-impl Direction {
-    fn turn_right(&self) -> Direction {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
+impl Patrol {
+    fn new(input: &str) -> Result<Self, SolveError> {
+        let (map, start) = Day06::parse_map(input)?;
+        Ok(Self {
+            map,
+            path: vec![start],
+            states: HashSet::from([start]),
+            visits: HashMap::from([(start.0, 1)]),
+        })
     }
+}
 
-    fn move_forward(&self, pos: (i32, i32)) -> (i32, i32) {
-        match self {
-            Direction::Up => (pos.0 - 1, pos.1),
-            Direction::Right => (pos.0, pos.1 + 1),
-            Direction::Down => (pos.0 + 1, pos.1),
-            Direction::Left => (pos.0, pos.1 - 1),
+impl Steppable for Patrol {
+    /// The length of the path
+    type Snapshot = usize;
+
+    fn step(&mut self) -> Result<bool, SolveError> {
+        let (pos, dir) = *self.path.last().unwrap();
+        let next_pos = pos.step(dir);
+
+        // Check if guard is out of bounds, or if there's an obstacle ahead
+        let state = match self.map.get_point(next_pos) {
+            None => return Ok(false),
+            Some('#') => (pos, dir.turn_right()),
+            Some(_) => (next_pos, dir),
+        };
+        // Back in a state we've seen: the guard walks in circles forever
+        if !self.states.insert(state) {
+            return Err(SolveError::unsolvable("the guard never leaves the map"));
         }
+        self.path.push(state);
+        *self.visits.entry(state.0).or_default() += 1;
+        Ok(true)
     }
-}
 
-pub struct Day06;
+    fn snapshot(&self) -> usize {
+        self.path.len()
+    }
 
-impl Day06 {
-    fn parse_map(input: &str) -> (Vec<Vec<char>>, (usize, usize, Direction)) {
-        let map: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut start_pos = None;
-
-        for (i, row) in map.iter().enumerate() {
-            for (j, &cell) in row.iter().enumerate() {
-                if cell == '^' {
-                    start_pos = Some((i, j, Direction::Up));
-                }
+    fn restore(&mut self, &length: &usize) {
+        for state in self.path.drain(length..) {
+            self.states.remove(&state);
+            let visits = self.visits.get_mut(&state.0).unwrap();
+            *visits -= 1;
+            if *visits == 0 {
+                self.visits.remove(&state.0);
             }
         }
-
-        let start = start_pos.expect("No starting position found");
-        (map, start)
     }
 
-    fn simulate_guard(&self, input: &str) -> usize {
-        let (map, (start_row, start_col, start_dir)) = Self::parse_map(input);
-        let rows = map.len() as i32;
-        let cols = map[0].len() as i32;
-        let mut visited = HashSet::new();
-
-        let mut pos = (start_row as i32, start_col as i32);
-        let mut dir = start_dir;
-        visited.insert(pos);
+    fn render(&self) -> String {
+        let mut map = self.map.clone();
+        for pos in self.visits.keys() {
+            map[*pos] = 'X';
+        }
+        let (pos, dir) = *self.path.last().unwrap();
+        map[pos] = dir.arrow();
+        map.to_string()
+    }
 
-        loop {
-            let next_pos = dir.move_forward(pos);
+    fn status(&self) -> String {
+        let (pos, dir) = *self.path.last().unwrap();
+        format!(
+            "guard at {} facing {}, {} cells visited",
+            pos,
+            dir.arrow(),
+            self.visits.len()
+        )
+    }
+}
 
-            // Check if guard is out of bounds
-            if next_pos.0 < 0 || next_pos.0 >= rows || next_pos.1 < 0 || next_pos.1 >= cols {
-                break;
-            }
+/// A debugger on the guard's walk, see [`crate::debugger`]
+pub fn debugger(input: &str) -> Result<Box<dyn Session>, SolveError> {
+    Ok(Box::new(Debugger::new(Patrol::new(input)?)))
+}
 
-            // Check if there's an obstacle ahead
-            if map[next_pos.0 as usize][next_pos.1 as usize] == '#' {
-                dir = dir.turn_right();
-            } else {
-                pos = next_pos;
-                visited.insert(pos);
-            }
-        }
+impl Day06 {
+    fn parse_map(input: &str) -> Result<(Grid<char>, (Point, Direction)), SolveError> {
+        let map = parse::grid_with(input, |c| matches!(c, '.' | '#' | '^').then_some(c))?;
+        let start = map
+            .find(&'^')
+            .ok_or_else(|| SolveError::unsolvable("no guard on the map"))?;
+        Ok((map, (Point::from_grid(start), Direction::Up)))
+    }
 
-        visited.len()
+    fn simulate_guard(&self, input: &str) -> Result<usize, SolveError> {
+        let mut patrol = Patrol::new(input)?;
+        while patrol.step()? {}
+        Ok(patrol.visits.len())
     }
 
     fn simulate_with_obstruction(
         &self,
-        map: &[Vec<char>],
-        start: (usize, usize, Direction),
+        map: &Grid<char>,
+        (mut pos, mut dir): (Point, Direction),
     ) -> bool {
-        let rows = map.len() as i32;
-        let cols = map[0].len() as i32;
         let mut visited = HashMap::new();
 
-        let mut pos = (start.0 as i32, start.1 as i32);
-        let mut dir = start.2;
         visited.insert((pos, dir), 0);
         let mut steps = 0;
 
         loop {
-            let next_pos = dir.move_forward(pos);
+            let next_pos = pos.step(dir);
             steps += 1;
 
-            // Check if guard is out of bounds
-            if next_pos.0 < 0 || next_pos.0 >= rows || next_pos.1 < 0 || next_pos.1 >= cols {
-                return false;
-            }
-
-            // Check if there's an obstacle ahead
-            if map[next_pos.0 as usize][next_pos.1 as usize] == '#' {
-                dir = dir.turn_right();
-            } else {
-                pos = next_pos;
+            // Check if guard is out of bounds, or if there's an obstacle ahead
+            match map.get_point(next_pos) {
+                None => return false,
+                Some('#') => dir = dir.turn_right(),
+                Some(_) => pos = next_pos,
             }
 
             // Check if we've been in this position and direction before (loop detected)
@@ -117,37 +138,41 @@ impl Day06 {
         }
     }
 
-    fn find_loop_positions(&self, input: &str) -> usize {
-        let (map, start) = Self::parse_map(input);
+    fn find_loop_positions(&self, input: &str) -> Result<usize, SolveError> {
+        let (mut map, start) = Self::parse_map(input)?;
         let mut count = 0;
 
         // Try placing an obstruction at each empty position
-        for i in 0..map.len() {
-            for j in 0..map[0].len() {
-                if map[i][j] == '.' && (i != start.0 || j != start.1) {
-                    // Place obstruction
-                    let mut map_clone = map.clone();
-                    map_clone[i][j] = '#';
-
-                    // Simulate guard movement
-                    if self.simulate_with_obstruction(&map_clone, start) {
-                        count += 1;
-                    }
-                }
+        let candidates: Vec<_> = map
+            .iter()
+            .filter(|&(pos, &cell)| cell == '.' && Some(pos) != start.0.to_grid())
+            .map(|(pos, _)| pos)
+            .collect();
+
+        for pos in candidates {
+            // Place obstruction, simulate guard movement, then clear it again
+            map[pos] = '#';
+            if self.simulate_with_obstruction(&map, start) {
+                count += 1;
             }
+            map[pos] = '.';
         }
 
-        count
+        Ok(count)
     }
 }
 
 impl Solution for Day06 {
-    fn part1(&self, input: &str) -> String {
-        self.simulate_guard(input).to_string()
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Self::parse_map(input).map(drop))
     }
 
-    fn part2(&self, input: &str) -> String {
-        self.find_loop_positions(input).to_string()
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.simulate_guard(input)?.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.find_loop_positions(input)?.to_string())
     }
 }
 
@@ -184,4 +209,27 @@ mod tests {
 ......#...";
         assert_eq!(Day06.part2(input), "6");
     }
+
+    #[test]
+    fn test_guard_stuck_in_a_loop() {
+        let input = ".#...\n....#\n.....\n#^...\n...#.";
+        assert_eq!(
+            Day06.try_part1_with(input, &Params::new()),
+            Err(SolveError::unsolvable("the guard never leaves the map"))
+        );
+    }
+
+    #[test]
+    fn test_patrol_steps_back() {
+        let input = crate::read_input(6, true);
+        let mut patrol = Patrol::new(&input).unwrap();
+        let start = patrol.render();
+        while patrol.step().unwrap() {}
+        assert_eq!(patrol.visits.len(), 41);
+
+        patrol.restore(&1);
+        assert_eq!(patrol.visits.len(), 1);
+        assert_eq!(patrol.render(), start);
+        assert_eq!(patrol.status(), "guard at 4,6 facing ^, 1 cells visited");
+    }
 }
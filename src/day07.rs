@@ -1,104 +1,85 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 pub struct Day07;
 
-impl Solution for Day07 {
-    fn part1(&self, input: &str) -> String {
-        let sum: i64 = input
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() != 2 {
-                    return None;
-                }
-
-                let test_value: i64 = parts[0].trim().parse().ok()?;
-                let numbers: Vec<i64> = parts[1]
-                    .split_whitespace()
-                    .filter_map(|n| n.parse().ok())
-                    .collect();
-
-                if can_make_value(&numbers, test_value) {
-                    Some(test_value)
-                } else {
-                    None
-                }
-            })
-            .sum();
+/// Reads `target: a b c ...` lines
+fn parse_equations(input: &str) -> ParseResult<Vec<(u64, Vec<u64>)>> {
+    parse::parse_lines(input, |line| {
+        let target = line.int()?;
+        let numbers = line.literal(": ")?.list(" ", |l| l.int())?;
+        line.end()?;
+        Ok((target, numbers))
+    })
+}
 
-        sum.to_string()
+fn calibration_total(input: &str, operators: &[char]) -> Result<String, SolveError> {
+    let mut total: u128 = 0;
+    for (i, (target, numbers)) in parse_equations(input)?.into_iter().enumerate() {
+        let mut ops = vec![];
+        if try_combinations(&numbers, &mut ops, operators, target) {
+            crate::explain!(
+                "calibrated",
+                equation = i + 1,
+                target = target,
+                expression = expression(&numbers, &ops)
+            );
+            total += u128::from(target);
+        }
     }
+    Ok(total.to_string())
+}
 
-    fn part2(&self, input: &str) -> String {
-        let sum: i64 = input
-            .lines()
-            .filter_map(|line| {
-                let parts: Vec<&str> = line.split(':').collect();
-                if parts.len() != 2 {
-                    return None;
-                }
-
-                let test_value: i64 = parts[0].trim().parse().ok()?;
-                let numbers: Vec<i64> = parts[1]
-                    .split_whitespace()
-                    .filter_map(|n| n.parse().ok())
-                    .collect();
-
-                if can_make_value_part2(&numbers, test_value) {
-                    Some(test_value)
-                } else {
-                    None
-                }
-            })
-            .sum();
-
-        sum.to_string()
+/// The equation as the puzzle writes it, e.g. `6 * 8 || 6 * 15`
+fn expression(numbers: &[u64], ops: &[char]) -> String {
+    let mut expression = numbers[0].to_string();
+    for (op, n) in ops.iter().zip(&numbers[1..]) {
+        let op = match op {
+            'c' => "||",
+            '+' => "+",
+            _ => "*",
+        };
+        expression += &format!(" {} {}", op, n);
     }
+    expression
 }
 
-fn can_make_value(numbers: &[i64], target: i64) -> bool {
-    if numbers.len() == 1 {
-        return numbers[0] == target;
+impl Solution for Day07 {
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_equations(input).map(drop).map_err(Into::into))
     }
 
-    let operators = vec!['+', '*'];
-    try_combinations(numbers, &mut vec![], &operators, target)
-}
-
-fn can_make_value_part2(numbers: &[i64], target: i64) -> bool {
-    if numbers.len() == 1 {
-        return numbers[0] == target;
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        calibration_total(input, &['+', '*'])
     }
 
-    let operators = vec!['+', '*', 'c'];
-    try_combinations(numbers, &mut vec![], &operators, target)
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        calibration_total(input, &['+', '*', 'c'])
+    }
 }
 
 fn try_combinations(
-    numbers: &[i64],
+    numbers: &[u64],
     current_ops: &mut Vec<char>,
     operators: &[char],
-    target: i64,
+    target: u64,
 ) -> bool {
     if current_ops.len() == numbers.len() - 1 {
-        let mut result = numbers[0];
-        let mut i = 0;
-        while i < current_ops.len() {
-            if current_ops[i] == 'c' {
-                // Handle concatenation
-                let mut concat_str = result.to_string();
-                concat_str.push_str(&numbers[i + 1].to_string());
-                result = concat_str.parse().unwrap();
-            } else {
-                match current_ops[i] {
-                    '+' => result += numbers[i + 1],
-                    '*' => result *= numbers[i + 1],
-                    _ => unreachable!(),
-                }
-            }
-            i += 1;
-        }
-        return result == target;
+        // An overflowing result is certainly past the target
+        let result = current_ops.iter().zip(&numbers[1..]).try_fold(
+            numbers[0],
+            |result, (&op, &n)| match op {
+                '+' => result.checked_add(n),
+                '*' => result.checked_mul(n),
+                // Concatenation shifts the result left by n's digit count
+                'c' => 10u64
+                    .checked_pow(n.checked_ilog10().unwrap_or(0) + 1)
+                    .and_then(|shift| result.checked_mul(shift))
+                    .and_then(|shifted| shifted.checked_add(n)),
+                _ => unreachable!(),
+            },
+        );
+        return result == Some(target);
     }
 
     for &op in operators.iter() {
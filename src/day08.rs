@@ -1,53 +1,39 @@
-use crate::Solution;
+use crate::geometry::{Bounds, Point};
+use crate::math::gcd;
+use crate::snapshot::Snapshot;
+use crate::{Params, Solution, SolveError};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: i32,
-    y: i32,
-}
-
-impl Point {
-    fn new(x: i32, y: i32) -> Self {
-        Self { x, y }
-    }
-}
-
 pub struct Day08;
 
 impl Day08 {
-    fn print_debug_map(
+    /// The map with antinodes (#) over the original antennas
+    fn debug_map(
         antennas: &HashMap<char, Vec<Point>>,
         antinodes: &HashSet<Point>,
-        max_x: usize,
-        max_y: usize,
-    ) {
-        println!("\nMap with antinodes (#) and original antennas:");
-        for y in 0..=max_y {
-            for x in 0..=max_x {
-                let point = Point::new(x as i32, y as i32);
-                if antinodes.contains(&point) {
-                    print!("#");
-                } else {
-                    let mut found = false;
-                    for (ch, positions) in antennas {
-                        if positions.contains(&point) {
-                            print!("{}", ch);
-                            found = true;
-                            break;
-                        }
-                    }
-                    if !found {
-                        print!(".");
-                    }
-                }
+        bounds: Bounds,
+    ) -> String {
+        let mut map = String::from("Map with antinodes (#) and original antennas:");
+        for y in bounds.min.y..=bounds.max.y {
+            map.push('\n');
+            for x in bounds.min.x..=bounds.max.x {
+                let point = Point::new(x, y);
+                let antenna = antennas
+                    .iter()
+                    .find(|(_, positions)| positions.contains(&point))
+                    .map(|(&ch, _)| ch);
+                map.push(match antenna {
+                    _ if antinodes.contains(&point) => '#',
+                    Some(ch) => ch,
+                    None => '.',
+                });
             }
-            println!();
         }
-        println!();
+        map
     }
 
-    fn solve(&self, input: &str, is_part2: bool, debug: bool) -> String {
+    /// The antennas by frequency and the map's bounds
+    fn antennas(input: &str) -> (HashMap<char, Vec<Point>>, Bounds) {
         let mut antennas: HashMap<char, Vec<Point>> = HashMap::new();
         let mut max_x = 0;
         let mut max_y = 0;
@@ -65,7 +51,19 @@ impl Day08 {
             }
             max_y = max_y.max(y);
         }
+        let bounds = Bounds {
+            min: Point::new(0, 0),
+            max: Point::new(max_x as i32, max_y as i32),
+        };
+        (antennas, bounds)
+    }
 
+    /// The antennas by frequency, their antinodes and the map's bounds
+    fn antinodes(
+        input: &str,
+        is_part2: bool,
+    ) -> (HashMap<char, Vec<Point>>, HashSet<Point>, Bounds) {
+        let (antennas, bounds) = Self::antennas(input);
         let mut antinodes = HashSet::new();
 
         if is_part2 {
@@ -93,45 +91,30 @@ impl Day08 {
                     let a2 = positions[j];
 
                     // Calculate the difference vector
-                    let dx = a2.x - a1.x;
-                    let dy = a2.y - a1.y;
+                    let delta = a2 - a1;
 
                     if is_part2 {
-                        // Part 2: Try different values of k
-                        for k in -100..=100 {
-                            let antinode = Point::new(a1.x + k * dx, a1.y + k * dy);
-
-                            if antinode.x >= 0
-                                && antinode.x <= max_x as i32
-                                && antinode.y >= 0
-                                && antinode.y <= max_y as i32
-                            {
+                        // Part 2: Every grid point on the line through both antennas,
+                        // walking the smallest integer step along it both ways
+                        let divisor = gcd(delta.x as i64, delta.y as i64) as i32;
+                        let step = Point::new(delta.x / divisor, delta.y / divisor);
+                        for dir in [step, -step] {
+                            let mut antinode = a1;
+                            while bounds.contains(antinode) {
                                 antinodes.insert(antinode);
+                                antinode += dir;
                             }
                         }
                     } else {
                         // Part 1: Only check the two reflection points
-                        let antinode1 = Point::new(a1.x - dx, a1.y - dy);
-
-                        let antinode2 = Point::new(a2.x + dx, a2.y + dy);
+                        let antinode1 = a1 - delta;
+                        let antinode2 = a2 + delta;
 
-                        if antinode1.x >= 0
-                            && antinode1.x <= max_x as i32
-                            && antinode1.y >= 0
-                            && antinode1.y <= max_y as i32
-                            && antinode1 != a1
-                            && antinode1 != a2
-                        {
+                        if bounds.contains(antinode1) && antinode1 != a1 && antinode1 != a2 {
                             antinodes.insert(antinode1);
                         }
 
-                        if antinode2.x >= 0
-                            && antinode2.x <= max_x as i32
-                            && antinode2.y >= 0
-                            && antinode2.y <= max_y as i32
-                            && antinode2 != a1
-                            && antinode2 != a2
-                        {
+                        if bounds.contains(antinode2) && antinode2 != a1 && antinode2 != a2 {
                             antinodes.insert(antinode2);
                         }
                     }
@@ -139,22 +122,42 @@ impl Day08 {
             }
         }
 
-        if debug {
-            Self::print_debug_map(&antennas, &antinodes, max_x, max_y);
-        }
+        (antennas, antinodes, bounds)
+    }
 
+    fn solve(&self, input: &str, is_part2: bool) -> String {
+        let (antennas, antinodes, bounds) = Self::antinodes(input, is_part2);
+        crate::render(|| Self::debug_map(&antennas, &antinodes, bounds));
         antinodes.len().to_string()
     }
 }
 
 impl Solution for Day08 {
-    fn part1(&self, input: &str) -> String {
-        self.solve(input, false, false)
+    /// Any character other than `.` is an antenna, so every map is valid
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Self::antennas(input);
+        Some(Ok(()))
     }
 
-    fn part2(&self, input: &str) -> String {
-        self.solve(input, true, false)
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.solve(input, false))
     }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.solve(input, true))
+    }
+}
+
+/// The sample's map with each part's antinodes
+pub fn snapshots() -> Vec<Snapshot> {
+    let input = crate::read_input(8, true);
+    [(1, "antinodes"), (2, "resonant-antinodes")]
+        .into_iter()
+        .map(|(part, name)| {
+            let (antennas, antinodes, bounds) = Day08::antinodes(&input, part == 2);
+            Snapshot::new(8, name, Day08::debug_map(&antennas, &antinodes, bounds))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -166,13 +169,13 @@ mod tests {
     fn test_part1_sample() {
         let input = read_input(8, true);
         let day = Day08;
-        assert_eq!(day.solve(&input, false, true), "14");
+        assert_eq!(day.solve(&input, false), "14");
     }
 
     #[test]
     fn test_part2_sample() {
         let input = read_input(8, true);
         let day = Day08;
-        assert_eq!(day.solve(&input, true, true), "34");
+        assert_eq!(day.solve(&input, true), "34");
     }
 }
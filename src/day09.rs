@@ -1,4 +1,5 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 #[derive(Debug, Clone)]
 struct File {
@@ -12,16 +13,21 @@ struct DiskMap {
 }
 
 impl DiskMap {
-    fn from_string(input: &str) -> Self {
+    /// Reads a single line of digits, alternating file and free space lengths
+    fn from_string(input: &str) -> ParseResult<Self> {
         let mut current_pos = 0;
         let mut file_id = 0;
         let mut files = Vec::new();
         let mut disk = Vec::new();
 
-        let numbers: Vec<usize> = input
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as usize)
-            .collect();
+        let numbers = parse::parse_lines(input, |line| {
+            let mut digits = Vec::new();
+            while !line.remaining().trim_end().is_empty() {
+                digits.push(line.digit()? as usize);
+            }
+            Ok(digits)
+        })?
+        .concat();
 
         for (i, &length) in numbers.iter().enumerate() {
             if i % 2 == 0 {
@@ -42,7 +48,7 @@ impl DiskMap {
             current_pos += length;
         }
 
-        DiskMap { files, disk }
+        Ok(DiskMap { files, disk })
     }
 
     fn move_block_left(&mut self) -> bool {
@@ -72,8 +78,11 @@ impl DiskMap {
     }
 
     fn move_file_left(&mut self, file_id: usize) -> bool {
+        // Empty files have nowhere to go
+        let Some(&file_start) = self.files[file_id].blocks.first() else {
+            return false;
+        };
         let file_size = self.files[file_id].blocks.len();
-        let file_start = self.files[file_id].blocks[0];
 
         // Find leftmost span of free space that can fit the file
         let mut current_span = 0;
@@ -126,24 +135,28 @@ impl DiskMap {
 pub struct Day09;
 
 impl Solution for Day09 {
-    fn part1(&self, input: &str) -> String {
-        let mut disk_map = DiskMap::from_string(input.trim());
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(DiskMap::from_string(input).map(drop).map_err(Into::into))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let mut disk_map = DiskMap::from_string(input)?;
 
         // Keep moving blocks until no more moves are possible
         while disk_map.move_block_left() {}
 
-        disk_map.calculate_checksum().to_string()
+        Ok(disk_map.calculate_checksum().to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let mut disk_map = DiskMap::from_string(input.trim());
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let mut disk_map = DiskMap::from_string(input)?;
 
         // Move files from highest ID to lowest
         for file_id in (0..disk_map.files.len()).rev() {
             disk_map.move_file_left(file_id);
         }
 
-        disk_map.calculate_checksum().to_string()
+        Ok(disk_map.calculate_checksum().to_string())
     }
 }
 
@@ -162,4 +175,14 @@ mod tests {
         let input = "2333133121414131402";
         assert_eq!(Day09.part2(input), "2858");
     }
+
+    #[test]
+    fn test_invalid_disk_maps() {
+        let err = Day09.try_part1_with("23x3", &Params::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid input at line 1, column 3: expected a digit, found 'x'"
+        );
+        assert_eq!(Day09.part2("1001"), Day09.part1("1001"));
+    }
 }
@@ -1,145 +1,86 @@
-use crate::Solution;
-use std::collections::{HashSet, VecDeque};
+use crate::grid::Grid;
+use crate::parse::{self, ParseResult};
+use crate::pathfinding::{bfs_distances, dijkstra_all};
+use crate::{Params, Solution, SolveError};
 
 #[derive(Debug, Clone)]
-struct Grid {
-    heights: Vec<Vec<u32>>,
-    rows: usize,
-    cols: usize,
+struct TopoMap {
+    heights: Grid<u32>,
 }
 
-impl Grid {
-    fn from_string(input: &str) -> Self {
-        let heights: Vec<Vec<u32>> = input
-            .lines()
-            .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
-            .collect();
-        let rows = heights.len();
-        let cols = heights[0].len();
-        Grid {
-            heights,
-            rows,
-            cols,
-        }
+impl TopoMap {
+    fn from_string(input: &str) -> ParseResult<Self> {
+        let heights = parse::grid_with(input, |c| c.to_digit(10))?;
+        Ok(TopoMap { heights })
     }
 
-    fn get_neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::new();
-        let current_height = self.heights[row][col];
-        let target_height = current_height + 1;
-
-        // Check all four directions
-        if row > 0 && self.heights[row - 1][col] == target_height {
-            neighbors.push((row - 1, col));
-        }
-        if row + 1 < self.rows && self.heights[row + 1][col] == target_height {
-            neighbors.push((row + 1, col));
-        }
-        if col > 0 && self.heights[row][col - 1] == target_height {
-            neighbors.push((row, col - 1));
-        }
-        if col + 1 < self.cols && self.heights[row][col + 1] == target_height {
-            neighbors.push((row, col + 1));
-        }
+    fn get_neighbors(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let target_height = self.heights[pos] + 1;
+        self.heights
+            .neighbors4(pos)
+            .filter(move |&next| self.heights[next] == target_height)
+    }
 
-        neighbors
+    fn trailheads(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.heights
+            .iter()
+            .filter(|&(_, &height)| height == 0)
+            .map(|(pos, _)| pos)
     }
 
-    fn find_trailhead_score(&self, start_row: usize, start_col: usize) -> usize {
-        if self.heights[start_row][start_col] != 0 {
+    /// Number of distinct height-9 positions reachable from a trailhead
+    fn find_trailhead_score(&self, start: (usize, usize)) -> usize {
+        if self.heights[start] != 0 {
             return 0;
         }
 
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-        let mut reachable_nines = HashSet::new();
-
-        queue.push_back((start_row, start_col));
-        visited.insert((start_row, start_col));
-
-        while let Some((row, col)) = queue.pop_front() {
-            if self.heights[row][col] == 9 {
-                reachable_nines.insert((row, col));
-                continue;
-            }
-
-            for (next_row, next_col) in self.get_neighbors(row, col) {
-                if !visited.contains(&(next_row, next_col)) {
-                    visited.insert((next_row, next_col));
-                    queue.push_back((next_row, next_col));
-                }
-            }
-        }
-
-        reachable_nines.len()
+        bfs_distances(start, |&pos| self.get_neighbors(pos))
+            .keys()
+            .filter(|&&pos| self.heights[pos] == 9)
+            .count()
     }
 
-    fn find_distinct_paths(&self, start_row: usize, start_col: usize) -> usize {
-        if self.heights[start_row][start_col] != 0 {
+    /// Number of distinct hiking trails from a trailhead to any height-9 position.
+    /// Every trail climbs one level per step, so they are all shortest paths.
+    fn find_distinct_paths(&self, start: (usize, usize)) -> usize {
+        if self.heights[start] != 0 {
             return 0;
         }
 
-        let mut paths = HashSet::new();
-        let mut current_path = Vec::new();
-        current_path.push((start_row, start_col));
-
-        fn dfs(
-            grid: &Grid,
-            row: usize,
-            col: usize,
-            current_path: &mut Vec<(usize, usize)>,
-            paths: &mut HashSet<Vec<(usize, usize)>>,
-        ) {
-            if grid.heights[row][col] == 9 {
-                paths.insert(current_path.clone());
-                return;
-            }
-
-            for (next_row, next_col) in grid.get_neighbors(row, col) {
-                if !current_path.contains(&(next_row, next_col)) {
-                    current_path.push((next_row, next_col));
-                    dfs(grid, next_row, next_col, current_path, paths);
-                    current_path.pop();
-                }
-            }
-        }
-
-        dfs(self, start_row, start_col, &mut current_path, &mut paths);
-        paths.len()
+        dijkstra_all(
+            start,
+            |&pos| self.get_neighbors(pos).map(|next| (next, 1)),
+            |&pos| self.heights[pos] == 9,
+        )
+        .map_or(0, |(paths, _)| paths.count_paths())
     }
 }
 
 pub struct Day10;
 
 impl Solution for Day10 {
-    fn part1(&self, input: &str) -> String {
-        let grid = Grid::from_string(input);
-        let mut total_score = 0;
-
-        for row in 0..grid.rows {
-            for col in 0..grid.cols {
-                if grid.heights[row][col] == 0 {
-                    total_score += grid.find_trailhead_score(row, col);
-                }
-            }
-        }
-
-        total_score.to_string()
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(TopoMap::from_string(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        let grid = Grid::from_string(input);
-        let mut total_rating = 0;
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let map = TopoMap::from_string(input)?;
+        let total_score: usize = map
+            .trailheads()
+            .map(|start| map.find_trailhead_score(start))
+            .sum();
 
-        for row in 0..grid.rows {
-            for col in 0..grid.cols {
-                if grid.heights[row][col] == 0 {
-                    total_rating += grid.find_distinct_paths(row, col);
-                }
-            }
-        }
+        Ok(total_score.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let map = TopoMap::from_string(input)?;
+        let total_rating: usize = map
+            .trailheads()
+            .map(|start| map.find_distinct_paths(start))
+            .sum();
 
-        total_rating.to_string()
+        Ok(total_rating.to_string())
     }
 }
 
@@ -1,57 +1,86 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{ParamSpec, Params, Solution, SolveError};
 use std::collections::HashMap;
 
 pub struct Day11;
 
+/// Reads the space separated stone numbers, on any number of lines
+fn parse_stones(input: &str) -> ParseResult<Vec<u64>> {
+    let lines = parse::parse_lines(input, |line| {
+        let stones = line.list(" ", |l| l.int::<u64>())?;
+        line.end()?;
+        Ok(stones)
+    })?;
+    Ok(lines.concat())
+}
+
 impl Day11 {
-    fn solve(&self, input: &str, blinks: usize) -> String {
+    fn solve(&self, input: &str, blinks: usize) -> Result<String, SolveError> {
+        let overflow = || SolveError::unsolvable("stone numbers or counts overflowed 64 bits");
+
         // Initialize the frequency map from input
         let mut freq_map: HashMap<u64, u64> = HashMap::new();
-        for num in input.split_whitespace() {
-            let n = num.parse().unwrap();
+        for n in parse_stones(input)? {
             *freq_map.entry(n).or_insert(0) += 1;
         }
 
         // Simulate blinks
         for _ in 0..blinks {
             let mut new_map: HashMap<u64, u64> = HashMap::new();
+            let mut add = |num: u64, count: u64| {
+                let entry = new_map.entry(num).or_insert(0);
+                *entry = entry.checked_add(count).ok_or_else(overflow)?;
+                Ok::<_, SolveError>(())
+            };
 
-            for (num, count) in freq_map.iter() {
-                if *num == 0 {
+            for (&num, &count) in freq_map.iter() {
+                if num == 0 {
                     // Rule 1: 0 becomes 1
-                    *new_map.entry(1).or_insert(0) += count;
+                    add(1, count)?;
                     continue;
                 }
 
                 // Rule 2: Check if number has even number of digits
-                let num_str = num.to_string();
-                let digit_count = num_str.len();
+                let digit_count = num.ilog10() + 1;
                 if digit_count % 2 == 0 {
-                    let mid = digit_count / 2;
-                    let left = num_str[..mid].parse::<u64>().unwrap();
-                    let right = num_str[mid..].parse::<u64>().unwrap();
-                    *new_map.entry(left).or_insert(0) += count;
-                    *new_map.entry(right).or_insert(0) += count;
+                    let half = 10u64.pow(digit_count / 2);
+                    add(num / half, count)?;
+                    add(num % half, count)?;
                 } else {
                     // Rule 3: Multiply by 2024
-                    *new_map.entry(num * 2024).or_insert(0) += count;
+                    add(num.checked_mul(2024).ok_or_else(overflow)?, count)?;
                 }
             }
             freq_map = new_map;
         }
 
         // Sum all frequencies to get total count
-        freq_map.values().sum::<u64>().to_string()
+        freq_map
+            .values()
+            .try_fold(0u64, |total, &count| total.checked_add(count))
+            .map(|total| total.to_string())
+            .ok_or_else(overflow)
     }
 }
 
 impl Solution for Day11 {
-    fn part1(&self, input: &str) -> String {
-        self.solve(input, 25)
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_stones(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        self.solve(input, 75)
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("part1_blinks", "25", "Number of blinks for part 1"),
+            ParamSpec::new("part2_blinks", "75", "Number of blinks for part 2"),
+        ]
+    }
+
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(input, params.get("part1_blinks")?)
+    }
+
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(input, params.get("part2_blinks")?)
     }
 }
 
@@ -64,4 +93,11 @@ mod tests {
         let input = "125 17";
         assert_eq!(Day11.part1(input), "55312");
     }
+
+    #[test]
+    fn test_part1_sample_six_blinks() {
+        let input = "125 17";
+        let params = Day11.default_params().with("part1_blinks", 6);
+        assert_eq!(Day11.part1_with(input, &params), "22");
+    }
 }
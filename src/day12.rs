@@ -1,46 +1,48 @@
-use crate::Solution;
+use crate::geometry::{Bounds, Point};
+use crate::grid::Grid;
+use crate::parse;
+use crate::{Params, Solution, SolveError};
 use std::collections::{HashSet, VecDeque};
-
-static DEBUG: bool = false;
+use std::ops::RangeInclusive;
 
 pub struct Day12;
 
+/// A picture of the cells in `rows` × `cols`, one line per row
+fn picture(
+    rows: RangeInclusive<usize>,
+    cols: RangeInclusive<usize>,
+    cell: impl Fn((usize, usize)) -> char,
+) -> String {
+    rows.map(|r| cols.clone().map(|c| cell((r, c))).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl Day12 {
-    fn find_regions(&self, grid: &[Vec<char>]) -> Vec<(char, HashSet<(usize, usize)>)> {
-        let rows = grid.len();
-        let cols = grid[0].len();
+    fn find_regions(&self, grid: &Grid<char>) -> Vec<(char, HashSet<(usize, usize)>)> {
         let mut visited = HashSet::new();
         let mut regions = Vec::new();
 
         // Helper function for BFS
         fn bfs(
-            grid: &[Vec<char>],
+            grid: &Grid<char>,
             start: (usize, usize),
             visited: &mut HashSet<(usize, usize)>,
         ) -> HashSet<(usize, usize)> {
-            let rows = grid.len();
-            let cols = grid[0].len();
-            let plant_type = grid[start.0][start.1];
+            let plant_type = grid[start];
             let mut region = HashSet::new();
             let mut queue = VecDeque::new();
             queue.push_back(start);
             visited.insert(start);
             region.insert(start);
 
-            while let Some((r, c)) = queue.pop_front() {
+            while let Some(pos) = queue.pop_front() {
                 // Check all 4 directions
-                for (dr, dc) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
-                    let new_r = r as i32 + dr;
-                    let new_c = c as i32 + dc;
-
-                    if new_r >= 0 && new_r < rows as i32 && new_c >= 0 && new_c < cols as i32 {
-                        let new_r = new_r as usize;
-                        let new_c = new_c as usize;
-                        if !visited.contains(&(new_r, new_c)) && grid[new_r][new_c] == plant_type {
-                            queue.push_back((new_r, new_c));
-                            visited.insert((new_r, new_c));
-                            region.insert((new_r, new_c));
-                        }
+                for next in grid.neighbors4(pos) {
+                    if !visited.contains(&next) && grid[next] == plant_type {
+                        queue.push_back(next);
+                        visited.insert(next);
+                        region.insert(next);
                     }
                 }
             }
@@ -48,16 +50,10 @@ impl Day12 {
         }
 
         // Find all regions
-        for r in 0..rows {
-            for c in 0..cols {
-                if !visited.contains(&(r, c)) {
-                    let region = bfs(grid, (r, c), &mut visited);
-                    if DEBUG {
-                        println!("\nFound region of type '{}' at ({}, {})", grid[r][c], r, c);
-                        println!("Region coordinates: {:?}", region);
-                    }
-                    regions.push((grid[r][c], region));
-                }
+        for (r, c) in grid.positions() {
+            if !visited.contains(&(r, c)) {
+                let region = bfs(grid, (r, c), &mut visited);
+                regions.push((grid[(r, c)], region));
             }
         }
 
@@ -77,32 +73,27 @@ impl Day12 {
                 }
             }
         }
-        if DEBUG {
-            println!("Region perimeter: {}", perimeter);
-        }
         perimeter
     }
 
     fn count_holes(&self, region: &HashSet<(usize, usize)>) -> usize {
-        if region.is_empty() {
-            return 0;
-        }
-
         // Find bounds of the region
-        let min_r = region.iter().map(|&(r, _)| r).min().unwrap();
-        let max_r = region.iter().map(|&(r, _)| r).max().unwrap();
-        let min_c = region.iter().map(|&(_, c)| c).min().unwrap();
-        let max_c = region.iter().map(|&(_, c)| c).max().unwrap();
-
-        if DEBUG {
-            println!("\nChecking for holes in region:");
-            for r in min_r..=max_r {
-                for c in min_c..=max_c {
-                    print!("{}", if region.contains(&(r, c)) { "█" } else { "." });
+        let Some(bounds) = Bounds::of(region.iter().map(|&pos| Point::from_grid(pos))) else {
+            return 0;
+        };
+        let (min_r, max_r) = (bounds.min.y as usize, bounds.max.y as usize);
+        let (min_c, max_c) = (bounds.min.x as usize, bounds.max.x as usize);
+
+        crate::render(|| {
+            let cells = picture(min_r..=max_r, min_c..=max_c, |pos| {
+                if region.contains(&pos) {
+                    '█'
+                } else {
+                    '.'
                 }
-                println!();
-            }
-        }
+            });
+            format!("Checking for holes in region:\n{}", cells)
+        });
 
         // Create a set of all empty spaces
         let empty_spaces: HashSet<(usize, usize)> = (min_r..=max_r)
@@ -206,24 +197,19 @@ impl Day12 {
             hole_count += 1;
         }
 
-        if DEBUG {
-            println!("Total holes found: {}", hole_count);
-            // Print the region with holes marked
-            let mut hole_groups = vec![vec!['.'; max_c - min_c + 1]; max_r - min_r + 1];
-            for r in min_r..=max_r {
-                for c in min_c..=max_c {
-                    let pos = (r, c);
-                    if region.contains(&pos) {
-                        hole_groups[r - min_r][c - min_c] = '█';
-                    } else if unreachable.contains(&pos) {
-                        hole_groups[r - min_r][c - min_c] = 'O';
-                    }
+        // The region with its holes marked
+        crate::render(|| {
+            let cells = picture(min_r..=max_r, min_c..=max_c, |pos| {
+                if region.contains(&pos) {
+                    '█'
+                } else if unreachable.contains(&pos) {
+                    'O'
+                } else {
+                    '.'
                 }
-            }
-            for row in hole_groups {
-                println!("{}", row.iter().collect::<String>());
-            }
-        }
+            });
+            format!("Total holes found: {}\n{}", hole_count, cells)
+        });
 
         hole_count
     }
@@ -231,9 +217,6 @@ impl Day12 {
     fn calculate_sides(&self, region: &HashSet<(usize, usize)>) -> usize {
         // If this is a single cell region, just return 4 sides
         if region.len() == 1 {
-            if DEBUG {
-                println!("Single cell region - 4 sides");
-            }
             return 4;
         }
 
@@ -245,15 +228,16 @@ impl Day12 {
 
         let mut inside_corners = 0;
 
-        if DEBUG {
-            println!("\nChecking for inside corners in region:");
-            for r in min_r..=max_r {
-                for c in min_c..=max_c {
-                    print!("{}", if region.contains(&(r, c)) { "█" } else { "." });
+        crate::render(|| {
+            let cells = picture(min_r..=max_r, min_c..=max_c, |pos| {
+                if region.contains(&pos) {
+                    '█'
+                } else {
+                    '.'
                 }
-                println!();
-            }
-        }
+            });
+            format!("Checking for inside corners in region:\n{}", cells)
+        });
 
         // Look at each potential 2x2 region
         for r in min_r..max_r {
@@ -268,24 +252,16 @@ impl Day12 {
 
                 // If exactly 3 cells are in the region, it's an inside corner
                 if count == 3 {
-                    if DEBUG {
-                        println!("Found inside corner at ({}, {})", r, c);
-                        println!("2x2 region:");
-                        for dr in 0..2 {
-                            for dc in 0..2 {
-                                print!(
-                                    "{}",
-                                    if region.contains(&(r + dr, c + dc)) {
-                                        "O"
-                                    } else {
-                                        "."
-                                    }
-                                );
+                    crate::render(|| {
+                        let cells = picture(r..=r + 1, c..=c + 1, |pos| {
+                            if region.contains(&pos) {
+                                'O'
+                            } else {
+                                '.'
                             }
-                            println!();
-                        }
-                        println!();
-                    }
+                        });
+                        format!("Found inside corner at ({}, {}):\n{}", r, c, cells)
+                    });
                     inside_corners += 1;
                 }
             }
@@ -295,71 +271,69 @@ impl Day12 {
         let holes = self.count_holes(region);
 
         // Base 4 sides plus 2 for each inside corner, minus 4 for each hole
-        let result = 4 + (inside_corners * 2) - (holes * 4);
-
-        if DEBUG {
-            println!("Final calculation:");
-            println!("  Base sides: 4");
-            println!(
-                "  Inside corners: {} (+{})",
-                inside_corners,
-                inside_corners * 2
-            );
-            println!("  Holes: {} (-{})", holes, holes * 4);
-            println!("  Total sides: {}", result);
-        }
-
-        result
+        4 + (inside_corners * 2) - (holes * 4)
     }
 
-    fn part1(&self, input: &str) -> String {
-        // Parse input into grid
-        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
+    fn fencing_price(&self, grid: &Grid<char>) -> usize {
         // Find all regions
-        let regions = self.find_regions(&grid);
+        let regions = self.find_regions(grid);
 
         // Calculate total price
         let total_price: usize = regions
             .iter()
-            .map(|(_, region)| {
+            .map(|(plant, region)| {
                 let area = region.len();
                 let perimeter = self.calculate_perimeter(region);
+                crate::explain!(
+                    "region",
+                    plant = plant,
+                    at = region.iter().min(),
+                    area = area,
+                    perimeter = perimeter
+                );
                 area * perimeter
             })
             .sum();
 
-        total_price.to_string()
+        total_price
     }
 
-    fn part2(&self, input: &str) -> String {
-        // Parse input into grid
-        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
+    fn bulk_fencing_price(&self, grid: &Grid<char>) -> usize {
         // Find all regions
-        let regions = self.find_regions(&grid);
+        let regions = self.find_regions(grid);
 
         // Calculate total price using sides instead of perimeter
         let total_price: usize = regions
             .iter()
-            .map(|(_, region)| {
+            .map(|(plant, region)| {
                 let area = region.len();
                 let sides = self.calculate_sides(region);
+                crate::explain!(
+                    "region",
+                    plant = plant,
+                    at = region.iter().min(),
+                    area = area,
+                    sides = sides
+                );
                 area * sides
             })
             .sum();
 
-        total_price.to_string()
+        total_price
     }
 }
 
 impl Solution for Day12 {
-    fn part1(&self, input: &str) -> String {
-        self.part1(input)
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse::grid(input).map(drop).map_err(Into::into))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.fencing_price(&parse::grid(input)?).to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        self.part2(input)
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        Ok(self.bulk_fencing_price(&parse::grid(input)?).to_string())
     }
 }
 
@@ -1,4 +1,6 @@
-use crate::Solution;
+use crate::math::{egcd, solve_non_negative_integer};
+use crate::parse::{self, Block, Line, ParseResult};
+use crate::{ParamSpec, Params, Solution, SolveError};
 
 #[derive(Debug)]
 struct ClawMachine {
@@ -8,177 +10,302 @@ struct ClawMachine {
 }
 
 impl ClawMachine {
-    fn parse(input: &str) -> Self {
-        let mut lines = input.lines();
-
-        let a_line = lines.next().unwrap();
-        let b_line = lines.next().unwrap();
-        let prize_line = lines.next().unwrap();
-
-        let parse_coords = |s: &str| {
-            let parts: Vec<&str> = s.split(", ").collect();
-            let x = parts[0]
-                .split(['+', '='])
-                .last()
-                .unwrap()
-                .trim_start_matches('X')
-                .parse()
-                .unwrap();
-            let y = parts[1]
-                .split(['+', '='])
-                .last()
-                .unwrap()
-                .trim_start_matches('Y')
-                .parse()
-                .unwrap();
-            (x, y)
-        };
-
-        ClawMachine {
-            button_a: parse_coords(a_line),
-            button_b: parse_coords(b_line),
-            prize: parse_coords(prize_line),
-        }
+    fn parse(block: &Block) -> ParseResult<Self> {
+        let [a_line, b_line, prize_line] = block.exact_lines()?;
+        Ok(ClawMachine {
+            button_a: Self::parse_coords(a_line, "Button A: ", "+")?,
+            button_b: Self::parse_coords(b_line, "Button B: ", "+")?,
+            prize: Self::parse_coords(prize_line, "Prize: ", "=")?,
+        })
     }
 
-    fn can_reach_prize(&self) -> Option<u32> {
-        println!("\nAnalyzing machine:");
-        println!(
-            "Button A moves: X{:+}, Y{:+}",
-            self.button_a.0, self.button_a.1
-        );
-        println!(
-            "Button B moves: X{:+}, Y{:+}",
-            self.button_b.0, self.button_b.1
-        );
-        println!("Prize at: X={}, Y={}", self.prize.0, self.prize.1);
+    /// Reads `<label>X<sign>n, Y<sign>n`. Coordinates are read as `i32`, which
+    /// keeps the exact solver's arithmetic well within `i128`.
+    fn parse_coords(mut line: Line, label: &str, sign: &str) -> ParseResult<(i64, i64)> {
+        let x: i32 = line.literal(label)?.literal("X")?.literal(sign)?.int()?;
+        let y: i32 = line.literal(", Y")?.literal(sign)?.int()?;
+        line.end()?;
+        Ok((x.into(), y.into()))
+    }
 
-        let mut min_tokens = None;
-        let mut solutions_found = 0;
+    fn parse_all(input: &str) -> ParseResult<Vec<Self>> {
+        parse::blocks(input).iter().map(Self::parse).collect()
+    }
 
-        for a in 0..=100 {
-            for b in 0..=100 {
+    /// The cheapest presses of A and B, each at most `max_presses`, that
+    /// land on the prize
+    fn can_reach_prize(&self, max_presses: u32) -> Option<(u64, u64)> {
+        let mut cheapest: Option<(u64, u64)> = None;
+        for a in 0..=max_presses {
+            for b in 0..=max_presses {
                 let x = a as i64 * self.button_a.0 + b as i64 * self.button_b.0;
                 let y = a as i64 * self.button_a.1 + b as i64 * self.button_b.1;
 
                 if x == self.prize.0 && y == self.prize.1 {
-                    let tokens = (3 * a + b) as u32;
-                    solutions_found += 1;
-                    println!(
-                        "Solution found! Press A {} times and B {} times for {} tokens",
-                        a, b, tokens
-                    );
-                    min_tokens =
-                        Some(min_tokens.map_or(tokens, |current: u32| current.min(tokens)));
+                    let presses = (a.into(), b.into());
+                    if cheapest.is_none_or(|c| tokens(presses) < tokens(c)) {
+                        cheapest = Some(presses);
+                    }
                 }
             }
         }
-
-        if let Some(min) = min_tokens {
-            println!(
-                "Found {} solutions. Minimum tokens needed: {}",
-                solutions_found, min
-            );
-        } else {
-            println!("No solution found - prize cannot be reached!");
-        }
-
-        min_tokens
+        cheapest
     }
 
-    fn can_reach_prize_part2(&self) -> Option<u64> {
-        const OFFSET: i128 = 10000000000000;
-        let prize_x = self.prize.0 as i128 + OFFSET;
-        let prize_y = self.prize.1 as i128 + OFFSET;
+    /// The cheapest presses of A and B, each at most `limit` if given, that
+    /// land exactly on the prize once it has been moved by `offset`
+    fn solve(&self, offset: i64, limit: Option<u64>) -> Option<(u64, u64)> {
+        let prize = (
+            self.prize.0 as i128 + offset as i128,
+            self.prize.1 as i128 + offset as i128,
+        );
+        let (ax, ay) = (self.button_a.0 as i128, self.button_a.1 as i128);
+        let (bx, by) = (self.button_b.0 as i128, self.button_b.1 as i128);
 
-        let button_a_x = self.button_a.0 as i128;
-        let button_a_y = self.button_a.1 as i128;
-        let button_b_x = self.button_b.0 as i128;
-        let button_b_y = self.button_b.1 as i128;
+        let presses = if ax * by == ay * bx {
+            // Both buttons move along one line, so the presses aren't unique.
+            // Solve along an axis the line isn't flat in, then check the other.
+            let (a, b) = if ax != 0 || bx != 0 {
+                presses_along_line(ax, bx, prize.0, limit)?
+            } else {
+                presses_along_line(ay, by, prize.1, limit)?
+            };
+            (a * ax + b * bx == prize.0 && a * ay + b * by == prize.1).then_some((a, b))?
+        } else {
+            // a * button_a + b * button_b = prize, one equation per axis
+            let system = [vec![ax, bx], vec![ay, by]];
+            let presses = solve_non_negative_integer(&system, &[prize.0, prize.1])?;
+            (presses[0], presses[1])
+        };
 
-        let det = button_a_x * button_b_y - button_a_y * button_b_x;
-        if det == 0 {
-            return None; // No unique solution
+        let presses = (
+            u64::try_from(presses.0).ok()?,
+            u64::try_from(presses.1).ok()?,
+        );
+        match limit {
+            Some(limit) if presses.0 > limit || presses.1 > limit => None,
+            _ => Some(presses),
         }
+    }
+}
 
-        let det_a = prize_x * button_b_y - button_b_x * prize_y;
-        let det_b = button_a_x * prize_y - prize_x * button_a_y;
+/// The cheapest non-negative `(a, b)`, each at most `limit` if given, with
+/// `a * u + b * v == w`
+fn presses_along_line(u: i128, v: i128, w: i128, limit: Option<u64>) -> Option<(i128, i128)> {
+    if u == 0 && v == 0 {
+        return (w == 0).then_some((0, 0));
+    }
+    let (g, x, y) = egcd(i64::try_from(u).ok()?, i64::try_from(v).ok()?);
+    let g = g as i128;
+    if w % g != 0 {
+        return None;
+    }
 
-        // Check if we have integer solutions
-        if det_a % det != 0 || det_b % det != 0 {
-            return None;
-        }
+    // Every solution is (a0 + k * da, b0 + k * db) for some integer k. The
+    // tokens change linearly with k, so the cheapest is at one end of the
+    // range of k keeping both counts in bounds.
+    let (a0, b0) = (x as i128 * (w / g), y as i128 * (w / g));
+    let (da, db) = (v / g, -u / g);
+    let max = limit.map_or(i128::MAX, i128::from);
+    let mut range = (i128::MIN, i128::MAX);
+    keep_within(&mut range, a0, da, max);
+    keep_within(&mut range, b0, db, max);
+    let (lo, hi) = range;
+    if lo > hi {
+        return None;
+    }
 
-        let a = det_a / det;
-        let b = det_b / det;
+    [lo, hi]
+        .into_iter()
+        .filter(|&k| k != i128::MIN && k != i128::MAX)
+        .map(|k| (a0 + k * da, b0 + k * db))
+        .min_by_key(|&(a, b)| 3 * a + b)
+}
 
-        // Check if solution is valid (positive numbers)
-        if a < 0 || b < 0 {
-            return None;
+/// Narrows the range of `k` to where `0 <= start + k * step <= max`
+fn keep_within((lo, hi): &mut (i128, i128), start: i128, step: i128, max: i128) {
+    // Floor and ceiling of `n / d` for positive `d`
+    let floor = |n: i128, d: i128| n.div_euclid(d);
+    let ceil = |n: i128, d: i128| -(-n).div_euclid(d);
+    match step.signum() {
+        1 => {
+            *lo = (*lo).max(ceil(-start, step));
+            if max != i128::MAX {
+                *hi = (*hi).min(floor(max - start, step));
+            }
+        }
+        -1 => {
+            *hi = (*hi).min(floor(start, -step));
+            if max != i128::MAX {
+                *lo = (*lo).max(ceil(start - max, -step));
+            }
         }
+        _ if start < 0 || start > max => *hi = i128::MIN,
+        _ => {}
+    }
+}
 
-        // Verify solution
-        let x = a * button_a_x + b * button_b_x;
-        let y = a * button_a_y + b * button_b_y;
+fn tokens((a, b): (u64, u64)) -> u64 {
+    3 * a + b
+}
 
-        if x == prize_x && y == prize_y {
-            // Calculate tokens using i128 to avoid overflow
-            let tokens = 3i128 * a + b;
-            if tokens > u64::MAX as i128 {
-                None // Token count too large
-            } else {
-                Some(tokens as u64)
+/// The tokens spent on each machine, explaining the presses behind them
+fn total_tokens(presses: impl Iterator<Item = Option<(u64, u64)>>) -> u64 {
+    let mut total = 0;
+    for (i, presses) in presses.enumerate() {
+        match presses {
+            Some((a, b)) => {
+                let spent = tokens((a, b));
+                crate::explain!("won", machine = i + 1, a = a, b = b, tokens = spent);
+                total += spent;
             }
-        } else {
-            None
+            None => crate::explain!("unreachable", machine = i + 1),
         }
     }
+    total
+}
+
+fn params() -> Vec<ParamSpec> {
+    vec![
+        ParamSpec::new("max_presses", "100", "Maximum presses per button in part 1"),
+        ParamSpec::new(
+            "offset",
+            "10000000000000",
+            "Amount added to each prize coordinate in part 2",
+        ),
+    ]
 }
 
+/// Solves each machine's 2x2 system directly
 pub struct Day13;
 
 impl Solution for Day13 {
-    fn part1(&self, input: &str) -> String {
-        let machines: Vec<ClawMachine> = input
-            .split("\r\n\r\n")
-            .flat_map(|s| s.split("\n\n"))
-            .map(ClawMachine::parse)
-            .collect();
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(ClawMachine::parse_all(input).map(drop).map_err(Into::into))
+    }
 
-        let total_tokens: u32 = machines.iter().filter_map(|m| m.can_reach_prize()).sum();
+    fn params(&self) -> Vec<ParamSpec> {
+        params()
+    }
 
-        total_tokens.to_string()
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let max_presses: u64 = params.get("max_presses")?;
+        let machines = ClawMachine::parse_all(input)?;
+        let presses = machines.iter().map(|m| m.solve(0, Some(max_presses)));
+        Ok(total_tokens(presses).to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let machines: Vec<ClawMachine> = input
-            .split("\r\n\r\n")
-            .flat_map(|s| s.split("\n\n"))
-            .map(ClawMachine::parse)
-            .collect();
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let offset = params.get("offset")?;
+        let machines = ClawMachine::parse_all(input)?;
+        let presses = machines.iter().map(|m| m.solve(offset, None));
+        Ok(total_tokens(presses).to_string())
+    }
+}
 
-        let total_tokens: u64 = machines
-            .iter()
-            .filter_map(|m| m.can_reach_prize_part2())
-            .sum();
+/// Tries every press combination up to `max_presses` in part 1. Kept as an
+/// oracle for [`Day13`]; part 2 is out of its reach, so it defers to the solver.
+pub struct Day13BruteForce;
+
+impl Solution for Day13BruteForce {
+    fn params(&self) -> Vec<ParamSpec> {
+        params()
+    }
 
-        total_tokens.to_string()
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let max_presses = params.get("max_presses")?;
+        let machines = ClawMachine::parse_all(input)?;
+        let presses = machines.iter().map(|m| m.can_reach_prize(max_presses));
+        Ok(total_tokens(presses).to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        Day13.try_part2_with(input, params)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::read_input;
 
     #[test]
     fn test_part1_sample() {
-        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\nButton A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176\n\nButton A: X+17, Y+86\nButton B: X+84, Y+37\nPrize: X=7870, Y=6450\n\nButton A: X+69, Y+23\nButton B: X+27, Y+71\nPrize: X=18641, Y=10279";
-        assert_eq!(Day13.part1(input), "480");
+        let input = read_input(13, true);
+        assert_eq!(Day13.part1(&input), "480");
     }
 
     #[test]
     fn test_part2_sample() {
-        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\nButton A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176\n\nButton A: X+17, Y+86\nButton B: X+84, Y+37\nPrize: X=7870, Y=6450\n\nButton A: X+69, Y+23\nButton B: X+27, Y+71\nPrize: X=18641, Y=10279";
-        assert_eq!(Day13.part2(input), "875318608908");
+        let input = read_input(13, true);
+        assert_eq!(Day13.part2(&input), "875318608908");
+    }
+
+    #[test]
+    fn test_explains_presses() {
+        let input = read_input(13, true);
+        let events = crate::explain::explained(|| {
+            Day13.part1(&input);
+        });
+        assert_eq!(
+            events,
+            [
+                "won: machine=1, a=80, b=40, tokens=280",
+                "unreachable: machine=2",
+                "won: machine=3, a=38, b=86, tokens=200",
+                "unreachable: machine=4"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        let input = read_input(13, true);
+        assert_eq!(Day13.part1(&input), Day13BruteForce.part1(&input));
+    }
+
+    #[test]
+    fn test_part2_without_offset_matches_part1() {
+        let input = read_input(13, true);
+        let params = Day13.default_params().with("offset", 0);
+        assert_eq!(Day13.part2_with(&input, &params), "480");
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        // Presses aren't unique when both buttons move along the same line
+        let machine = |a: (i64, i64), b: (i64, i64), prize: (i64, i64)| ClawMachine {
+            button_a: a,
+            button_b: b,
+            prize,
+        };
+        assert_eq!(machine((1, 1), (2, 2), (4, 4)).solve(0, None), Some((0, 2)));
+        assert_eq!(machine((1, 1), (2, 2), (4, 5)).solve(0, None), None);
+        assert_eq!(
+            machine((1, 1), (3, 3), (303, 303)).solve(0, Some(100)),
+            Some((3, 100))
+        );
+        assert_eq!(machine((0, 2), (0, 4), (0, 6)).solve(0, None), Some((1, 1)));
+        assert_eq!(
+            machine((2, 2), (3, 3), (0, 0)).solve(10, None),
+            Some((2, 2))
+        );
+
+        let input = "Button A: X+1, Y+1\nButton B: X+2, Y+2\nPrize: X=4, Y=4\n\n\
+                     Button A: X+1, Y+1\nButton B: X+3, Y+3\nPrize: X=303, Y=303\n\n\
+                     Button A: X+4, Y+2\nButton B: X+6, Y+3\nPrize: X=10, Y=6";
+        assert_eq!(Day13.part1(input), "111");
+        assert_eq!(Day13.part1(input), Day13BruteForce.part1(input));
+    }
+
+    #[test]
+    fn test_parse_error_is_positioned() {
+        let input = read_input(13, true).replacen("Y+66", "Y66", 1);
+        let err = parse::blocks(&input)
+            .iter()
+            .map(ClawMachine::parse)
+            .collect::<ParseResult<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (5, 18));
     }
 }
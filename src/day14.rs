@@ -1,97 +1,126 @@
-use crate::Solution;
+use crate::debugger::{Debugger, Session, Steppable};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::math::crt;
+use crate::parse::{self, Line, ParseResult};
+use crate::snapshot::{self, Snapshot};
+use crate::{ParamSpec, Params, Solution, SolveError};
 use std::collections::HashSet;
 
-const DEBUG_VISUALIZATION: bool = false; // Set to true to enable visualization
-
 #[derive(Debug, Clone)]
 struct Robot {
-    pos: (i32, i32),
-    vel: (i32, i32),
+    pos: Point,
+    vel: Point,
 }
 
 impl Robot {
-    fn from_str(s: &str) -> Self {
-        let parts: Vec<&str> = s.split(' ').collect();
-        let pos_str = &parts[0][2..];
-        let vel_str = &parts[1][2..];
-
-        let pos: Vec<i32> = pos_str.split(',').map(|n| n.parse().unwrap()).collect();
-        let vel: Vec<i32> = vel_str.split(',').map(|n| n.parse().unwrap()).collect();
-
-        Robot {
-            pos: (pos[0], pos[1]),
-            vel: (vel[0], vel[1]),
-        }
+    /// Reads `p=x,y v=dx,dy`
+    fn parse(line: &mut Line) -> ParseResult<Self> {
+        let px = line.literal("p=")?.int()?;
+        let py = line.literal(",")?.int()?;
+        let vx = line.literal(" v=")?.int()?;
+        let vy = line.literal(",")?.int()?;
+        line.end()?;
+        Ok(Robot {
+            pos: Point::new(px, py),
+            vel: Point::new(vx, vy),
+        })
     }
 
-    fn update(&mut self, width: i32, height: i32) {
-        self.pos.0 = (self.pos.0 + self.vel.0).rem_euclid(width);
-        self.pos.1 = (self.pos.1 + self.vel.1).rem_euclid(height);
+    fn parse_all(input: &str) -> ParseResult<Vec<Self>> {
+        parse::parse_lines(input, Self::parse)
     }
-}
-
-fn flood_fill(grid: &Vec<Vec<char>>, visited: &mut HashSet<(usize, usize)>, x: usize, y: usize) {
-    if visited.contains(&(x, y)) || y >= grid.len() || x >= grid[0].len() || grid[y][x] == '.' {
-        return;
-    }
-
-    visited.insert((x, y));
 
-    // Check only orthogonal directions (no diagonals)
-    let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
-    for (dx, dy) in directions {
-        let new_x = x as i32 + dx;
-        let new_y = y as i32 + dy;
-
-        if new_x >= 0 && new_y >= 0 && new_x < grid[0].len() as i32 && new_y < grid.len() as i32 {
-            flood_fill(grid, visited, new_x as usize, new_y as usize);
-        }
+    /// Done in `i64` so that large velocities or second counts cannot overflow
+    fn position_after(&self, seconds: i64, width: i32, height: i32) -> Point {
+        let wrap = |p: i32, v: i32, size: i32| {
+            (p as i64 + v as i64 * seconds.rem_euclid(size as i64)).rem_euclid(size as i64) as i32
+        };
+        Point::new(
+            wrap(self.pos.x, self.vel.x, width),
+            wrap(self.pos.y, self.vel.y, height),
+        )
     }
 }
 
-fn analyze_grid(grid: &Vec<Vec<char>>) -> (usize, Vec<usize>, Vec<usize>) {
-    let mut visited = HashSet::new();
-    let mut islands = 0;
+/// The robots where they are after `seconds`
+fn moved(robots: &[Robot], seconds: i64, width: i32, height: i32) -> Vec<Robot> {
+    robots
+        .iter()
+        .map(|r| Robot {
+            pos: r.position_after(seconds, width, height),
+            vel: r.vel,
+        })
+        .collect()
+}
 
-    // Count islands
-    for (y, row) in grid.iter().enumerate() {
-        for (x, &cell) in row.iter().enumerate() {
-            if !visited.contains(&(x, y)) && cell != '.' {
-                islands += 1;
-                flood_fill(grid, &mut visited, x, y);
-            }
+/// The product of the robot counts in each quadrant, leaving out the robots
+/// on the middle lines
+fn safety_factor(robots: &[Robot], width: i32, height: i32) -> u64 {
+    // Count robots in each quadrant
+    let mid_x = width / 2;
+    let mid_y = height / 2;
+
+    let mut quadrants = [0u64; 4];
+    for robot in robots.iter() {
+        // Skip robots on the middle lines
+        if robot.pos.x == mid_x || robot.pos.y == mid_y {
+            continue;
         }
+
+        let quadrant = match (robot.pos.x < mid_x, robot.pos.y < mid_y) {
+            (true, true) => 0,   // Top-left
+            (false, true) => 1,  // Top-right
+            (true, false) => 2,  // Bottom-left
+            (false, false) => 3, // Bottom-right
+        };
+        quadrants[quadrant] += 1;
     }
 
-    // Count robots in each row and column
-    let mut row_counts = vec![0; grid.len()];
-    let mut col_counts = vec![0; grid[0].len()];
+    quadrants.iter().product()
+}
 
-    for (y, row) in grid.iter().enumerate() {
-        for (x, &cell) in row.iter().enumerate() {
-            if cell != '.' {
-                row_counts[y] += 1;
-                col_counts[x] += 1;
-            }
-        }
+/// Reads the space dimensions, which must be positive for the robots to wrap
+fn dimensions(params: &Params) -> Result<(i32, i32), SolveError> {
+    let width: i32 = params.get("width")?;
+    let height: i32 = params.get("height")?;
+    if width <= 0 || height <= 0 {
+        return Err(SolveError::unsolvable(format!(
+            "the space must have a positive size, got {}x{}",
+            width, height
+        )));
     }
+    Ok((width, height))
+}
 
-    (islands, row_counts, col_counts)
+/// Second within one `period` at which the given coordinates are packed most
+/// tightly, measured by their variance
+fn most_clustered<I>(period: i32, coords_at: impl Fn(i32) -> I) -> i32
+where
+    I: Iterator<Item = i32>,
+{
+    (0..period)
+        .min_by_key(|&t| {
+            let (n, sum, sum_sq) = coords_at(t).fold((0i128, 0i128, 0i128), |(n, s, sq), c| {
+                (n + 1, s + c as i128, sq + (c as i128).pow(2))
+            });
+            // n² times the variance, which keeps everything in integers
+            n * sum_sq - sum * sum
+        })
+        .unwrap_or(0)
 }
 
-fn display_grid(robots: &[Robot], width: i32, height: i32) -> Vec<Vec<char>> {
-    let mut grid = vec![vec!['.'; width as usize]; height as usize];
+fn display_grid(robots: &[Robot], width: i32, height: i32) -> Grid<char> {
+    let mut grid = Grid::new(width as usize, height as usize, '.');
 
     // Place robots on grid
     for robot in robots {
-        let x = robot.pos.0 as usize;
-        let y = robot.pos.1 as usize;
-        if x < width as usize && y < height as usize {
+        if let Some(cell) = grid.get_mut(robot.pos.y as usize, robot.pos.x as usize) {
             // Count robots at this position
-            grid[y][x] = match grid[y][x] {
+            *cell = match *cell {
                 '.' => '#',
                 '#' => '2',
-                n if n.is_ascii_digit() => {
+                n if n.is_ascii_digit() && n < '9' => {
                     let count = n.to_digit(10).unwrap() + 1;
                     char::from_digit(count, 10).unwrap()
                 }
@@ -100,145 +129,188 @@ fn display_grid(robots: &[Robot], width: i32, height: i32) -> Vec<Vec<char>> {
         }
     }
 
-    if DEBUG_VISUALIZATION {
-        // Display grid
-        println!("\nTime step visualization:");
-        for row in &grid {
-            println!("{}", row.iter().collect::<String>());
-        }
-        println!();
-    }
-
     grid
 }
 
 pub struct Day14;
 
 impl Solution for Day14 {
-    fn part1(&self, input: &str) -> String {
-        let mut robots: Vec<Robot> = input.lines().map(Robot::from_str).collect();
-
-        // For the sample, use smaller dimensions
-        let (width, height) = if robots.len() == 12 {
-            (11, 7) // Sample dimensions
-        } else {
-            (101, 103) // Real dimensions
-        };
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Robot::parse_all(input).map(drop).map_err(Into::into))
+    }
 
-        // Simulate for 100 seconds
-        for _step in 0..100 {
-            for robot in robots.iter_mut() {
-                robot.update(width, height);
-            }
-        }
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("width", "101", "Width of the space the robots move in"),
+            ParamSpec::new("height", "103", "Height of the space the robots move in"),
+            ParamSpec::new("seconds", "100", "Seconds to simulate in part 1"),
+        ]
+    }
 
-        // Count robots in each quadrant
-        let mid_x = width / 2;
-        let mid_y = height / 2;
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let robots: Vec<Robot> = Robot::parse_all(input)?;
+        let (width, height) = dimensions(params)?;
+        let seconds: i64 = params.get("seconds")?;
 
-        let mut quadrants = [0; 4];
-        for robot in robots.iter() {
-            // Skip robots on the middle lines
-            if robot.pos.0 == mid_x || robot.pos.1 == mid_y {
-                continue;
-            }
+        let robots = moved(&robots, seconds, width, height);
+        Ok(safety_factor(&robots, width, height).to_string())
+    }
 
-            let quadrant = match (robot.pos.0 < mid_x, robot.pos.1 < mid_y) {
-                (true, true) => 0,   // Top-left
-                (false, true) => 1,  // Top-right
-                (true, false) => 2,  // Bottom-left
-                (false, false) => 3, // Bottom-right
-            };
-            quadrants[quadrant] += 1;
-        }
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let robots: Vec<Robot> = Robot::parse_all(input)?;
+        let (width, height) = dimensions(params)?;
+
+        // The x coordinates repeat every `width` seconds and the y coordinates
+        // every `height` seconds. The tree is where the robots bunch up on both
+        // axes, so find the tightest second for each axis and combine them.
+        let tx = most_clustered(width, |t| {
+            robots
+                .iter()
+                .map(move |r| r.position_after(t as i64, width, height).x)
+        });
+        let ty = most_clustered(height, |t| {
+            robots
+                .iter()
+                .map(move |r| r.position_after(t as i64, width, height).y)
+        });
+
+        let Some((step, _)) = crt(&[(tx as i64, width as i64), (ty as i64, height as i64)]) else {
+            return Err(SolveError::unsolvable(
+                "the clustered seconds of the two axes never coincide",
+            ));
+        };
 
-        // Calculate safety factor
-        let safety_factor: i32 = quadrants.iter().product();
-        safety_factor.to_string()
+        crate::render(|| {
+            let arranged = moved(&robots, step, width, height);
+            format!(
+                "Time step visualization:\n{}",
+                display_grid(&arranged, width, height)
+            )
+        });
+
+        Ok(step.to_string())
     }
+}
 
-    fn part2(&self, input: &str) -> String {
-        let mut robots: Vec<Robot> = input.lines().map(Robot::from_str).collect();
+/// The robots second by second, until their positions repeat after
+/// `width * height` seconds
+struct Robots {
+    robots: Vec<Robot>,
+    width: i32,
+    height: i32,
+    seconds: i64,
+}
 
-        let width = 101;
-        let height = 103;
+impl Steppable for Robots {
+    /// The seconds passed
+    type Snapshot = i64;
 
-        let mut min_islands = usize::MAX;
-        let mut min_islands_step = 0;
-        let mut min_islands_grid = Vec::new();
+    fn step(&mut self) -> Result<bool, SolveError> {
+        if self.seconds == self.width as i64 * self.height as i64 {
+            return Ok(false);
+        }
+        self.seconds += 1;
+        Ok(true)
+    }
 
-        // Simulate and look for Christmas tree pattern
-        for step in 0..100000 {
-            // Increased to 100k steps
-            for robot in robots.iter_mut() {
-                robot.update(width, height);
-            }
+    fn snapshot(&self) -> i64 {
+        self.seconds
+    }
 
-            let grid = display_grid(&robots, width, height);
-            let (islands, row_counts, col_counts) = analyze_grid(&grid);
-
-            if islands < min_islands {
-                min_islands = islands;
-                min_islands_step = step + 1;
-                min_islands_grid = grid.clone();
-                println!(
-                    "New minimum islands ({}) found at step {}",
-                    islands,
-                    step + 1
-                );
-
-                // Print row/column statistics
-                let max_row = row_counts.iter().max().unwrap();
-                let max_col = col_counts.iter().max().unwrap();
-                println!("Max robots in any row: {}", max_row);
-                println!("Max robots in any column: {}", max_col);
-
-                if islands <= 10 {
-                    // Increased threshold to see more potential patterns
-                    println!("\nPattern at step {}:", step + 1);
-                    for row in &grid {
-                        println!("{}", row.iter().collect::<String>());
-                    }
-                    println!();
-                }
-            }
-        }
+    fn restore(&mut self, &seconds: &i64) {
+        self.seconds = seconds;
+    }
 
-        println!(
-            "\nFinal minimum pattern (islands: {}, step: {}):",
-            min_islands, min_islands_step
-        );
-        for row in &min_islands_grid {
-            println!("{}", row.iter().collect::<String>());
-        }
-        println!();
+    fn render(&self) -> String {
+        let robots = moved(&self.robots, self.seconds, self.width, self.height);
+        display_grid(&robots, self.width, self.height).to_string()
+    }
 
+    fn status(&self) -> String {
+        let robots = moved(&self.robots, self.seconds, self.width, self.height);
+        let cells: HashSet<Point> = robots.iter().map(|r| r.pos).collect();
         format!(
-            "Minimum islands: {} at step {}",
-            min_islands, min_islands_step
+            "After {} seconds: safety factor {}{}",
+            self.seconds,
+            safety_factor(&robots, self.width, self.height),
+            if cells.len() == robots.len() {
+                ", no robots overlap"
+            } else {
+                ""
+            }
         )
     }
 }
 
+/// A debugger on the robots' movement, one second per step; see
+/// [`crate::debugger`]
+pub fn debugger(input: &str, params: &Params) -> Result<Box<dyn Session>, SolveError> {
+    let (width, height) = dimensions(params)?;
+    Ok(Box::new(Debugger::new(Robots {
+        robots: Robot::parse_all(input)?,
+        width,
+        height,
+        seconds: 0,
+    })))
+}
+
+/// The sample's robots over part 1's seconds, and a crowd meeting in one cell
+pub fn snapshots() -> Vec<Snapshot> {
+    let input = crate::load_input(14, true);
+    let params = input
+        .params_for(&Day14)
+        .expect("the sample's metadata is valid");
+    let (width, height) = dimensions(&params).expect("the sample's space is valid");
+    let grid_after = |robots: &[Robot], seconds: i64| {
+        display_grid(&moved(robots, seconds, width, height), width, height).to_string()
+    };
+    let states = |robots: &[Robot], seconds: &[i64]| {
+        snapshot::sequence(
+            seconds
+                .iter()
+                .map(|&s| (format!("After {} seconds", s), grid_after(robots, s))),
+        )
+    };
+
+    let sample = Robot::parse_all(&input.text).expect("the sample parses");
+    let crowd =
+        Robot::parse_all("p=8,6 v=1,2\np=3,5 v=3,-1\np=10,2 v=-2,4\np=6,1 v=4,1\np=7,2 v=-3,-3")
+            .expect("the crowd parses");
+    vec![
+        Snapshot::new(14, "quadrants", states(&sample, &[0, 1, 2, 100])),
+        Snapshot::new(14, "crowd", states(&crowd, &[0, 29, 30])),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::load_input;
 
     #[test]
     fn test_part1_sample() {
-        let input = "\
-p=0,4 v=3,-3
-p=6,3 v=-1,-3
-p=10,3 v=-1,2
-p=2,0 v=2,-1
-p=0,0 v=1,3
-p=3,0 v=-2,-2
-p=7,6 v=-1,-3
-p=3,0 v=-1,-2
-p=9,3 v=2,3
-p=7,3 v=-1,2
-p=2,4 v=2,-3
-p=9,5 v=-3,-3";
-        assert_eq!(Day14.part1(input), "12");
+        let input = load_input(14, true);
+        let params = input.params_for(&Day14).unwrap();
+        assert_eq!(Day14.part1_with(&input.text, &params), "12");
+    }
+
+    #[test]
+    fn test_part2_finds_convergence() {
+        // Every robot reaches (5, 3) after 30 seconds
+        let input = "p=8,6 v=1,2\np=3,5 v=3,-1\np=10,2 v=-2,4\np=6,1 v=4,1\np=7,2 v=-3,-3";
+        let params = Day14.default_params().with("width", 11).with("height", 7);
+        assert_eq!(Day14.part2_with(input, &params), "30");
+    }
+
+    #[test]
+    fn test_part2_without_a_common_second() {
+        // The x coordinates meet at odd seconds, the y coordinates never move,
+        // and an even width and height share no odd second
+        let input = "p=0,0 v=1,0\np=2,0 v=-1,0";
+        let params = Day14.default_params().with("width", 4).with("height", 6);
+        assert!(matches!(
+            Day14.try_part2_with(input, &params),
+            Err(SolveError::Unsolvable(_))
+        ));
     }
 }
@@ -1,107 +1,138 @@
-use crate::Solution;
-use std::collections::HashMap;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Coordinate {
-    row: i32,
-    col: i32,
-}
+use crate::debugger::{Debugger, Session, Steppable};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::parse;
+use crate::snapshot::{self, Snapshot};
+use crate::{Params, Solution, SolveError};
 
 #[derive(Debug)]
 struct Robot {
-    position: Coordinate,
+    position: Point,
 }
 
+/// Cell values: 0 is empty, 1 is a wall and 2+ are crate IDs (a wide crate
+/// occupies two cells with the same ID)
 struct Warehouse {
-    grid: HashMap<Coordinate, i32>,
+    grid: Grid<i32>,
     robot: Robot,
-    commands: Vec<char>,
+    commands: Vec<Direction>,
+    /// How many commands the debugger has run
+    moves: usize,
 }
 
 impl Warehouse {
-    fn from_str(input: &str, double_mode: bool) -> Self {
-        let mut grid = HashMap::new();
-        let mut robot = None;
-        let mut commands = Vec::new();
+    fn from_str(input: &str, double_mode: bool) -> Result<Self, SolveError> {
         let mut next_crate_id = 2; // Start crate IDs at 2
 
-        // Parse the grid
-        let lines: Vec<&str> = input.lines().collect();
-        let mut parsing_grid = true;
-
-        for (row, line) in lines.iter().enumerate() {
-            if line.is_empty() {
-                parsing_grid = false;
-                continue;
-            }
+        // The map comes first, then the commands after a blank line
+        let map_lines: Vec<&str> = input.lines().take_while(|line| !line.is_empty()).collect();
+        let commands = input
+            .lines()
+            .skip(map_lines.len())
+            .flat_map(|line| line.chars().filter_map(Direction::from_arrow))
+            .collect();
+
+        let map = parse::grid_with(&map_lines.join("\n"), |ch| {
+            matches!(ch, '#' | 'O' | '@' | '.').then_some(ch)
+        })?;
+        let mut robots = map.iter().filter(|&(_, &ch)| ch == '@');
+        let Some(((row, col), _)) = robots.next() else {
+            return Err(SolveError::unsolvable("no robot in the warehouse"));
+        };
+        if robots.next().is_some() {
+            return Err(SolveError::unsolvable(
+                "more than one robot in the warehouse",
+            ));
+        }
 
-            if parsing_grid {
-                for (col, ch) in line.chars().enumerate() {
-                    let base_col = if double_mode { col * 2 } else { col };
-                    let coord = Coordinate {
-                        row: row as i32,
-                        col: base_col as i32,
-                    };
-
-                    match ch {
-                        '#' => {
-                            grid.insert(coord, 1);
-                            if double_mode {
-                                grid.insert(
-                                    Coordinate {
-                                        row: row as i32,
-                                        col: (base_col + 1) as i32,
-                                    },
-                                    1,
-                                );
-                            }
-                        }
-                        'O' => {
-                            grid.insert(coord, next_crate_id);
-                            if double_mode {
-                                grid.insert(
-                                    Coordinate {
-                                        row: row as i32,
-                                        col: (base_col + 1) as i32,
-                                    },
-                                    next_crate_id,
-                                );
-                            }
-                            next_crate_id += 1;
-                        }
-                        '@' => {
-                            robot = Some(Robot { position: coord });
-                            // In double mode, the space after @ is empty
-                        }
-                        _ => (), // Empty space or other characters
-                    }
+        let scale = if double_mode { 2 } else { 1 };
+        // In double mode, the space after @ is empty
+        let robot = Robot {
+            position: Point::from_grid((row, col * scale)),
+        };
+        let mut grid = Grid::new(map.width() * scale, map.height(), 0);
+
+        for ((row, col), &ch) in map.iter() {
+            let value = match ch {
+                '#' => 1,
+                'O' => {
+                    next_crate_id += 1;
+                    next_crate_id - 1
                 }
-            } else {
-                // Parse commands
-                commands.extend(line.chars().filter(|&c| "^v<>".contains(c)));
+                _ => continue, // Empty space or the robot
+            };
+            for offset in 0..scale {
+                grid[(row, col * scale + offset)] = value;
             }
         }
 
-        Warehouse {
+        Ok(Warehouse {
             grid,
-            robot: robot.expect("Robot position not found in input"),
+            robot,
             commands,
+            moves: 0,
+        })
+    }
+
+    /// What occupies a cell, or `None` if it is empty. Outside the map counts
+    /// as wall, so maps without a border still keep everything inside.
+    fn get(&self, coord: &Point) -> Option<i32> {
+        self.grid
+            .get_point(*coord)
+            .map_or(Some(1), |&v| (v != 0).then_some(v))
+    }
+
+    /// The map as the puzzle draws it: `#` walls, `O` or `[]` crates and `@`
+    /// the robot
+    fn render(&self) -> String {
+        let mut rendered = self.grid.map(|&value| match value {
+            0 => '.',
+            1 => '#',
+            _ => 'O',
+        });
+        for (pos, &id) in self.grid.iter().filter(|&(_, &v)| v >= 2) {
+            let point = Point::from_grid(pos);
+            if self.get(&point.step(Direction::Right)) == Some(id) {
+                rendered[pos] = '[';
+            } else if self.get(&point.step(Direction::Left)) == Some(id) {
+                rendered[pos] = ']';
+            }
         }
+        rendered[self.robot.position] = '@';
+        rendered.to_string()
+    }
+
+    /// The layout before and after each move
+    fn replay(mut self) -> String {
+        let mut states = vec![("Initial state".to_string(), self.render())];
+        for (i, command) in self.commands.clone().into_iter().enumerate() {
+            self.execute_move(command);
+            states.push((format!("Move {} {}", i + 1, command.arrow()), self.render()));
+        }
+        snapshot::sequence(states)
+    }
+
+    fn cells_with_id(&self, id: i32) -> Vec<Point> {
+        self.grid
+            .iter()
+            .filter(|&(_, &v)| v == id)
+            .map(|(pos, _)| Point::from_grid(pos))
+            .collect()
     }
 
-    fn is_wall(&self, coord: &Coordinate) -> bool {
-        self.grid.get(coord).map_or(false, |&v| v == 1)
+    fn is_wall(&self, coord: &Point) -> bool {
+        self.get(coord) == Some(1)
     }
 
-    fn is_crate(&self, coord: &Coordinate) -> bool {
-        self.grid.get(coord).map_or(false, |&v| v >= 2)
+    fn is_crate(&self, coord: &Point) -> bool {
+        self.get(coord).is_some_and(|v| v >= 2)
     }
 
-    fn get_crate_id(&self, coord: &Coordinate) -> Option<i32> {
-        self.grid.get(coord).copied().filter(|&v| v >= 2)
+    fn get_crate_id(&self, coord: &Point) -> Option<i32> {
+        self.get(coord).filter(|&v| v >= 2)
     }
 
-    fn can_move_crate(&self, from: &Coordinate, direction: char) -> bool {
+    fn can_move_crate(&self, from: &Point, direction: Direction) -> bool {
         let mut to_check = vec![]; // Stack of crate IDs we need to check
         let mut checked = std::collections::HashSet::new(); // Set of crate IDs we've already checked
 
@@ -119,37 +150,14 @@ impl Warehouse {
             checked.insert(current_id);
 
             // Find all coordinates containing this crate ID
-            let mut crate_coords = vec![];
-            for (&coord, &id) in &self.grid {
-                if id == current_id {
-                    crate_coords.push(coord);
-                }
-            }
+            let crate_coords = self.cells_with_id(current_id);
 
             // Calculate new positions for all parts of this crate
             for coord in crate_coords {
-                let new_coord = match direction {
-                    '^' => Coordinate {
-                        row: coord.row - 1,
-                        col: coord.col,
-                    },
-                    'v' => Coordinate {
-                        row: coord.row + 1,
-                        col: coord.col,
-                    },
-                    '<' => Coordinate {
-                        row: coord.row,
-                        col: coord.col - 1,
-                    },
-                    '>' => Coordinate {
-                        row: coord.row,
-                        col: coord.col + 1,
-                    },
-                    _ => return false,
-                };
+                let new_coord = coord.step(direction);
 
                 // Check what's in the new position
-                if let Some(&id) = self.grid.get(&new_coord) {
+                if let Some(id) = self.get(&new_coord) {
                     if id == 1 {
                         // Hit a wall, entire stack cannot move
                         return false;
@@ -165,7 +173,7 @@ impl Warehouse {
         true
     }
 
-    fn move_crate(&mut self, from: &Coordinate, direction: char) -> bool {
+    fn move_crate(&mut self, from: &Point, direction: Direction) -> bool {
         // First check if the move is possible
         if !self.can_move_crate(from, direction) {
             return false;
@@ -184,35 +192,12 @@ impl Warehouse {
             to_move.insert(current_id);
 
             // Find any crates that would be overlapped by moving this one
-            let crate_coords: Vec<_> = self
-                .grid
-                .iter()
-                .filter(|(_, &id)| id == current_id)
-                .map(|(&coord, _)| coord)
-                .collect();
+            let crate_coords = self.cells_with_id(current_id);
 
             for coord in crate_coords {
-                let new_coord = match direction {
-                    '^' => Coordinate {
-                        row: coord.row - 1,
-                        col: coord.col,
-                    },
-                    'v' => Coordinate {
-                        row: coord.row + 1,
-                        col: coord.col,
-                    },
-                    '<' => Coordinate {
-                        row: coord.row,
-                        col: coord.col - 1,
-                    },
-                    '>' => Coordinate {
-                        row: coord.row,
-                        col: coord.col + 1,
-                    },
-                    _ => return false,
-                };
-
-                if let Some(&id) = self.grid.get(&new_coord) {
+                let new_coord = coord.step(direction);
+
+                if let Some(id) = self.get(&new_coord) {
                     if id >= 2 && !checked.contains(&id) {
                         to_check.push(id);
                     }
@@ -224,40 +209,20 @@ impl Warehouse {
         // First collect all moves we need to make to avoid conflicts
         let mut moves = vec![];
         for &id in &to_move {
-            for (&coord, &grid_id) in &self.grid {
-                if grid_id == id {
-                    let new_coord = match direction {
-                        '^' => Coordinate {
-                            row: coord.row - 1,
-                            col: coord.col,
-                        },
-                        'v' => Coordinate {
-                            row: coord.row + 1,
-                            col: coord.col,
-                        },
-                        '<' => Coordinate {
-                            row: coord.row,
-                            col: coord.col - 1,
-                        },
-                        '>' => Coordinate {
-                            row: coord.row,
-                            col: coord.col + 1,
-                        },
-                        _ => return false,
-                    };
-                    moves.push((coord, new_coord, id));
-                }
+            for coord in self.cells_with_id(id) {
+                let new_coord = coord.step(direction);
+                moves.push((coord, new_coord, id));
             }
         }
 
         // Then apply all moves
         // First remove all old positions
         for (from_coord, _, _) in &moves {
-            self.grid.remove(from_coord);
+            self.grid[*from_coord] = 0;
         }
         // Then add all new positions
         for (_, to_coord, id) in moves {
-            self.grid.insert(to_coord, id);
+            self.grid[to_coord] = id;
         }
         true
     }
@@ -265,46 +230,28 @@ impl Warehouse {
     fn calculate_gps(&self) -> i32 {
         let mut crate_positions = std::collections::HashMap::new();
         // First collect all positions for each crate
-        for (coord, &v) in self.grid.iter() {
+        for (pos, &v) in self.grid.iter() {
             if v >= 2 {
                 let crate_id = v - 2;
                 crate_positions
                     .entry(crate_id)
                     .or_insert_with(Vec::new)
-                    .push(coord);
+                    .push(Point::from_grid(pos));
             }
         }
         // Then sum using the leftmost position for each crate
         crate_positions
             .into_values()
             .map(|coords| {
-                let leftmost = coords.into_iter().min_by_key(|c| c.col).unwrap();
-                100 * leftmost.row + leftmost.col
+                let leftmost = coords.into_iter().min_by_key(|c| c.x).unwrap();
+                100 * leftmost.y + leftmost.x
             })
             .sum()
     }
 
-    fn execute_move(&mut self, command: char) {
+    fn execute_move(&mut self, command: Direction) {
         let robot_pos = self.robot.position;
-        let new_pos = match command {
-            '^' => Coordinate {
-                row: robot_pos.row - 1,
-                col: robot_pos.col,
-            },
-            'v' => Coordinate {
-                row: robot_pos.row + 1,
-                col: robot_pos.col,
-            },
-            '<' => Coordinate {
-                row: robot_pos.row,
-                col: robot_pos.col - 1,
-            },
-            '>' => Coordinate {
-                row: robot_pos.row,
-                col: robot_pos.col + 1,
-            },
-            _ => return,
-        };
+        let new_pos = robot_pos.step(command);
 
         // If there's a wall in the way, don't move
         if self.is_wall(&new_pos) {
@@ -325,94 +272,129 @@ impl Warehouse {
 pub struct Day15;
 
 impl Solution for Day15 {
-    fn part1(&self, input: &str) -> String {
-        let mut warehouse = Warehouse::from_str(input, false);
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Warehouse::from_str(input, false).map(drop))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let mut warehouse = Warehouse::from_str(input, false)?;
 
         // Execute all commands
         for command in warehouse.commands.clone() {
             warehouse.execute_move(command);
         }
 
-        warehouse.calculate_gps().to_string()
+        Ok(warehouse.calculate_gps().to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let mut warehouse = Warehouse::from_str(input, true);
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let mut warehouse = Warehouse::from_str(input, true)?;
 
         // Execute all commands
         for command in warehouse.commands.clone() {
             warehouse.execute_move(command);
         }
 
-        warehouse.calculate_gps().to_string()
+        Ok(warehouse.calculate_gps().to_string())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Steppable for Warehouse {
+    /// The grid, the robot's position and the moves made
+    type Snapshot = (Grid<i32>, Point, usize);
 
-    fn warehouse_to_string(warehouse: &Warehouse) -> String {
-        let mut min_row = i32::MAX;
-        let mut max_row = i32::MIN;
-        let mut min_col = i32::MAX;
-        let mut max_col = i32::MIN;
-
-        // Find the bounds of the grid
-        for coord in warehouse.grid.keys() {
-            min_row = min_row.min(coord.row);
-            max_row = max_row.max(coord.row);
-            min_col = min_col.min(coord.col);
-            max_col = max_col.max(coord.col);
-        }
+    fn step(&mut self) -> Result<bool, SolveError> {
+        let Some(&command) = self.commands.get(self.moves) else {
+            return Ok(false);
+        };
+        self.execute_move(command);
+        self.moves += 1;
+        Ok(true)
+    }
 
-        // Also consider the robot's position
-        min_row = min_row.min(warehouse.robot.position.row);
-        max_row = max_row.max(warehouse.robot.position.row);
-        min_col = min_col.min(warehouse.robot.position.col);
-        max_col = max_col.max(warehouse.robot.position.col);
-
-        let mut result = String::new();
-        for row in min_row..=max_row {
-            for col in min_col..=max_col {
-                let coord = Coordinate { row, col };
-                if coord == warehouse.robot.position {
-                    result.push('@');
-                } else if let Some(&value) = warehouse.grid.get(&coord) {
-                    if value == 1 {
-                        result.push('#');
-                    } else {
-                        result.push('O');
-                    }
-                } else {
-                    result.push('.');
-                }
-            }
-            result.push('\n');
-        }
-        result
+    fn snapshot(&self) -> Self::Snapshot {
+        (self.grid.clone(), self.robot.position, self.moves)
     }
 
-    fn compare_states(actual: &str, expected: &str) -> bool {
-        let expected = expected.trim().replace(['[', ']'], "O");
-        let actual = actual.trim().replace(['[', ']'], "O");
-        actual == expected
+    fn restore(&mut self, (grid, position, moves): &Self::Snapshot) {
+        self.grid = grid.clone();
+        self.robot.position = *position;
+        self.moves = *moves;
     }
 
-    fn assert_states_eq(actual: &str, expected: &str, message: &str) {
-        assert!(
-            compare_states(actual, expected),
-            "{}\nExpected state:\n{}\nActual state:\n{}",
-            message,
-            expected,
-            actual
-        );
+    fn render(&self) -> String {
+        Warehouse::render(self)
     }
 
+    fn status(&self) -> String {
+        let next = match self.commands.get(self.moves) {
+            Some(command) => format!(", next {}", command.arrow()),
+            None => String::new(),
+        };
+        format!(
+            "move {} of {}, robot at {}{}",
+            self.moves,
+            self.commands.len(),
+            self.robot.position,
+            next
+        )
+    }
+}
+
+/// A debugger on the robot's moves, in the wide warehouse of part 2 if
+/// `double_mode` is set; see [`crate::debugger`]
+pub fn debugger(input: &str, double_mode: bool) -> Result<Box<dyn Session>, SolveError> {
+    Ok(Box::new(Debugger::new(Warehouse::from_str(
+        input,
+        double_mode,
+    )?)))
+}
+
+/// Every move of the small examples, and the sample's layout before and
+/// after its moves in both modes
+pub fn snapshots() -> Vec<Snapshot> {
+    let warehouse = |input: &str, double_mode| {
+        Warehouse::from_str(input, double_mode).expect("the examples are valid")
+    };
+    let named = |name: &str| {
+        crate::load_named_sample(15, name)
+            .expect("the named samples exist")
+            .text
+    };
+    let (small, small_wide) = (named("small"), named("small-wide"));
+    let sample = crate::read_input(15, true);
+    let layouts = [false, true].into_iter().flat_map(|double_mode| {
+        let mode = if double_mode { "Wide" } else { "Narrow" };
+        let mut warehouse = warehouse(&sample, double_mode);
+        let initial = warehouse.render();
+        for command in warehouse.commands.clone() {
+            warehouse.execute_move(command);
+        }
+        [
+            (format!("{}, initial state", mode), initial),
+            (format!("{}, after all moves", mode), warehouse.render()),
+        ]
+    });
+
+    vec![
+        Snapshot::new(15, "small-moves", warehouse(&small, false).replay()),
+        Snapshot::new(
+            15,
+            "small-wide-moves",
+            warehouse(&small_wide, true).replay(),
+        ),
+        Snapshot::new(15, "layouts", snapshot::sequence(layouts)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn test_parse_input() {
         let input = "########\n#..O.O.#\n##@.O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#\n########\n\n<^^>>>vv<v>>v<<";
-        let warehouse = Warehouse::from_str(input, false);
+        let warehouse = Warehouse::from_str(input, false).unwrap();
 
         // Basic checks
         assert!(warehouse.grid.values().any(|&v| v == 1)); // Has walls
@@ -420,33 +402,21 @@ mod tests {
         assert!(!warehouse.commands.is_empty()); // Has commands
 
         // Specific position checks
-        let wall_positions = [
-            Coordinate { row: 0, col: 0 },
-            Coordinate { row: 0, col: 1 },
-            Coordinate { row: 1, col: 0 },
-        ];
+        let wall_positions = [Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)];
         for pos in wall_positions {
-            assert_eq!(
-                warehouse.grid.get(&pos),
-                Some(&1),
-                "Expected wall at {:?}",
-                pos
-            );
+            assert_eq!(warehouse.get(&pos), Some(1), "Expected wall at {:?}", pos);
         }
 
         // Empty space check
         assert_eq!(
-            warehouse.grid.get(&Coordinate { row: 1, col: 1 }),
+            warehouse.get(&Point::new(1, 1)),
             None,
             "Expected empty space at (1,1)"
         );
 
         // Crate check
         assert!(
-            warehouse
-                .grid
-                .get(&Coordinate { row: 1, col: 3 })
-                .map_or(false, |&id| id >= 2),
+            warehouse.get(&Point::new(3, 1)).is_some_and(|id| id >= 2),
             "Expected crate at (1,3)"
         );
     }
@@ -464,7 +434,7 @@ mod tests {
 
 <^^>>>vv<v>>v<<";
 
-        let mut warehouse = Warehouse::from_str(input, false);
+        let mut warehouse = Warehouse::from_str(input, false).unwrap();
 
         // Execute all commands
         let commands = warehouse.commands.clone();
@@ -475,45 +445,6 @@ mod tests {
         assert_eq!(warehouse.calculate_gps(), 2028, "Final GPS sum mismatch");
     }
 
-    #[test]
-    fn test_small_example_layout() {
-        let input = "########
-#..O.O.#
-##@.O..#
-#...O..#
-#.#.O..#
-#...O..#
-#......#
-########
-
-<^^>>>vv<v>>v<<";
-
-        let expected_final_state = "\
-########
-#....OO#
-##.....#
-#.....O#
-#.#O@..#
-#...O..#
-#...O..#
-########
-";
-
-        let mut warehouse = Warehouse::from_str(input, false);
-
-        // Execute all commands
-        let commands = warehouse.commands.clone();
-        for command in commands {
-            warehouse.execute_move(command);
-        }
-
-        assert_states_eq(
-            &warehouse_to_string(&warehouse),
-            expected_final_state,
-            "Final state mismatch",
-        );
-    }
-
     #[test]
     fn test_large_example_gps() {
         let input = "##########
@@ -529,7 +460,7 @@ mod tests {
 
 <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
-        let mut warehouse = Warehouse::from_str(input, false);
+        let mut warehouse = Warehouse::from_str(input, false).unwrap();
 
         // Execute all commands
         let commands = warehouse.commands.clone();
@@ -540,127 +471,6 @@ mod tests {
         assert_eq!(warehouse.calculate_gps(), 10092, "Final GPS sum mismatch");
     }
 
-    #[test]
-    fn test_large_example_layout() {
-        let input = "##########
-#..O..O.O#
-#......O.#
-#.OO..O.O#
-#..O@..O.#
-#O#..O...#
-#O..O..O.#
-#.OO.O.OO#
-#....O...#
-##########
-
-<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
-
-        let expected_final_state = "\
-##########
-#.O.O.OOO#
-#........#
-#OO......#
-#OO@.....#
-#O#.....O#
-#O.....OO#
-#O.....OO#
-#OO....OO#
-##########
-";
-
-        let mut warehouse = Warehouse::from_str(input, false);
-
-        // Execute all commands
-        let commands = warehouse.commands.clone();
-        for command in commands {
-            warehouse.execute_move(command);
-        }
-
-        assert_states_eq(
-            &warehouse_to_string(&warehouse),
-            expected_final_state,
-            "Final state mismatch",
-        );
-    }
-
-    #[test]
-    fn test_large_example_wide_boxes() {
-        let input = "##########
-#..O..O.O#
-#......O.#
-#.OO..O.O#
-#..O@..O.#
-#O#..O...#
-#O..O..O.#
-#.OO.O.OO#
-#....O...#
-##########
-
-<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
-
-        let expected_final_state = "\
-####################
-##[].......[].[][]##
-##[]...........[].##
-##[]........[][][]##
-##[]......[]....[]##
-##..##......[]....##
-##..[]............##
-##..@......[].[][]##
-##......[][]..[]..##
-####################
-";
-
-        let mut warehouse = Warehouse::from_str(input, true);
-
-        // Execute all commands
-        let commands = warehouse.commands.clone();
-        for command in commands {
-            warehouse.execute_move(command);
-        }
-
-        assert_states_eq(
-            &warehouse_to_string(&warehouse),
-            expected_final_state,
-            "Final state mismatch",
-        );
-    }
-
-    #[test]
-    fn test_double_mode_example() {
-        let input = "\
-##########
-#..O..O.O#
-#......O.#
-#.OO..O.O#
-#..O@..O.#
-#O#..O...#
-#O..O..O.#
-#.OO.O.OO#
-#....O...#
-##########";
-
-        let warehouse = Warehouse::from_str(input, true);
-        let expected = "\
-####################
-##....[]....[]..[]##
-##............[]..##
-##..[][]....[]..[]##
-##....[]@.....[]..##
-##[]##....[]......##
-##[]....[]....[]..##
-##..[][]..[]..[][]##
-##........[]......##
-####################
-";
-
-        assert_states_eq(
-            &warehouse_to_string(&warehouse),
-            expected,
-            "Initial state mismatch in double mode",
-        );
-    }
-
     #[test]
     fn test_large_example_wide_boxes_gps() {
         let input = "##########
@@ -676,7 +486,7 @@ mod tests {
 
 <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
-        let mut warehouse = Warehouse::from_str(input, true);
+        let mut warehouse = Warehouse::from_str(input, true).unwrap();
 
         // Execute all commands
         let commands = warehouse.commands.clone();
@@ -684,166 +494,29 @@ mod tests {
             warehouse.execute_move(command);
         }
 
-        println!("{}", warehouse_to_string(&warehouse));
-
         assert_eq!(warehouse.calculate_gps(), 9021);
     }
 
     #[test]
-    fn test_small_example_wide_boxes() {
-        let input = "\
-#######
-#...#.#
-#.....#
-#..OO@#
-#..O..#
-#.....#
-#######
-
-<vv<<^^<<^^";
-
-        let expected_states = [
-            "\
-##############
-##......##..##
-##..........##
-##....[][]@.##
-##....[]....##
-##..........##
-##############",
-            // After move <
-            "\
-##############
-##......##..##
-##..........##
-##...[][]@..##
-##....[]....##
-##..........##
-##############",
-            // After move v
-            "\
-##############
-##......##..##
-##..........##
-##...[][]...##
-##....[].@..##
-##..........##
-##############",
-            // After move v
-            "\
-##############
-##......##..##
-##..........##
-##...[][]...##
-##....[]....##
-##.......@..##
-##############",
-            // After move <
-            "\
-##############
-##......##..##
-##..........##
-##...[][]...##
-##....[]....##
-##......@...##
-##############",
-            // After move <
-            "\
-##############
-##......##..##
-##..........##
-##...[][]...##
-##....[]....##
-##.....@....##
-##############",
-            // After move ^
-            "\
-##############
-##......##..##
-##...[][]...##
-##....[]....##
-##.....@....##
-##..........##
-##############",
-            // After move ^
-            "\
-##############
-##......##..##
-##...[][]...##
-##....[]....##
-##.....@....##
-##..........##
-##############",
-            // After move <
-            "\
-##############
-##......##..##
-##...[][]...##
-##....[]....##
-##....@.....##
-##..........##
-##############",
-            "\
-##############
-##......##..##
-##...[][]...##
-##....[]....##
-##...@......##
-##..........##
-##############",
-            // After move <
-            "\
-##############
-##......##..##
-##...[][]...##
-##...@[]....##
-##..........##
-##..........##
-##############",
-            // After move ^
-            "\
-##############
-##...[].##..##
-##...@.[]...##
-##....[]....##
-##..........##
-##..........##
-##############",
-        ];
-
-        let mut warehouse = Warehouse::from_str(input, true); // Set double_width to true
-
-        // Check initial state
-        assert_states_eq(
-            &warehouse_to_string(&warehouse),
-            expected_states[0],
-            "Initial state mismatch",
-        );
+    fn test_wide_moves_keep_crates_whole() {
+        // The states themselves are in the 15-small-wide-moves snapshot
+        let input = crate::load_named_sample(15, "small-wide").unwrap().text;
+        let mut warehouse = Warehouse::from_str(&input, true).unwrap();
+        let crate_cells =
+            |warehouse: &Warehouse| warehouse.grid.values().filter(|&&v| v >= 2).count();
 
-        // Execute each command and verify the state
         let commands = warehouse.commands.clone();
-        for (i, command) in commands.iter().enumerate() {
-            // Verify before and after that there are SIX crate positions
-            assert_eq!(
-                warehouse.grid.values().filter(|&&v| v >= 2).count(),
-                6,
-                "Expected 6 crate positions before move {}, but found {}",
-                i + 1,
-                warehouse.grid.values().filter(|&&v| v >= 2).count()
-            );
-            warehouse.execute_move(*command);
-            assert_eq!(
-                warehouse.grid.values().filter(|&&v| v >= 2).count(),
-                6,
-                "Expected 6 crate positions after move {}, but found {}",
-                i + 1,
-                warehouse.grid.values().filter(|&&v| v >= 2).count()
-            );
-            assert_states_eq(
-                &warehouse_to_string(&warehouse),
-                expected_states[i + 1],
-                &format!("State mismatch after move {}", i + 1),
-            );
+        for (i, command) in commands.into_iter().enumerate() {
+            warehouse.execute_move(command);
+            assert_eq!(crate_cells(&warehouse), 6, "after move {}", i + 1);
+            for (pos, &id) in warehouse.grid.iter().filter(|&(_, &v)| v >= 2) {
+                assert_eq!(
+                    warehouse.cells_with_id(id).len(),
+                    2,
+                    "crate at {:?} split",
+                    pos
+                );
+            }
         }
     }
 }
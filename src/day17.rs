@@ -1,37 +1,34 @@
-use crate::Solution;
+use crate::debugger::{Debugger, Session, Steppable};
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 
+/// Instructions a single run may execute before it is taken to loop forever
+const STEP_LIMIT: usize = 10_000_000;
+
+/// What the debugger needs to go back to an earlier point of the run
 #[derive(Debug, Clone)]
 struct State {
-    reg_a: i64,
-    reg_b: i64,
-    reg_c: i64,
+    reg_a: u64,
+    reg_b: u64,
+    reg_c: u64,
     ip: usize,
-    output: Vec<u8>,
+    output_len: usize,
+    steps_left: usize,
 }
 
 #[derive(Debug, Default)]
 struct Computer {
-    reg_a: i64,
-    reg_b: i64,
-    reg_c: i64,
+    reg_a: u64,
+    reg_b: u64,
+    reg_c: u64,
     ip: usize,
     program: Vec<u8>,
     output: Vec<u8>,
-    debug: bool,
-    history: Vec<State>,
-    reverse_mode: bool,
+    steps_left: usize,
 }
 
 impl Computer {
-    fn new(program: Vec<u8>, reg_a: i64, reg_b: i64, reg_c: i64) -> Self {
-        let initial_state = State {
-            reg_a,
-            reg_b,
-            reg_c,
-            ip: 0,
-            output: Vec::new(),
-        };
-
+    fn new(program: Vec<u8>, reg_a: u64, reg_b: u64, reg_c: u64) -> Self {
         Self {
             reg_a,
             reg_b,
@@ -39,54 +36,35 @@ impl Computer {
             ip: 0,
             program,
             output: Vec::new(),
-            debug: false,
-            history: vec![initial_state],
-            reverse_mode: false,
-        }
-    }
-
-    fn save_state(&mut self) {
-        let state = State {
-            reg_a: self.reg_a,
-            reg_b: self.reg_b,
-            reg_c: self.reg_c,
-            ip: self.ip,
-            output: self.output.clone(),
-        };
-        self.history.push(state);
-    }
-
-    fn restore_previous_state(&mut self) -> bool {
-        if self.history.len() > 1 {
-            self.history.pop(); // Remove current state
-            let prev_state = self.history.last().unwrap();
-            self.reg_a = prev_state.reg_a;
-            self.reg_b = prev_state.reg_b;
-            self.reg_c = prev_state.reg_c;
-            self.ip = prev_state.ip;
-            self.output = prev_state.output.clone();
-            true
-        } else {
-            false
+            steps_left: STEP_LIMIT,
         }
     }
 
-    fn with_debug(mut self) -> Self {
-        self.debug = true;
+    fn with_step_limit(mut self, steps: usize) -> Self {
+        self.steps_left = steps;
         self
     }
 
-    fn get_combo_value(&self, operand: u8) -> i64 {
+    fn get_combo_value(&self, operand: u8) -> Result<u64, SolveError> {
         match operand {
-            0..=3 => operand as i64,
-            4 => self.reg_a,
-            5 => self.reg_b,
-            6 => self.reg_c,
-            7 => panic!("Invalid combo operand 7"),
-            _ => unreachable!(),
+            0..=3 => Ok(operand as u64),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            _ => Err(SolveError::unsolvable(format!(
+                "invalid combo operand {} at position {}",
+                operand,
+                self.ip - 1
+            ))),
         }
     }
 
+    /// `reg_a / 2^operand`, shared by `adv`, `bdv` and `cdv`
+    fn divide(&self, operand: u8) -> Result<u64, SolveError> {
+        let power = self.get_combo_value(operand)?;
+        Ok(if power >= 64 { 0 } else { self.reg_a >> power })
+    }
+
     fn get_opcode_name(&self, opcode: u8) -> &'static str {
         match opcode {
             0 => "adv (divide reg_a by 2^operand)",
@@ -101,221 +79,222 @@ impl Computer {
         }
     }
 
-    fn display_state(&self) {
-        // Convert instruction pointer to arrow display
-        let mut ip_display = vec![' '; self.program.len()];
-        if self.ip + 1 < self.program.len() {
-            ip_display[self.ip] = '>';
-            ip_display[self.ip + 1] = '>';
+    fn run(&mut self) -> Result<(), SolveError> {
+        while self.step()? {}
+        Ok(())
+    }
+}
+
+impl Steppable for Computer {
+    type Snapshot = State;
+
+    /// Executes one instruction
+    fn step(&mut self) -> Result<bool, SolveError> {
+        if self.ip + 1 >= self.program.len() {
+            return Ok(false);
+        }
+        if self.steps_left == 0 {
+            return Err(SolveError::unsolvable("the program does not halt"));
         }
+        self.steps_left -= 1;
+
+        let opcode = self.program[self.ip];
+        let operand = self.program[self.ip + 1];
+        self.ip += 2;
 
-        println!(
-            "\nProgram: {} Mode",
-            if self.reverse_mode {
-                "REVERSE"
-            } else {
-                "FORWARD"
+        match opcode {
+            0 => {
+                // adv
+                self.reg_a = self.divide(operand)?;
             }
-        );
-        for i in (0..self.program.len()).step_by(2) {
-            if i + 1 < self.program.len() {
-                println!(
-                    "{}{} {} {} \t# {}",
-                    ip_display[i],
-                    ip_display[i + 1],
-                    self.program[i],
-                    self.program[i + 1],
-                    self.get_opcode_name(self.program[i])
-                );
+            1 => {
+                // bxl
+                self.reg_b ^= operand as u64;
+            }
+            2 => {
+                // bst
+                self.reg_b = self.get_combo_value(operand)? % 8;
+            }
+            3 => {
+                // jnz
+                if self.reg_a != 0 {
+                    self.ip = operand as usize;
+                }
+            }
+            4 => {
+                // bxc
+                self.reg_b ^= self.reg_c;
+            }
+            5 => {
+                // out
+                let value = (self.get_combo_value(operand)? % 8) as u8;
+                self.output.push(value);
+            }
+            6 => {
+                // bdv
+                self.reg_b = self.divide(operand)?;
+            }
+            7 => {
+                // cdv
+                self.reg_c = self.divide(operand)?;
+            }
+            _ => {
+                return Err(SolveError::unsolvable(format!(
+                    "invalid opcode {} at position {}",
+                    opcode,
+                    self.ip - 2
+                )))
             }
         }
+        Ok(true)
+    }
 
-        println!("\nRegisters:");
-        println!("A: {:032b} ({})", self.reg_a as u32, self.reg_a);
-        println!("B: {:032b} ({})", self.reg_b as u32, self.reg_b);
-        println!("C: {:032b} ({})", self.reg_c as u32, self.reg_c);
-
-        println!("\nOutput so far:");
-        if self.output.is_empty() {
-            println!("(none)");
-        } else {
-            println!("{:?}", self.output);
+    fn snapshot(&self) -> State {
+        State {
+            reg_a: self.reg_a,
+            reg_b: self.reg_b,
+            reg_c: self.reg_c,
+            ip: self.ip,
+            output_len: self.output.len(),
+            steps_left: self.steps_left,
         }
+    }
 
-        println!(
-            "\nPress ENTER to {}, 'r' to toggle reverse mode, 'c' to continue without debugging...",
-            if self.reverse_mode {
-                "step backward"
-            } else {
-                "continue"
-            }
-        );
+    fn restore(&mut self, state: &State) {
+        self.reg_a = state.reg_a;
+        self.reg_b = state.reg_b;
+        self.reg_c = state.reg_c;
+        self.ip = state.ip;
+        self.output.truncate(state.output_len);
+        self.steps_left = state.steps_left;
     }
 
-    fn run(&mut self) {
-        use std::io;
-
-        while self.ip + 1 < self.program.len() {
-            if self.debug {
-                self.display_state();
-
-                // Read a line of input
-                let mut buffer = String::new();
-                io::stdin().read_line(&mut buffer).unwrap();
-
-                match buffer.trim() {
-                    "r" => {
-                        self.reverse_mode = !self.reverse_mode;
-                        continue;
-                    }
-                    "c" => {
-                        println!("Continuing without debug mode...");
-                        self.debug = false;
-                    }
-                    _ => {
-                        // Handle reverse mode
-                        if self.reverse_mode {
-                            if !self.restore_previous_state() {
-                                println!("Cannot go back further!");
-                                self.reverse_mode = false;
-                            }
-                            continue;
-                        }
-                    }
-                }
-            }
+    /// The program with `>>` at the instruction pointer, the registers and
+    /// the output so far
+    fn render(&self) -> String {
+        let mut lines = vec!["Program:".to_string()];
+        for (i, pair) in self.program.chunks_exact(2).enumerate() {
+            lines.push(format!(
+                "{} {} {} \t# {}",
+                if 2 * i == self.ip { ">>" } else { "  " },
+                pair[0],
+                pair[1],
+                self.get_opcode_name(pair[0])
+            ));
+        }
 
-            let opcode = self.program[self.ip];
-            let operand = self.program[self.ip + 1];
-            self.ip += 2;
+        lines.push("\nRegisters:".to_string());
+        for (name, value) in [("A", self.reg_a), ("B", self.reg_b), ("C", self.reg_c)] {
+            lines.push(format!("{}: {:032b} ({})", name, value as u32, value));
+        }
 
-            match opcode {
-                0 => {
-                    // adv
-                    let power = self.get_combo_value(operand);
-                    self.reg_a /= 1 << power;
-                }
-                1 => {
-                    // bxl
-                    self.reg_b ^= operand as i64;
-                }
-                2 => {
-                    // bst
-                    self.reg_b = self.get_combo_value(operand) % 8;
-                }
-                3 => {
-                    // jnz
-                    if self.reg_a != 0 {
-                        self.ip = operand as usize;
-                    }
-                }
-                4 => {
-                    // bxc
-                    self.reg_b ^= self.reg_c;
-                }
-                5 => {
-                    // out
-                    let value = (self.get_combo_value(operand) % 8) as u8;
-                    self.output.push(value);
-                }
-                6 => {
-                    // bdv
-                    let power = self.get_combo_value(operand);
-                    self.reg_b = self.reg_a / (1 << power);
-                }
-                7 => {
-                    // cdv
-                    let power = self.get_combo_value(operand);
-                    self.reg_c = self.reg_a / (1 << power);
-                }
-                _ => panic!("Invalid opcode"),
-            }
+        lines.push("\nOutput so far:".to_string());
+        lines.push(if self.output.is_empty() {
+            "(none)".to_string()
+        } else {
+            format!("{:?}", self.output)
+        });
+        lines.join("\n")
+    }
 
-            if self.debug {
-                self.save_state();
-            }
-        }
+    fn status(&self) -> String {
+        let instruction = match self.program.get(self.ip..self.ip + 2) {
+            Some(&[opcode, operand]) => format!(
+                "{} {}",
+                self.get_opcode_name(opcode).split(' ').next().unwrap(),
+                operand
+            ),
+            _ => "halted".to_string(),
+        };
+        format!(
+            "ip={} {}, A={} B={} C={}",
+            self.ip, instruction, self.reg_a, self.reg_b, self.reg_c
+        )
     }
 }
 
+/// A debugger on the program, one instruction per step; see
+/// [`crate::debugger`]
+pub fn debugger(input: &str) -> Result<Box<dyn Session>, SolveError> {
+    let (program, reg_a, reg_b, reg_c) = Day17::parse_program(input)?;
+    Ok(Box::new(Debugger::new(Computer::new(
+        program, reg_a, reg_b, reg_c,
+    ))))
+}
+
 #[derive(Default)]
 pub struct Day17;
 
 impl Day17 {
-    fn parse_input(&self, input: &str) -> (Vec<u8>, i64, i64, i64) {
-        let mut lines = input.lines();
-
-        // Parse register values
-        let reg_a = lines
-            .next()
-            .unwrap()
-            .strip_prefix("Register A: ")
-            .unwrap()
-            .parse()
-            .unwrap();
-        let reg_b = lines
-            .next()
-            .unwrap()
-            .strip_prefix("Register B: ")
-            .unwrap()
-            .parse()
-            .unwrap();
-        let reg_c = lines
-            .next()
-            .unwrap()
-            .strip_prefix("Register C: ")
-            .unwrap()
-            .parse()
-            .unwrap();
-
-        // Skip empty line and "Program: " line
-        lines.next();
-        let program_line = lines.next().unwrap().strip_prefix("Program: ").unwrap();
-
-        // Parse program
-        let program: Vec<u8> = program_line
-            .split(',')
-            .map(|s| s.parse().unwrap())
-            .collect();
-
-        (program, reg_a, reg_b, reg_c)
-    }
-
-    fn create_computer(&self, program: Vec<u8>, reg_a: i64, reg_b: i64, reg_c: i64) -> Computer {
-        let mut computer = Computer::new(program, reg_a, reg_b, reg_c);
-        if std::env::var("DEBUG").is_ok() {
-            computer = computer.with_debug();
+    /// Reads the three `Register X: n` lines, a blank line and `Program: a,b,...`
+    fn parse_program(input: &str) -> ParseResult<(Vec<u8>, u64, u64, u64)> {
+        let [registers, program] = parse::sections(input)?;
+        let [mut a, mut b, mut c] = registers.exact_lines()?;
+        let reg_a = a.literal("Register A: ")?.int()?;
+        let reg_b = b.literal("Register B: ")?.int()?;
+        let reg_c = c.literal("Register C: ")?.int()?;
+        for line in [&a, &b, &c] {
+            line.end()?;
         }
-        computer
-    }
 
-    fn run_program(&self, input: &str, override_reg_a: Option<i64>) -> Vec<u8> {
-        let (program, reg_a, reg_b, reg_c) = self.parse_input(input);
-        let mut computer =
-            self.create_computer(program, override_reg_a.unwrap_or(reg_a), reg_b, reg_c);
+        let [mut line] = program.exact_lines()?;
+        let program = line.literal("Program: ")?.list(",", |l| {
+            let column = l.column();
+            match l.int()? {
+                op @ 0..=7 => Ok(op),
+                op => Err(l.error_at(column, format!("{} is not a 3-bit value", op))),
+            }
+        })?;
+        line.end()?;
 
-        // Enable debug mode if environment variable is set
-        if std::env::var("DEBUG").is_ok() {
-            computer = computer.with_debug();
-        }
+        Ok((program, reg_a, reg_b, reg_c))
+    }
 
-        computer.run();
-        computer.output
+    /// Runs the program within `step_limit` instructions, returning its output
+    /// and how many of those steps were left over
+    fn run_program(
+        &self,
+        input: &str,
+        override_reg_a: Option<u64>,
+        step_limit: usize,
+    ) -> Result<(Vec<u8>, usize), SolveError> {
+        let (program, reg_a, reg_b, reg_c) = Self::parse_program(input)?;
+        let mut computer = Computer::new(program, override_reg_a.unwrap_or(reg_a), reg_b, reg_c)
+            .with_step_limit(step_limit);
+
+        computer.run()?;
+        Ok((computer.output, computer.steps_left))
     }
 }
 
 impl Solution for Day17 {
-    fn part1(&self, input: &str) -> String {
-        self.run_program(input, None)
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Self::parse_program(input).map(drop).map_err(Into::into))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (output, _) = self.run_program(input, None, STEP_LIMIT)?;
+        Ok(output
             .iter()
             .map(|n| n.to_string())
             .collect::<Vec<_>>()
-            .join(",")
+            .join(","))
     }
 
-    fn part2(&self, input: &str) -> String {
-        let reverse_program: Vec<u8> = self.parse_input(input).0.into_iter().rev().collect();
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let reverse_program: Vec<u8> = Self::parse_program(input)?.0.into_iter().rev().collect();
+        if reverse_program.is_empty() || reverse_program.len() * 3 > 64 {
+            return Err(SolveError::unsolvable(format!(
+                "cannot search register A for a program of length {}",
+                reverse_program.len()
+            )));
+        }
+        let not_found = || SolveError::unsolvable("no value of register A reproduces the program");
 
+        // Each output digit is driven by three bits of A, most significant first
+        let top_shift = 3 * (reverse_program.len() - 1);
+        // All runs of the search share one step budget
+        let mut steps_left = STEP_LIMIT;
         let mut matching_digits = 0;
         let mut reverse_digits = vec![0; reverse_program.len()];
         reverse_digits[0] = 1;
@@ -323,6 +302,9 @@ impl Solution for Day17 {
         while matching_digits < reverse_program.len() {
             // Exit with error if any reverse digit is 8 or more
             if reverse_digits.iter().any(|&n| n >= 8) {
+                if matching_digits == 0 {
+                    return Err(not_found());
+                }
                 reverse_digits[matching_digits] = 0;
                 reverse_digits[matching_digits - 1] += 1;
                 matching_digits -= 1;
@@ -331,23 +313,20 @@ impl Solution for Day17 {
             let reg_a = reverse_digits
                 .iter()
                 .enumerate()
-                .fold(0i64, |acc, (i, &digit)| acc | (digit << (45 - (i * 3))));
+                .fold(0u64, |acc, (i, &digit)| {
+                    acc | (digit << (top_shift - (i * 3)))
+                });
 
-            let result = self
-                .run_program(input, Some(reg_a))
-                .into_iter()
-                .rev()
-                .collect::<Vec<u8>>();
+            let (output, left) = self.run_program(input, Some(reg_a), steps_left)?;
+            steps_left = left;
+            let result = output.into_iter().rev().collect::<Vec<u8>>();
 
             // Count up matching digits
-            let mut current_matching = 0;
-            for i in 0..reverse_program.len() {
-                if result[i] == reverse_program[i] {
-                    current_matching += 1;
-                } else {
-                    break;
-                }
-            }
+            let current_matching = result
+                .iter()
+                .zip(&reverse_program)
+                .take_while(|(a, b)| a == b)
+                .count();
             if current_matching > matching_digits {
                 // Yay we found one!
                 matching_digits = current_matching;
@@ -357,43 +336,46 @@ impl Solution for Day17 {
             reverse_digits[matching_digits] += 1;
         }
 
-        reverse_digits
+        Ok(reverse_digits
             .iter()
             .enumerate()
-            .fold(0i64, |acc, (i, &digit)| acc | (digit << (45 - (i * 3))))
-            .to_string()
+            .fold(0u64, |acc, (i, &digit)| {
+                acc | (digit << (top_shift - (i * 3)))
+            })
+            .to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::debugger::Session;
 
     #[test]
     fn test_example_outputs() {
         // Test case 1: If register C contains 9, the program 2,6 would set register B to 1
         let mut computer = Computer::new(vec![2, 6], 0, 0, 9);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.reg_b, 1);
 
         // Test case 2: If register A contains 10, the program 5,0,5,1,5,4 would output 0,1,2
         let mut computer = Computer::new(vec![5, 0, 5, 1, 5, 4], 10, 0, 0);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.output, vec![0, 1, 2]);
 
         // Test case 3: If register A contains 2024, the program 0,1,5,4,3,0 would output 4,2,5,6,7,7,7,7,3,1,0
         let mut computer = Computer::new(vec![0, 1, 5, 4, 3, 0], 2024, 0, 0);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.output, vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
 
         // Test case 4: If register B contains 29, the program 1,7 would set register B to 26
         let mut computer = Computer::new(vec![1, 7], 0, 29, 0);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.reg_b, 26);
 
         // Test case 5: If register B contains 2024 and register C contains 43690, the program 4,0 would set register B to 44354
         let mut computer = Computer::new(vec![4, 0], 0, 2024, 43690);
-        computer.run();
+        computer.run().unwrap();
         assert_eq!(computer.reg_b, 44354);
     }
 
@@ -407,4 +389,30 @@ Register C: 0
 Program: 0,1,5,4,3,0";
         assert_eq!(Day17.part1(input), "4,6,3,5,6,3,5,2,1,0");
     }
+
+    #[test]
+    fn test_stepping_back() {
+        let mut debugger = Debugger::new(Computer::new(vec![0, 1, 5, 4, 3, 0], 729, 0, 0));
+        debugger.handle("break out").unwrap();
+        debugger.handle("continue").unwrap();
+        debugger.handle("continue").unwrap();
+        assert_eq!(debugger.machine().status(), "ip=2 out 4, A=182 B=0 C=0");
+        debugger.handle("step").unwrap();
+        assert_eq!(debugger.machine().output, vec![4, 6]);
+
+        debugger.handle("back 3").unwrap();
+        assert_eq!(debugger.machine().output, vec![4]);
+        debugger.handle("clear").unwrap();
+        debugger.handle("continue").unwrap();
+        assert_eq!(
+            debugger.machine().output,
+            vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn test_part2_sample() {
+        let input = crate::load_input(17, true);
+        assert_eq!(Day17.part2(input.text_for(2)), "117440");
+    }
 }
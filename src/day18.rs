@@ -1,182 +1,130 @@
-use crate::Solution;
-use std::collections::{BinaryHeap, HashMap};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse::{self, Line, ParseResult};
+use crate::pathfinding::bfs;
+use crate::{ParamSpec, Params, Solution, SolveError};
 
 pub struct Day18;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: i32,
-    y: i32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct State {
-    cost: i32,
-    position: Point,
-}
-
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.x.cmp(&other.position.x))
-            .then_with(|| self.position.y.cmp(&other.position.y))
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 impl Day18 {
-    fn parse_input(input: &str) -> Vec<Point> {
-        input
-            .lines()
-            .map(|line| {
-                let mut parts = line.split(',');
-                let x = parts.next().unwrap().trim().parse().unwrap();
-                let y = parts.next().unwrap().trim().parse().unwrap();
-                Point { x, y }
-            })
-            .collect()
+    /// Reads one `x,y` byte position per line, each within `0..=size`
+    fn parse_input(input: &str, size: i32) -> Result<Vec<Point>, SolveError> {
+        if size < 0 {
+            return Err(SolveError::unsolvable(format!(
+                "memory size must not be negative, got {}",
+                size
+            )));
+        }
+        fn coord(line: &mut Line, size: i32) -> ParseResult<i32> {
+            let column = line.skip_whitespace().column();
+            match line.int()? {
+                c @ 0.. if c <= size => Ok(c),
+                c => Err(line.error_at(column, format!("{} is outside the memory space", c))),
+            }
+        }
+        Ok(parse::parse_lines(input, |line| {
+            let x = coord(line, size)?;
+            line.literal(",")?;
+            let y = coord(line, size)?;
+            line.end()?;
+            Ok(Point::new(x, y))
+        })?)
     }
 
-    fn get_neighbors(
-        point: Point,
-        size: i32,
-        corrupted: &HashMap<Point, bool>,
-    ) -> Vec<(Point, i32)> {
-        let dirs = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-        dirs.iter()
-            .map(|(dx, dy)| Point {
-                x: point.x + dx,
-                y: point.y + dy,
-            })
-            .filter(|p| {
-                p.x >= 0 && p.x <= size && p.y >= 0 && p.y <= size && !corrupted.contains_key(p)
-            })
-            .map(|p| (p, 1))
-            .collect()
+    fn get_neighbors(point: Point, corrupted: &Grid<bool>) -> impl Iterator<Item = Point> + '_ {
+        point
+            .neighbors4()
+            .into_iter()
+            .filter(|&p| corrupted.get_point(p) == Some(&false))
     }
 
-    fn shortest_path(
-        start: Point,
-        end: Point,
-        size: i32,
-        corrupted: &HashMap<Point, bool>,
-    ) -> Option<i32> {
-        let mut distances: HashMap<Point, i32> = HashMap::new();
-        let mut heap = BinaryHeap::new();
-
-        distances.insert(start, 0);
-        heap.push(State {
-            cost: 0,
-            position: start,
-        });
-
-        while let Some(State { cost, position }) = heap.pop() {
-            if position == end {
-                return Some(cost);
-            }
-
-            if let Some(&best) = distances.get(&position) {
-                if cost > best {
-                    continue;
-                }
-            }
-
-            for (next, edge_cost) in Self::get_neighbors(position, size, corrupted) {
-                let next_cost = cost + edge_cost;
-
-                let is_better = distances
-                    .get(&next)
-                    .map_or(true, |&current| next_cost < current);
-
-                if is_better {
-                    distances.insert(next, next_cost);
-                    heap.push(State {
-                        cost: next_cost,
-                        position: next,
-                    });
-                }
-            }
+    /// Memory space of `size + 1` cells square, with the given bytes marked as corrupted
+    fn memory_space(size: i32, bytes: &[Point]) -> Grid<bool> {
+        let side = size as usize + 1;
+        let mut corrupted = Grid::new(side, side, false);
+        for p in bytes {
+            corrupted[*p] = true;
         }
+        corrupted
+    }
 
-        None
+    fn shortest_path(start: Point, end: Point, corrupted: &Grid<bool>) -> Option<usize> {
+        let path = bfs(start, |&p| Self::get_neighbors(p, corrupted), |&p| p == end)?;
+        Some(path.len() - 1)
     }
 }
 
 impl Solution for Day18 {
-    fn part1(&self, input: &str) -> String {
-        let points = Self::parse_input(input);
-        let size = if input.lines().count() < 20 { 6 } else { 70 }; // Use 6 for sample, 70 for real input
+    fn try_parse(&self, input: &str, params: &Params) -> Option<Result<(), SolveError>> {
+        Some(
+            params
+                .get("size")
+                .and_then(|size| Self::parse_input(input, size))
+                .map(drop),
+        )
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new("size", "70", "Largest coordinate of the memory space"),
+            ParamSpec::new("bytes", "1024", "Number of fallen bytes in part 1"),
+        ]
+    }
+
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let size: i32 = params.get("size")?;
+        let points = Self::parse_input(input, size)?;
+        let bytes: usize = params.get("bytes")?;
 
-        // Take only first 1024 points for part 1
-        let corrupted: HashMap<Point, bool> =
-            points.into_iter().take(1024).map(|p| (p, true)).collect();
+        let corrupted = Self::memory_space(size, &points[..bytes.min(points.len())]);
 
-        let start = Point { x: 0, y: 0 };
-        let end = Point { x: size, y: size };
+        let start = Point::new(0, 0);
+        let end = Point::new(size, size);
 
-        match Self::shortest_path(start, end, size, &corrupted) {
-            Some(steps) => steps.to_string(),
-            None => "No path found".to_string(),
+        match Self::shortest_path(start, end, &corrupted) {
+            Some(steps) => Ok(steps.to_string()),
+            None => Err(SolveError::unsolvable("the exit cannot be reached")),
         }
     }
 
-    fn part2(&self, input: &str) -> String {
-        let points = Self::parse_input(input);
-        let size = if input.lines().count() < 20 { 6 } else { 70 };
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let size: i32 = params.get("size")?;
+        let points = Self::parse_input(input, size)?;
 
-        let mut corrupted: HashMap<Point, bool> = HashMap::new();
-        let start = Point { x: 0, y: 0 };
-        let end = Point { x: size, y: size };
+        let mut corrupted = Self::memory_space(size, &[]);
+        let start = Point::new(0, 0);
+        let end = Point::new(size, size);
 
         // Try each point in sequence until we find one that blocks all paths
-        for (_, point) in points.iter().enumerate() {
-            corrupted.insert(*point, true);
+        for point in points.iter() {
+            corrupted[*point] = true;
 
-            if Self::shortest_path(start, end, size, &corrupted).is_none() {
+            if Self::shortest_path(start, end, &corrupted).is_none() {
                 // Found the blocking point - return its coordinates
-                return format!("{},{}", point.x, point.y);
+                return Ok(point.to_string());
             }
         }
 
-        "No blocking point found".to_string()
+        Err(SolveError::unsolvable("no byte cuts off the exit"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::read_input;
+    use crate::load_input;
 
     #[test]
     fn test_part1_sample() {
-        let input = "\
-5,4
-4,2
-4,5
-3,0
-2,1
-6,3
-2,4
-1,5
-0,6
-3,3
-2,6
-5,1";
-        let day18 = Day18;
-        assert_eq!(day18.part1(input), "22");
+        let input = load_input(18, true);
+        let params = input.params_for(&Day18).unwrap();
+        assert_eq!(Day18.part1_with(&input.text, &params), "22");
     }
 
     #[test]
     fn test_part2_sample() {
-        let input = read_input(18, true);
-        let day18 = Day18;
-        assert_ne!(day18.part2(&input), "Not implemented");
+        let input = load_input(18, true);
+        let params = input.params_for(&Day18).unwrap();
+        assert_eq!(Day18.part2_with(&input.text, &params), "6,1");
     }
 }
@@ -1,102 +1,96 @@
-use crate::Solution;
-use itertools::Itertools;
-use std::collections::HashMap;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 pub struct Day19;
 
 impl Day19 {
-    fn count_pattern_solutions(
-        pattern: &str,
-        towels: &[&str],
-        cache: &mut HashMap<String, u64>,
-    ) -> u64 {
-        // Check cache first
-        if let Some(&result) = cache.get(pattern) {
-            return result;
-        }
-
-        // Base case: if pattern is empty, we've found one valid solution
-        if pattern.is_empty() {
-            return 1;
-        }
-
-        let first_char = pattern.chars().next().unwrap();
-
-        // Try each available towel that could match the start of our pattern
-        let mut total = 0;
-        for &towel in towels
-            .iter()
-            .filter(|&&t| !t.is_empty() && t.starts_with(first_char))
-        {
-            if pattern.starts_with(towel) {
-                let remaining = &pattern[towel.len()..];
-                if remaining.len() < pattern.len() {
-                    total += Self::count_pattern_solutions(remaining, towels, cache);
-                }
+    /// Ways to build `pattern` from the towels, or `None` if that count
+    /// overflows. Works back from the end of the pattern, so each suffix is
+    /// counted once and long patterns don't recurse deeply.
+    fn count_pattern_solutions(pattern: &str, towels: &[&str]) -> Option<u64> {
+        // ways[i] is the number of arrangements of pattern[i..]
+        let mut ways = vec![0u64; pattern.len() + 1];
+        ways[pattern.len()] = 1;
+
+        for start in (0..pattern.len()).rev() {
+            let rest = &pattern[start..];
+            let mut total = 0u64;
+            for towel in towels.iter().filter(|t| rest.starts_with(**t)) {
+                total = total.checked_add(ways[start + towel.len()])?;
             }
+            ways[start] = total;
         }
+        Some(ways[0])
+    }
 
-        // Cache the result before returning
-        cache.insert(pattern.to_string(), total);
-        total
+    /// Reads the comma separated towels, a blank line, then one pattern per line
+    fn parse_input(input: &str) -> ParseResult<(Vec<&str>, Vec<&str>)> {
+        let [towels, patterns] = parse::sections(input)?;
+        let [mut line] = towels.exact_lines()?;
+        let towels = line.list(",", |l| l.skip_whitespace().word())?;
+        line.end()?;
+
+        let patterns = patterns
+            .lines()
+            .map(|mut line| {
+                let pattern = line.word()?;
+                line.end()?;
+                Ok(pattern)
+            })
+            .collect::<ParseResult<_>>()?;
+        Ok((towels, patterns))
     }
 
-    fn parse_input(input: &str) -> (Vec<&str>, Vec<&str>) {
-        let mut parts = input.trim().split("\n\n");
-        let towels: Vec<&str> = parts
-            .next()
-            .unwrap()
-            .split(", ")
-            .sorted_by_key(|s| std::cmp::Reverse(s.len()))
-            .collect();
-        let patterns = parts.next().unwrap().lines().collect();
-        (towels, patterns)
+    fn solution_counts(input: &str) -> Result<Vec<(&str, u64)>, SolveError> {
+        let (towels, patterns) = Day19::parse_input(input)?;
+        patterns
+            .into_iter()
+            .map(|pattern| {
+                let solutions =
+                    Day19::count_pattern_solutions(pattern, &towels).ok_or_else(|| {
+                        SolveError::unsolvable("arrangement count overflowed 64 bits")
+                    })?;
+                Ok((pattern, solutions))
+            })
+            .collect()
     }
 }
 
 impl Solution for Day19 {
-    fn part1(&self, input: &str) -> String {
-        let (towels, patterns) = Day19::parse_input(input);
-        let mut cache = HashMap::new();
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Day19::parse_input(input).map(drop).map_err(Into::into))
+    }
 
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
         let mut possible_count = 0;
-        for pattern in patterns.iter() {
-            let solutions = Day19::count_pattern_solutions(pattern, &towels, &mut cache);
-            let can_make = solutions > 0;
-            println!(
-                "Pattern '{}': {}",
-                pattern,
-                if can_make { "✓" } else { "✗" }
-            );
-            if can_make {
+        for (pattern, solutions) in Day19::solution_counts(input)? {
+            if solutions > 0 {
+                crate::explain!("possible", pattern = pattern);
                 possible_count += 1;
+            } else {
+                crate::explain!("impossible", pattern = pattern);
             }
         }
 
-        possible_count.to_string()
+        Ok(possible_count.to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let (towels, patterns) = Day19::parse_input(input);
-        let mut cache = HashMap::new();
-
-        let total = patterns
-            .iter()
-            .map(|pattern| {
-                let solutions = Day19::count_pattern_solutions(pattern, &towels, &mut cache);
-                println!("Pattern '{}': {} solutions", pattern, solutions);
-                solutions
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let total = Day19::solution_counts(input)?
+            .into_iter()
+            .map(|(pattern, solutions)| {
+                crate::explain!("arranged", pattern = pattern, ways = solutions);
+                solutions as u128
             })
-            .sum::<u64>();
+            .sum::<u128>();
 
-        total.to_string()
+        Ok(total.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::read_input;
 
     #[test]
     fn test_part1_sample() {
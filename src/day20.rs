@@ -1,129 +1,85 @@
-use crate::Solution;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse;
+use crate::pathfinding::bfs_distances;
+use crate::{ParamSpec, Params, Solution, SolveError};
 use rayon::prelude::*;
-use std::collections::{HashSet, VecDeque};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub struct Day20;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Pos {
-    row: i32,
-    col: i32,
-}
-
-impl Pos {
-    fn new(row: i32, col: i32) -> Self {
-        Self { row, col }
+impl Day20 {
+    fn parse_input(input: &str) -> Result<(Grid<char>, Point, Point), SolveError> {
+        let grid = parse::grid_with(input, |c| matches!(c, '#' | '.' | 'S' | 'E').then_some(c))?;
+        let find = |ch| {
+            grid.find(&ch)
+                .map(Point::from_grid)
+                .ok_or_else(|| SolveError::unsolvable(format!("no '{}' on the racetrack", ch)))
+        };
+        let (start, end) = (find('S')?, find('E')?);
+
+        Ok((grid, start, end))
     }
 
-    fn neighbors(&self) -> Vec<Pos> {
-        vec![
-            Pos::new(self.row - 1, self.col),
-            Pos::new(self.row + 1, self.col),
-            Pos::new(self.row, self.col - 1),
-            Pos::new(self.row, self.col + 1),
-        ]
+    /// The track distances, after checking that the end is on the track
+    fn race(
+        grid: &Grid<char>,
+        start: Point,
+        end: Point,
+    ) -> Result<HashMap<Point, i32>, SolveError> {
+        let track = Self::track_distances(grid, start);
+        let Some(length) = track.get(&end) else {
+            return Err(SolveError::unsolvable(
+                "the end cannot be reached from the start",
+            ));
+        };
+        crate::explain!("track", length = length);
+        Ok(track)
     }
-}
-
-impl Day20 {
-    fn parse_input(input: &str) -> (Vec<Vec<char>>, Pos, Pos) {
-        let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        let mut start = Pos::new(0, 0);
-        let mut end = Pos::new(0, 0);
-
-        for (row, line) in grid.iter().enumerate() {
-            for (col, &ch) in line.iter().enumerate() {
-                if ch == 'S' {
-                    start = Pos::new(row as i32, col as i32);
-                } else if ch == 'E' {
-                    end = Pos::new(row as i32, col as i32);
-                }
-            }
-        }
 
-        (grid, start, end)
+    fn cell(grid: &Grid<char>, pos: Point) -> Option<char> {
+        grid.get_point(pos).copied()
     }
 
-    fn find_normal_path(grid: &[Vec<char>], start: Pos, end: Pos) -> (i32, HashSet<Pos>) {
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-        let mut path = HashSet::new();
-
-        queue.push_back((start, 0, vec![start]));
-        visited.insert(start);
-
-        while let Some((pos, steps, current_path)) = queue.pop_front() {
-            if pos == end {
-                path.extend(current_path);
-                return (steps, path);
-            }
-
-            for next_pos in pos.neighbors() {
-                if next_pos.row < 0
-                    || next_pos.row >= grid.len() as i32
-                    || next_pos.col < 0
-                    || next_pos.col >= grid[0].len() as i32
-                {
-                    continue;
-                }
-
-                let cell = grid[next_pos.row as usize][next_pos.col as usize];
-                if cell != '#' && visited.insert(next_pos) {
-                    let mut new_path = current_path.clone();
-                    new_path.push(next_pos);
-                    queue.push_back((next_pos, steps + 1, new_path));
-                }
-            }
-        }
-
-        panic!("No path found!");
+    /// Picoseconds from the start to every track cell. There is only a single
+    /// track, so the normal time between two cells is the difference of theirs.
+    fn track_distances(grid: &Grid<char>, start: Point) -> HashMap<Point, i32> {
+        bfs_distances(start, |&pos| {
+            pos.neighbors4()
+                .into_iter()
+                .filter(|&next| Self::cell(grid, next).is_some_and(|c| c != '#'))
+        })
+        .into_iter()
+        .map(|(pos, steps)| (pos, steps as i32))
+        .collect()
     }
 
-    fn find_shortcuts(grid: &[Vec<char>], path: &HashSet<Pos>) -> Vec<i32> {
+    fn find_shortcuts(grid: &Grid<char>, track: &HashMap<Point, i32>) -> Vec<i32> {
         let mut shortcuts = Vec::new();
         let mut wall_tiles = HashSet::new();
 
-        // Find all wall tiles adjacent to path tiles
-        for &pos in path {
-            for neighbor in pos.neighbors() {
-                if neighbor.row >= 0
-                    && neighbor.row < grid.len() as i32
-                    && neighbor.col >= 0
-                    && neighbor.col < grid[0].len() as i32
-                {
-                    let cell = grid[neighbor.row as usize][neighbor.col as usize];
-                    if cell == '#' {
-                        wall_tiles.insert(neighbor);
-                    }
+        // Find all wall tiles adjacent to track tiles
+        for &pos in track.keys() {
+            for neighbor in pos.neighbors4() {
+                if Self::cell(grid, neighbor) == Some('#') {
+                    wall_tiles.insert(neighbor);
                 }
             }
         }
 
-        // For each wall tile, check all pairs of path neighbors
+        // For each wall tile, check all pairs of track neighbors
         for wall in wall_tiles {
-            let mut path_neighbors = Vec::new();
-            for neighbor in wall.neighbors() {
-                if neighbor.row >= 0
-                    && neighbor.row < grid.len() as i32
-                    && neighbor.col >= 0
-                    && neighbor.col < grid[0].len() as i32
-                {
-                    if path.contains(&neighbor) {
-                        path_neighbors.push(neighbor);
-                    }
-                }
-            }
+            let track_neighbors: Vec<i32> = wall
+                .neighbors4()
+                .iter()
+                .filter_map(|neighbor| track.get(neighbor).copied())
+                .collect();
 
             // Check all pairs of neighbors
-            for i in 0..path_neighbors.len() {
-                for j in i + 1..path_neighbors.len() {
-                    let start = path_neighbors[i];
-                    let end = path_neighbors[j];
-
-                    // Calculate time saved: normal path between neighbors - 2
-                    let normal_time = Self::find_normal_path(grid, start, end).0;
+            for i in 0..track_neighbors.len() {
+                for j in i + 1..track_neighbors.len() {
+                    // Calculate time saved: normal time between neighbors - 2
+                    let normal_time = (track_neighbors[i] - track_neighbors[j]).abs();
                     let shortcut_time = 2; // Always takes 2 steps through the wall
                     let saved = normal_time - shortcut_time;
                     if saved > 0 {
@@ -136,16 +92,25 @@ impl Day20 {
         shortcuts
     }
 
-    fn find_long_shortcuts(
-        grid: &[Vec<char>],
-        path: &HashSet<Pos>,
-        max_shortcut_length: i32,
-    ) -> Vec<i32> {
-        let path_points: Vec<_> = path.iter().collect();
+    /// How many cheats save each amount, smallest saving first, as the puzzle
+    /// lists them. Recorded here, on the part's thread, after any parallel
+    /// search is done.
+    fn explain_savings(time_savings: &[i32]) {
+        if !crate::explain::enabled() {
+            return;
+        }
+        let mut counts = BTreeMap::new();
+        for &saving in time_savings {
+            *counts.entry(saving).or_insert(0) += 1;
+        }
+        for (saving, count) in counts {
+            crate::explain!("cheats", saving = saving, count = count);
+        }
+    }
+
+    fn find_long_shortcuts(track: &HashMap<Point, i32>, max_shortcut_length: i32) -> Vec<i32> {
+        let path_points: Vec<_> = track.iter().collect();
         let total_pairs = (path_points.len() * (path_points.len() - 1)) / 2;
-        let pairs_checked = Arc::new(AtomicUsize::new(0));
-        let progress_interval = total_pairs / 100; // Show progress every 1%
-        println!("Total point pairs to check: {}", total_pairs);
 
         // Create all pairs of indices
         let pairs: Vec<_> = (0..path_points.len())
@@ -156,27 +121,16 @@ impl Day20 {
         let shortcuts: Vec<_> = pairs
             .par_iter()
             .filter_map(|&(i, j)| {
-                let pairs_checked_ref = Arc::clone(&pairs_checked);
-                let current = pairs_checked_ref.fetch_add(1, Ordering::Relaxed);
-                if progress_interval > 0 && current % progress_interval == 0 {
-                    println!(
-                        "Progress: {:.1}% ({}/{})",
-                        (current as f64 / total_pairs as f64) * 100.0,
-                        current,
-                        total_pairs
-                    );
-                }
-
-                let start = *path_points[i];
-                let end = *path_points[j];
+                let (&start, &start_time) = path_points[i];
+                let (&end, &end_time) = path_points[j];
 
                 // Calculate manhattan distance
-                let manhattan_dist = (end.row - start.row).abs() + (end.col - start.col).abs();
+                let manhattan_dist = start.manhattan(end);
 
                 // Only consider points that are within max_shortcut_length manhattan distance
                 if manhattan_dist <= max_shortcut_length {
-                    // Calculate normal path length between these points
-                    let normal_time = Self::find_normal_path(grid, start, end).0;
+                    // Normal path length between these points
+                    let normal_time = (end_time - start_time).abs();
 
                     // If normal path is longer than manhattan distance, we found a shortcut
                     if normal_time > manhattan_dist {
@@ -191,113 +145,101 @@ impl Day20 {
             })
             .collect();
 
-        println!("Finished checking all {} pairs", total_pairs);
-        println!("Found {} shortcuts", shortcuts.len());
+        // Recorded once the parallel search is done, since events recorded on
+        // rayon's threads never reach the report
+        crate::explain!("shortcuts", pairs = total_pairs, found = shortcuts.len());
         shortcuts
     }
 }
 
 impl Solution for Day20 {
-    fn part1(&self, input: &str) -> String {
-        let (grid, start, end) = Self::parse_input(input);
-        let (normal_time, path) = Self::find_normal_path(&grid, start, end);
-        println!("Normal path length: {}", normal_time);
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(Self::parse_input(input).map(drop))
+    }
 
-        let time_savings = Self::find_shortcuts(&grid, &path);
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new(
+                "min_saving",
+                "100",
+                "Only count cheats saving at least this many picoseconds",
+            ),
+            ParamSpec::new(
+                "cheat_length",
+                "20",
+                "Maximum cheat duration in picoseconds for part 2",
+            ),
+        ]
+    }
 
-        // Count occurrences of each time saving
-        let mut counts: std::collections::HashMap<i32, i32> = std::collections::HashMap::new();
-        for &saving in &time_savings {
-            *counts.entry(saving).or_insert(0) += 1;
-        }
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let min_saving: i32 = params.get("min_saving")?;
+        let (grid, start, end) = Self::parse_input(input)?;
+        let track = Self::race(&grid, start, end)?;
 
-        // Print in ascending order
-        let mut savings: Vec<_> = counts.iter().collect();
-        savings.sort_by_key(|&(k, _)| k);
-
-        for (saving, count) in savings {
-            if *count == 1 {
-                println!("There is one cheat that saves {} picoseconds.", saving);
-            } else {
-                println!(
-                    "There are {} cheats that save {} picoseconds.",
-                    count, saving
-                );
-            }
-        }
+        let time_savings = Self::find_shortcuts(&grid, &track);
 
-        time_savings
+        Self::explain_savings(&time_savings);
+
+        Ok(time_savings
             .iter()
-            .filter(|&&x| x >= 100)
+            .filter(|&&x| x >= min_saving)
             .count()
-            .to_string()
+            .to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let (grid, start, end) = Self::parse_input(input);
-        let (normal_time, path) = Self::find_normal_path(&grid, start, end);
-        println!("Normal path length: {}", normal_time);
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        let min_saving: i32 = params.get("min_saving")?;
+        let cheat_length: i32 = params.get("cheat_length")?;
+        let (grid, start, end) = Self::parse_input(input)?;
+        let track = Self::race(&grid, start, end)?;
 
-        let time_savings = Self::find_long_shortcuts(&grid, &path, 20);
+        let time_savings = Self::find_long_shortcuts(&track, cheat_length);
 
-        // Count occurrences of each time saving
-        let mut counts: std::collections::HashMap<i32, i32> = std::collections::HashMap::new();
-        for &saving in &time_savings {
-            *counts.entry(saving).or_insert(0) += 1;
-        }
+        Self::explain_savings(&time_savings);
 
-        // Print in ascending order
-        let mut savings: Vec<_> = counts.iter().collect();
-        savings.sort_by_key(|&(k, _)| k);
-
-        for (saving, count) in savings {
-            println!(
-                "There are {} cheats that save {} picoseconds.",
-                count, saving
-            );
-        }
-
-        time_savings
+        Ok(time_savings
             .iter()
-            .filter(|&&x| x >= 100)
+            .filter(|&&x| x >= min_saving)
             .count()
-            .to_string()
+            .to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const SAMPLE: &str = "###############
-#...#...#.....#
-#.#.#.#.#.###.#
-#S#...#.#.#...#
-#######.#.#.###
-#######.#.#...#
-#######.#.###.#
-###..E#...#...#
-###.#######.###
-#...###...#...#
-#.#####.#.###.#
-#.#...#.#.#...#
-#.#.#.#.#.#.###
-#...#...#...###
-###############";
+    use crate::load_input;
 
     #[test]
     fn test_part1_sample() {
-        let day = Day20;
-        let result = day.part1(SAMPLE);
-        assert_eq!(result, "0");
+        let input = load_input(20, true);
+        let params = input.params_for(&Day20).unwrap();
+        assert_eq!(Day20.part1_with(&input.text, &params), "1");
+
+        let params = Day20.default_params().with("min_saving", 20);
+        assert_eq!(Day20.part1_with(&input.text, &params), "5");
+    }
+
+    #[test]
+    fn test_explains_savings() {
+        let input = load_input(20, true);
+        let params = Day20.default_params().with("min_saving", 20);
+        let events = crate::explain::explained(|| {
+            Day20.part1_with(&input.text, &params);
+        });
+        assert_eq!(events[0], "track: length=84");
+        assert_eq!(events[1], "cheats: saving=2, count=14");
+        assert_eq!(events.last().unwrap(), "cheats: saving=64, count=1");
+        assert_eq!(events.len(), 12);
     }
 
     #[test]
     fn test_part2_sample() {
-        let day = Day20;
-        let (grid, start, end) = Day20::parse_input(SAMPLE);
-        let (normal_time, path) = Day20::find_normal_path(&grid, start, end);
-        let time_savings = Day20::find_long_shortcuts(&grid, &path, 20);
+        let input = load_input(20, true);
+        let (grid, start, _) = Day20::parse_input(&input.text).unwrap();
+        let track = Day20::track_distances(&grid, start);
+        let time_savings = Day20::find_long_shortcuts(&track, 20);
 
         // Count occurrences of each time saving
         let mut counts: std::collections::HashMap<i32, i32> = std::collections::HashMap::new();
@@ -310,4 +252,11 @@ mod tests {
         assert_eq!(*counts.get(&74).unwrap_or(&0), 4); // 4 cheats that save 74 picoseconds
         assert_eq!(*counts.get(&72).unwrap_or(&0), 22); // 22 cheats that save 72 picoseconds
     }
+
+    #[test]
+    fn test_part2_sample_with_metadata() {
+        let input = load_input(20, true);
+        let params = input.params_for(&Day20).unwrap();
+        assert_eq!(Day20.part2_with(&input.text, &params.for_part(2)), "285");
+    }
 }
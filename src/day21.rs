@@ -4,13 +4,13 @@
 // The core idea is to model the keypads as graphs and find shortest paths between buttons,
 // while handling multiple layers of robot control through recursive path finding
 
+use crate::geometry::Point;
 use itertools::Itertools;
 use std::collections::{HashMap, VecDeque};
 use std::iter;
 
-// Position on a keypad, using zero-based row and column indices
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Pos(usize, usize);
+// Position on a keypad: x is the zero-based column and y the row
+type Pos = Point<usize>;
 
 // Find all possible shortest paths between two positions on a keypad while avoiding a gap
 // Uses a breadth-first search (BFS) to ensure we find all shortest paths
@@ -18,9 +18,9 @@ struct Pos(usize, usize);
 fn paths(a: Pos, b: Pos, gap: Pos) -> Vec<String> {
     let mut q = VecDeque::from([(a, String::new())]);
     let mut res = vec![];
-    while let Some((Pos(i, j), mut path)) = q.pop_front() {
+    while let Some((Point { x: j, y: i }, mut path)) = q.pop_front() {
         // Found target position - add the final 'A' press and save this path
-        if Pos(i, j) == b {
+        if Point::new(j, i) == b {
             path.push('A');
             res.push(path);
             continue;
@@ -33,28 +33,28 @@ fn paths(a: Pos, b: Pos, gap: Pos) -> Vec<String> {
         // 4. Add the new position and path to the queue
 
         // Move left if target is to the left and we won't cross the gap
-        if b.1 < j && !(gap.0 == i && gap.1 < j && gap.1 >= b.1) {
+        if b.x < j && !(gap.y == i && gap.x < j && gap.x >= b.x) {
             let mut new_path = path.clone();
-            new_path.extend(iter::repeat('<').take(j - b.1));
-            q.push_back((Pos(i, b.1), new_path));
+            new_path.extend(iter::repeat_n('<', j - b.x));
+            q.push_back((Point::new(b.x, i), new_path));
         }
         // Move up if target is above and won't cross gap
-        if b.0 < i && !(gap.1 == j && gap.0 < i && gap.0 >= b.0) {
+        if b.y < i && !(gap.x == j && gap.y < i && gap.y >= b.y) {
             let mut new_path = path.clone();
-            new_path.extend(iter::repeat('^').take(i - b.0));
-            q.push_back((Pos(b.0, j), new_path));
+            new_path.extend(iter::repeat_n('^', i - b.y));
+            q.push_back((Point::new(j, b.y), new_path));
         }
         // Move down if target is below and won't cross gap
-        if b.0 > i && !(gap.1 == j && gap.0 > i && gap.0 <= b.0) {
+        if b.y > i && !(gap.x == j && gap.y > i && gap.y <= b.y) {
             let mut new_path = path.clone();
-            new_path.extend(iter::repeat('v').take(b.0 - i));
-            q.push_back((Pos(b.0, j), new_path));
+            new_path.extend(iter::repeat_n('v', b.y - i));
+            q.push_back((Point::new(j, b.y), new_path));
         }
         // Move right if target is to the right and won't cross gap
-        if b.1 > j && !(gap.0 == i && gap.1 > j && gap.1 <= b.1) {
+        if b.x > j && !(gap.y == i && gap.x > j && gap.x <= b.x) {
             let mut new_path = path.clone();
-            new_path.extend(iter::repeat('>').take(b.1 - j));
-            q.push_back((Pos(i, b.1), new_path));
+            new_path.extend(iter::repeat_n('>', b.x - j));
+            q.push_back((Point::new(b.x, i), new_path));
         }
     }
     res
@@ -74,19 +74,19 @@ impl Keypad {
     //  0A
     fn numeric() -> Self {
         let keymap = HashMap::from([
-            ('7', Pos(0, 0)),
-            ('8', Pos(0, 1)),
-            ('9', Pos(0, 2)),
-            ('4', Pos(1, 0)),
-            ('5', Pos(1, 1)),
-            ('6', Pos(1, 2)),
-            ('1', Pos(2, 0)),
-            ('2', Pos(2, 1)),
-            ('3', Pos(2, 2)),
-            ('0', Pos(3, 1)),
-            ('A', Pos(3, 2)),
+            ('7', Point::new(0, 0)),
+            ('8', Point::new(1, 0)),
+            ('9', Point::new(2, 0)),
+            ('4', Point::new(0, 1)),
+            ('5', Point::new(1, 1)),
+            ('6', Point::new(2, 1)),
+            ('1', Point::new(0, 2)),
+            ('2', Point::new(1, 2)),
+            ('3', Point::new(2, 2)),
+            ('0', Point::new(1, 3)),
+            ('A', Point::new(2, 3)),
         ]);
-        let gap = Pos(3, 0);
+        let gap = Point::new(0, 3);
         Keypad { keymap, gap }
     }
 
@@ -95,13 +95,13 @@ impl Keypad {
     // <v>
     fn directional() -> Self {
         let keymap = HashMap::from([
-            ('^', Pos(0, 1)),
-            ('A', Pos(0, 2)),
-            ('<', Pos(1, 0)),
-            ('v', Pos(1, 1)),
-            ('>', Pos(1, 2)),
+            ('^', Point::new(1, 0)),
+            ('A', Point::new(2, 0)),
+            ('<', Point::new(0, 1)),
+            ('v', Point::new(1, 1)),
+            ('>', Point::new(2, 1)),
         ]);
-        let gap = Pos(0, 0);
+        let gap = Point::new(0, 0);
         Keypad { keymap, gap }
     }
 
@@ -127,10 +127,10 @@ fn shortest_len(
     depth: usize,
     max_depth: usize,
     cache: &mut HashMap<(usize, String), usize>,
-) -> usize {
+) -> Option<usize> {
     // Check if we've already computed this result
     if let Some(&cached) = cache.get(&(depth, code.clone())) {
-        return cached;
+        return Some(cached);
     }
 
     // Choose which keypad to use based on depth
@@ -140,54 +140,115 @@ fn shortest_len(
     // 1. Find all possible paths between them on the current keypad
     // 2. If at max depth, take the shortest path length
     // 3. Otherwise, recursively find shortest sequence for each path
+    // Lengths grow exponentially with depth, so give up with None on overflow
     let res = iter::once('A')
         .chain(code.chars())
         .tuple_windows()
         .map(|(a, b)| {
             let paths = kp.paths(a, b);
             if depth == max_depth {
-                paths.iter().map(String::len).min().unwrap()
+                paths.iter().map(String::len).min()
             } else {
                 paths
                     .into_iter()
                     .map(|path| shortest_len(np, dp, path, depth + 1, max_depth, cache))
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter()
                     .min()
-                    .unwrap()
             }
         })
-        .sum::<usize>();
+        .try_fold(0usize, |total, len| total.checked_add(len?))?;
 
     // Cache and return the result
     cache.insert((depth, code), res);
-    res
+    Some(res)
 }
 
-// Part 1: Find complexity sum for 2 layers of robots
-pub fn part1(input: &str) -> usize {
+// Reads a door code: three digits and an `A`, returning it with its numeric part
+fn parse_code<'a>(line: &mut Line<'a>) -> ParseResult<(&'a str, usize)> {
+    let text = line.text();
+    let mut numeric = 0;
+    for _ in 0..3 {
+        numeric = numeric * 10 + line.digit()? as usize;
+    }
+    line.literal("A")?;
+    line.end()?;
+    Ok((&text[..4], numeric))
+}
+
+// Sum of complexities for typing every code through the given number of robot layers
+fn complexity_sum(input: &str, robots: usize) -> Result<usize, SolveError> {
+    // Sequence lengths overflow long before this many layers, and each layer
+    // is a level of recursion
+    const MAX_ROBOTS: usize = 64;
+    if robots > MAX_ROBOTS {
+        return Err(SolveError::unsolvable(format!(
+            "at most {} robots are supported, got {}",
+            MAX_ROBOTS, robots
+        )));
+    }
+    let overflow = || SolveError::unsolvable("the button press count overflowed");
+
     let np = Keypad::numeric();
     let dp = Keypad::directional();
     let mut cache = HashMap::new();
-    input
-        .lines()
-        .map(|code| {
-            shortest_len(&np, &dp, code.to_string(), 0, 2, &mut cache)
-                * code[0..3].parse::<usize>().unwrap()
+    parse::parse_lines(input, parse_code)?
+        .into_iter()
+        .try_fold(0usize, |sum, (code, numeric)| {
+            let len = shortest_len(&np, &dp, code.to_string(), 0, robots, &mut cache)
+                .ok_or_else(overflow)?;
+            len.checked_mul(numeric)
+                .and_then(|complexity| sum.checked_add(complexity))
+                .ok_or_else(overflow)
         })
-        .sum()
+}
+
+// Part 1: Find complexity sum for 2 layers of robots
+pub fn part1(input: &str) -> Result<usize, SolveError> {
+    complexity_sum(input, 2)
 }
 
 // Part 2: Same as part 1 but with 25 layers of robots
-pub fn part2(input: &str) -> usize {
-    let np = Keypad::numeric();
-    let dp = Keypad::directional();
-    let mut cache = HashMap::new();
-    input
-        .lines()
-        .map(|code| {
-            shortest_len(&np, &dp, code.to_string(), 0, 25, &mut cache)
-                * code[0..3].parse::<usize>().unwrap()
-        })
-        .sum()
+pub fn part2(input: &str) -> Result<usize, SolveError> {
+    complexity_sum(input, 25)
+}
+
+use crate::parse::{self, Line, ParseResult};
+use crate::{ParamSpec, Params, Solution, SolveError};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(
+            parse::parse_lines(input, parse_code)
+                .map(drop)
+                .map_err(Into::into),
+        )
+    }
+
+    fn params(&self) -> Vec<ParamSpec> {
+        vec![
+            ParamSpec::new(
+                "part1_robots",
+                "2",
+                "Directional keypad robots between you and the door in part 1",
+            ),
+            ParamSpec::new(
+                "part2_robots",
+                "25",
+                "Directional keypad robots between you and the door in part 2",
+            ),
+        ]
+    }
+
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        Ok(complexity_sum(input, params.get("part1_robots")?)?.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        Ok(complexity_sum(input, params.get("part2_robots")?)?.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -210,7 +271,7 @@ mod tests {
         ];
 
         for (code, expected_len, numeric_part) in test_cases {
-            let len = shortest_len(&np, &dp, code.to_string(), 0, 2, &mut cache);
+            let len = shortest_len(&np, &dp, code.to_string(), 0, 2, &mut cache).unwrap();
             assert_eq!(
                 len, expected_len,
                 "Sequence length mismatch for code {}",
@@ -226,12 +287,12 @@ mod tests {
 
         // Test total complexity for part 1
         let input = "029A\n980A\n179A\n456A\n379A";
-        assert_eq!(part1(input), 126384);
+        assert_eq!(part1(input).unwrap(), 126384);
 
         // Test part 2 with same input but 25 robots
         let mut cache = HashMap::new();
         for (code, _, numeric_part) in test_cases {
-            let len = shortest_len(&np, &dp, code.to_string(), 0, 25, &mut cache);
+            let len = shortest_len(&np, &dp, code.to_string(), 0, 25, &mut cache).unwrap();
             println!(
                 "Part 2 - Code: {}, Length: {}, Numeric: {}, Complexity: {}",
                 code,
@@ -242,17 +303,3 @@ mod tests {
         }
     }
 }
-
-use crate::Solution;
-
-pub struct Day21;
-
-impl Solution for Day21 {
-    fn part1(&self, input: &str) -> String {
-        part1(input).to_string()
-    }
-
-    fn part2(&self, input: &str) -> String {
-        part2(input).to_string()
-    }
-}
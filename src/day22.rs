@@ -1,21 +1,90 @@
-use crate::Solution;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
+
+/// Reads one initial secret per line. Secrets are read as `u32` so the first
+/// multiplication can't overflow before pruning.
+fn parse_secrets(input: &str) -> ParseResult<Vec<u64>> {
+    parse::parse_lines(input, |line| {
+        let secret: u32 = line.int()?;
+        line.end()?;
+        Ok(secret.into())
+    })
+}
 
+/// Tallies, in one pass over each buyer's prices, the bananas every change
+/// sequence would fetch
 pub struct Day22;
 
 impl Solution for Day22 {
-    fn part1(&self, input: &str) -> String {
-        let initial_secrets: Vec<u64> = input.lines().map(|line| line.parse().unwrap()).collect();
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_secrets(input).map(drop).map_err(Into::into))
+    }
+
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let initial_secrets = parse_secrets(input)?;
 
         let sum: u64 = initial_secrets
             .iter()
             .map(|&secret| generate_nth_secret(secret, 2000))
             .sum();
 
-        sum.to_string()
+        Ok(sum.to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
-        let initial_secrets: Vec<u64> = input.lines().map(|line| line.parse().unwrap()).collect();
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let initial_secrets = parse_secrets(input)?;
+
+        // Each change is in -9..=9, so four of them pack into a base-19 index
+        let mut totals = vec![0u64; 19usize.pow(4)];
+        let mut seen = vec![usize::MAX; totals.len()];
+
+        for (buyer, &secret) in initial_secrets.iter().enumerate() {
+            let prices = generate_price_sequence(secret);
+            for window in prices.windows(5) {
+                let key = window
+                    .windows(2)
+                    .fold(0, |key, pair| key * 19 + (pair[1] - pair[0] + 9) as usize);
+                // The monkey sells at the first occurrence only
+                if seen[key] != buyer {
+                    seen[key] = buyer;
+                    totals[key] += window[4] as u64;
+                }
+            }
+        }
+
+        let (best, bananas) = totals
+            .iter()
+            .enumerate()
+            .max_by_key(|&(key, &total)| (total, std::cmp::Reverse(key)))
+            .unwrap_or((0, &0));
+        crate::explain!(
+            "best_sequence",
+            changes = changes_of(best),
+            bananas = bananas
+        );
+        Ok(bananas.to_string())
+    }
+}
+
+/// The four price changes packed into `key` by [`Day22`]
+fn changes_of(key: usize) -> Vec<i32> {
+    (0..4)
+        .rev()
+        .map(|i| (key / 19usize.pow(i) % 19) as i32 - 9)
+        .collect()
+}
+
+/// Scans all 19^4 change sequences against every buyer. Kept as an oracle
+/// for [`Day22`].
+pub struct Day22BruteForce;
+
+impl Solution for Day22BruteForce {
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        Day22.try_part1_with(input, params)
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let initial_secrets = parse_secrets(input)?;
 
         // Generate all price sequences for each buyer
         let all_prices: Vec<Vec<i32>> = initial_secrets
@@ -27,11 +96,6 @@ impl Solution for Day22 {
         let mut max_bananas = 0;
         let mut best_sequence = vec![0; 4];
 
-        // Calculate total combinations for progress tracking
-        let total_combinations = 19 * 19 * 19 * 19; // (-9 to 9 = 19 possibilities per position)
-        let mut combinations_tried = 0;
-        let progress_interval = total_combinations / 100; // Update every 1%
-
         for a in -9..=9 {
             for b in -9..=9 {
                 for c in -9..=9 {
@@ -41,31 +105,18 @@ impl Solution for Day22 {
                         if total > max_bananas {
                             max_bananas = total;
                             best_sequence = sequence.clone();
-                            println!(
-                                "New best sequence {:?} gives {} bananas",
-                                best_sequence, max_bananas
-                            );
-                        }
-
-                        combinations_tried += 1;
-                        if combinations_tried % progress_interval == 0 {
-                            println!(
-                                "Progress: {:.1}% ({}/{} combinations)",
-                                (combinations_tried as f64 / total_combinations as f64) * 100.0,
-                                combinations_tried,
-                                total_combinations
-                            );
                         }
                     }
                 }
             }
         }
 
-        println!(
-            "Final best sequence {:?} gives {} bananas",
-            best_sequence, max_bananas
+        crate::explain!(
+            "best_sequence",
+            changes = best_sequence,
+            bananas = max_bananas
         );
-        max_bananas.to_string()
+        Ok(max_bananas.to_string())
     }
 }
 
@@ -117,13 +168,11 @@ fn generate_price_sequence(initial: u64) -> Vec<i32> {
     prices
 }
 
-fn calculate_total_bananas(all_prices: &[Vec<i32>], sequence: &[i32]) -> i32 {
+fn calculate_total_bananas(all_prices: &[Vec<i32>], sequence: &[i32]) -> u64 {
     let mut total = 0;
 
     // For each buyer
     for prices in all_prices {
-        let mut found = false;
-
         // Look for the sequence in price changes
         for i in 0..prices.len().saturating_sub(4) {
             let mut matches = true;
@@ -135,8 +184,7 @@ fn calculate_total_bananas(all_prices: &[Vec<i32>], sequence: &[i32]) -> i32 {
                 }
             }
             if matches {
-                total += prices[i + 4]; // Add price at the time sequence is found
-                found = true;
+                total += prices[i + 4] as u64; // Add price at the time sequence is found
                 break;
             }
         }
@@ -147,7 +195,6 @@ fn calculate_total_bananas(all_prices: &[Vec<i32>], sequence: &[i32]) -> i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::read_input;
 
     #[test]
     fn test_part1_sample() {
@@ -159,5 +206,15 @@ mod tests {
     fn test_part2_sample() {
         let input = "1\n2\n3\n2024";
         assert_eq!(Day22.part2(input), "23");
+        assert_eq!(Day22BruteForce.part2(input), "23");
+    }
+
+    #[test]
+    fn test_explains_best_sequence() {
+        let input = "1\n2\n3\n2024";
+        let events = crate::explain::explained(|| {
+            Day22.part2(input);
+        });
+        assert_eq!(events, ["best_sequence: changes=[-2,1,-1,3], bananas=23"]);
     }
 }
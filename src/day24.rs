@@ -1,4 +1,7 @@
-use crate::Solution;
+use crate::debugger::{Debugger, Session, Steppable};
+use crate::graph::Graph;
+use crate::parse::{self, ParseResult};
+use crate::{Params, Solution, SolveError};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -8,6 +11,7 @@ enum Logic {
     Xor,
 }
 
+#[derive(Clone, Copy)]
 struct Gate<'a> {
     logic: Logic,
     a: &'a str,
@@ -19,13 +23,21 @@ pub struct Day24;
 
 /// Take all wires that start with the given prefix, sort them by name, and
 /// decode them to an integer
-fn get_value(wires: &HashMap<&str, u8>, prefix: &str) -> u64 {
+fn get_value(wires: &HashMap<&str, u8>, prefix: &str) -> Result<u64, SolveError> {
     // find wires that start with prefix
     let mut wires_to_decode = wires
         .iter()
         .filter(|(name, _)| name.starts_with(prefix))
         .collect::<Vec<_>>();
 
+    if wires_to_decode.len() > 64 {
+        return Err(SolveError::unsolvable(format!(
+            "{} '{}' wires don't fit in 64 bits",
+            wires_to_decode.len(),
+            prefix
+        )));
+    }
+
     // sort wires by name
     wires_to_decode.sort();
 
@@ -41,75 +53,171 @@ fn get_value(wires: &HashMap<&str, u8>, prefix: &str) -> u64 {
         }
     }
 
-    result
+    Ok(result)
 }
 
-fn run<'a>(wires: &HashMap<&'a str, u8>, gates: &[Gate<'a>]) -> HashMap<&'a str, u8> {
-    let mut wires = wires.clone();
-    let mut changed = true;
+/// Directed graph from each gate's input wires to its output wire
+fn circuit<'a>(gates: &[Gate<'a>]) -> Graph<&'a str> {
+    let mut graph = Graph::directed();
+    for g in gates {
+        graph.add_edge(g.a, g.out);
+        graph.add_edge(g.b, g.out);
+    }
+    graph
+}
 
-    while changed {
-        changed = false;
-        for gate in gates {
-            if wires.contains_key(gate.out) {
-                continue;
-            }
+fn run<'a>(
+    wires: &HashMap<&'a str, u8>,
+    gates: &[Gate<'a>],
+) -> Result<HashMap<&'a str, u8>, SolveError> {
+    let mut evaluation = Evaluation::new(wires, gates)?;
+    while evaluation.step()? {}
+    Ok(evaluation.wires)
+}
 
-            let Some(&a) = wires.get(gate.a) else {
-                continue;
-            };
-            let Some(&b) = wires.get(gate.b) else {
-                continue;
-            };
+/// The circuit worked out one gate at a time, in dependency order so both
+/// inputs are always known
+struct Evaluation<'a> {
+    order: Vec<Gate<'a>>,
+    wires: HashMap<&'a str, u8>,
+    /// How many gates of `order` have been evaluated
+    evaluated: usize,
+}
 
-            let v = match gate.logic {
-                Logic::And => {
-                    if a == 1 && b == 1 {
-                        1
-                    } else {
-                        0
-                    }
-                }
-                Logic::Or => {
-                    if a == 1 || b == 1 {
-                        1
-                    } else {
-                        0
-                    }
-                }
-                Logic::Xor => {
-                    if a != b {
-                        1
-                    } else {
-                        0
-                    }
-                }
-            };
+impl<'a> Evaluation<'a> {
+    fn new(wires: &HashMap<&'a str, u8>, gates: &[Gate<'a>]) -> Result<Self, SolveError> {
+        let gates_by_output: HashMap<&str, Gate> = gates.iter().map(|&g| (g.out, g)).collect();
 
-            wires.insert(gate.out, v);
-            changed = true;
-        }
+        let graph = circuit(gates);
+        let order = graph.toposort().map_err(|cycle| {
+            let cycle: Vec<&str> = cycle.into_iter().map(|id| *graph.key(id)).collect();
+            SolveError::unsolvable(format!("circuit contains a loop: {}", cycle.join(" -> ")))
+        })?;
+        // Initial wires aren't gates
+        let order = order
+            .into_iter()
+            .filter_map(|id| gates_by_output.get(graph.key(id)).copied())
+            .collect();
+
+        Ok(Self {
+            order,
+            wires: wires.clone(),
+            evaluated: 0,
+        })
+    }
+
+    /// The wires starting with `prefix`, most significant first, `?` where
+    /// the value isn't known yet
+    fn bits(&self, prefix: &str) -> String {
+        let mut names: Vec<&str> = self
+            .order
+            .iter()
+            .map(|g| g.out)
+            .chain(self.wires.keys().copied())
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+            .iter()
+            .rev()
+            .map(|name| match self.wires.get(name) {
+                Some(value) => char::from(b'0' + value),
+                None => '?',
+            })
+            .collect()
+    }
+}
+
+impl<'a> Steppable for Evaluation<'a> {
+    /// The gates evaluated and the wire values
+    type Snapshot = (usize, HashMap<&'a str, u8>);
+
+    fn step(&mut self) -> Result<bool, SolveError> {
+        let Some(&gate) = self.order.get(self.evaluated) else {
+            return Ok(false);
+        };
+        self.evaluated += 1;
+        let (Some(&a), Some(&b)) = (self.wires.get(gate.a), self.wires.get(gate.b)) else {
+            return Ok(true); // fed by a wire that never gets a value
+        };
+
+        let v = match gate.logic {
+            Logic::And => a & b,
+            Logic::Or => a | b,
+            Logic::Xor => a ^ b,
+        };
+        self.wires.insert(gate.out, v);
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> Self::Snapshot {
+        (self.evaluated, self.wires.clone())
+    }
+
+    fn restore(&mut self, (evaluated, wires): &Self::Snapshot) {
+        self.evaluated = *evaluated;
+        self.wires = wires.clone();
     }
 
-    wires
+    fn render(&self) -> String {
+        ["x", "y", "z"]
+            .iter()
+            .map(|prefix| format!("{}: {}", prefix, self.bits(prefix)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The gate evaluated last
+    fn status(&self) -> String {
+        let Some(gate) = self.evaluated.checked_sub(1).map(|i| self.order[i]) else {
+            return format!("0 of {} gates", self.order.len());
+        };
+        let logic = match gate.logic {
+            Logic::And => "AND",
+            Logic::Or => "OR",
+            Logic::Xor => "XOR",
+        };
+        let value = self
+            .wires
+            .get(gate.out)
+            .map_or('?', |&v| char::from(b'0' + v));
+        format!(
+            "{} of {} gates, {} {} {} -> {} = {}",
+            self.evaluated,
+            self.order.len(),
+            gate.a,
+            logic,
+            gate.b,
+            gate.out,
+            value
+        )
+    }
+}
+
+/// A debugger on the circuit, one gate per step; see [`crate::debugger`]
+pub fn debugger(input: &str) -> Result<Box<dyn Session + '_>, SolveError> {
+    let (wires, gates) = parse_circuit(input)?;
+    Ok(Box::new(Debugger::new(Evaluation::new(&wires, &gates)?)))
 }
 
 impl Solution for Day24 {
-    fn part1(&self, input: &str) -> String {
-        let (initial_values, gates) = parse_input(input);
-        let wire_values = run(&initial_values, &gates);
-        get_value(&wire_values, "z").to_string()
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_circuit(input).map(drop).map_err(Into::into))
     }
 
-    fn part2(&self, input: &str) -> String {
-        let (initial_wires, gates) = parse_input(input);
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (initial_values, gates) = parse_circuit(input)?;
+        let wire_values = run(&initial_values, &gates)?;
+        Ok(get_value(&wire_values, "z")?.to_string())
+    }
+
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (_initial_wires, gates) = parse_circuit(input)?;
 
         // Find broken nodes by checking common patterns
-        let mut edges: HashMap<&str, Vec<&str>> = HashMap::new();
-        for g in &gates {
-            edges.entry(g.a).or_default().push(g.out);
-            edges.entry(g.b).or_default().push(g.out);
-        }
+        let graph = circuit(&gates);
+        let consumers = |wire: &str| graph.id(&wire).map_or(0, |id| graph.neighbors(id).len());
 
         let mut broken_nodes = HashSet::new();
         for g in &gates {
@@ -135,14 +243,14 @@ impl Solution for Day24 {
             }
 
             // XOR nodes (except z nodes) must always be input of exactly two other nodes
-            if g.logic == Logic::Xor && !g.out.starts_with("z") && edges[g.out].len() != 2 {
+            if g.logic == Logic::Xor && !g.out.starts_with("z") && consumers(g.out) != 2 {
                 broken_nodes.insert(g.out);
             }
 
             // AND nodes must always be input of exactly one other node
             if g.logic == Logic::And
                 && !g.out.starts_with("z")
-                && edges[g.out].len() != 1
+                && consumers(g.out) != 1
                 && !((g.a == "x00" && g.b == "y00") || (g.a == "y00" && g.b == "x00"))
             {
                 broken_nodes.insert(g.out);
@@ -152,42 +260,52 @@ impl Solution for Day24 {
         // Return the broken nodes sorted
         let mut broken_nodes = broken_nodes.into_iter().collect::<Vec<_>>();
         broken_nodes.sort();
-        broken_nodes.join(",")
+        Ok(broken_nodes.join(","))
     }
 }
 
-fn parse_input(input: &str) -> (HashMap<&str, u8>, Vec<Gate>) {
-    let (wires, gates) = input.split_once("\n\n").unwrap();
+/// Reads the `name: 0|1` initial wires, a blank line, then `a OP b -> out` gates
+fn parse_circuit(input: &str) -> ParseResult<(HashMap<&str, u8>, Vec<Gate<'_>>)> {
+    let [wires, gates] = parse::sections(input)?;
 
     let wires = wires
         .lines()
-        .map(|w| {
-            let (name, value) = w.split_once(": ").unwrap();
-            (name, if value == "1" { 1 } else { 0 })
+        .map(|mut line| {
+            let name = line.label(": ")?;
+            let column = line.column();
+            let value = line.int()?;
+            if value > 1 {
+                return Err(line.error_at(column, format!("wire value {} is not a bit", value)));
+            }
+            line.end()?;
+            Ok((name, value))
         })
-        .collect();
+        .collect::<ParseResult<_>>()?;
 
+    let mut outputs = HashSet::new();
     let gates = gates
         .lines()
-        .map(|l| {
-            let (left, out) = l.split_once(" -> ").unwrap();
-            let s = left.split_whitespace().collect::<Vec<_>>();
-            let logic = match s[1] {
+        .map(|mut line| {
+            let a = line.word()?;
+            let column = line.literal(" ")?.column();
+            let logic = match line.word()? {
                 "AND" => Logic::And,
                 "OR" => Logic::Or,
                 "XOR" => Logic::Xor,
-                _ => panic!("Invalid gate: {}", left),
+                other => return Err(line.error_at(column, format!("unknown gate '{}'", other))),
             };
-            Gate {
-                logic,
-                a: s[0],
-                b: s[2],
-                out,
+            let b = line.literal(" ")?.word()?;
+            let column = line.literal(" -> ")?.column();
+            let out = line.word()?;
+            line.end()?;
+            if !outputs.insert(out) {
+                return Err(line.error_at(column, format!("wire {} has more than one gate", out)));
             }
+            Ok(Gate { logic, a, b, out })
         })
-        .collect();
+        .collect::<ParseResult<_>>()?;
 
-    (wires, gates)
+    Ok((wires, gates))
 }
 
 #[cfg(test)]
@@ -206,4 +324,32 @@ mod tests {
         let input = read_input(24, true);
         Day24.part2(&input);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_circuit("x00: 1\n\nx00 NAND y00 -> z00")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "line 3, column 5: unknown gate 'NAND'");
+
+        let err = parse_circuit("x00: 2\n\nx00 AND y00 -> z00").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 6));
+        assert!(parse_circuit("x00: 1").is_err());
+
+        let err = parse_circuit("x00: 1\n\nx00 AND x00 -> z00\nx00 OR x00 -> z00")
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4, column 15: wire z00 has more than one gate"
+        );
+    }
+
+    #[test]
+    fn test_loop_is_an_error() {
+        let input = "x00: 1\n\nx00 AND a -> b\nb OR x00 -> a";
+        let err = Day24.try_part1_with(input, &Params::new()).unwrap_err();
+        assert!(matches!(err, SolveError::Unsolvable(_)));
+        assert!(err.to_string().contains("circuit contains a loop"));
+    }
 }
@@ -1,19 +1,16 @@
-use crate::Solution;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError, ParseResult};
+use crate::{Params, Solution, SolveError};
 
 #[derive(Debug, Clone)]
 struct Schematic {
-    grid: Vec<Vec<char>>,
+    grid: Grid<char>,
 }
 
 impl Schematic {
-    fn from_str(s: &str) -> Self {
-        let grid = s.lines().map(|line| line.chars().collect()).collect();
-        Self { grid }
-    }
-
     fn get_heights(&self, from_bottom: bool) -> Vec<usize> {
-        let width = self.grid[0].len();
-        let height = self.grid.len();
+        let width = self.grid.width();
+        let height = self.grid.height();
 
         (0..width)
             .map(|x| {
@@ -21,13 +18,13 @@ impl Schematic {
                     // For keys: count '#' from bottom up (subtract 1 to not count first #)
                     let count = (0..height)
                         .rev()
-                        .take_while(|&y| self.grid[y][x] == '#')
+                        .take_while(|&y| self.grid[(y, x)] == '#')
                         .count();
                     count.saturating_sub(1)
                 } else {
                     // For locks: count from top until we hit a '.' (subtract 1 to not count first #)
                     let pos = (0..height)
-                        .position(|y| self.grid[y][x] == '.')
+                        .position(|y| self.grid[(y, x)] == '.')
                         .unwrap_or(height);
                     pos.saturating_sub(1)
                 }
@@ -36,22 +33,52 @@ impl Schematic {
     }
 }
 
-fn parse_input(input: &str) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
-    let sections: Vec<&str> = input.split("\n\n").collect();
+/// Column heights of one lock or key
+type Heights = Vec<usize>;
+
+/// Reads the blank-line separated schematics into lock and key heights,
+/// along with the schematic height they all share
+fn parse_input(input: &str) -> ParseResult<(Vec<Heights>, Vec<Heights>, usize)> {
     let mut locks = Vec::new();
     let mut keys = Vec::new();
+    let mut size = None;
+
+    for block in parse::blocks(input) {
+        let schematic = Schematic {
+            grid: block.grid_with(|c| matches!(c, '#' | '.').then_some(c))?,
+        };
+        let dims = (schematic.grid.width(), schematic.grid.height());
+        match size {
+            None if dims.1 < 2 => {
+                return Err(ParseError::new(
+                    block.first_line(),
+                    1,
+                    "a schematic needs at least two rows",
+                ))
+            }
+            None => size = Some(dims),
+            Some(expected) if expected != dims => {
+                return Err(ParseError::new(
+                    block.first_line(),
+                    1,
+                    format!(
+                        "schematic is {}x{}, expected {}x{}",
+                        dims.0, dims.1, expected.0, expected.1
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
 
-    for section in sections {
-        let schematic = Schematic::from_str(section);
         // If top row is filled (#), it's a lock
-        if schematic.grid[0].iter().all(|&c| c == '#') {
+        if schematic.grid.row(0).iter().all(|&c| c == '#') {
             locks.push(schematic.get_heights(false));
         } else {
             keys.push(schematic.get_heights(true));
         }
     }
 
-    (locks, keys)
+    Ok((locks, keys, size.map_or(2, |(_, height)| height)))
 }
 
 fn is_compatible(lock: &[usize], key: &[usize], grid_height: usize) -> bool {
@@ -63,41 +90,35 @@ fn is_compatible(lock: &[usize], key: &[usize], grid_height: usize) -> bool {
 pub struct Day25;
 
 impl Solution for Day25 {
-    fn part1(&self, input: &str) -> String {
-        let (locks, keys) = parse_input(input);
-        let mut valid_pairs = 0;
-
-        // Get grid height from first schematic
-        let grid_height = input.lines().take_while(|l| !l.is_empty()).count();
+    fn try_parse(&self, input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        Some(parse_input(input).map(drop).map_err(Into::into))
+    }
 
-        // Debug print
-        println!("Locks:");
-        for lock in locks.iter() {
-            println!("{:?}", lock);
-        }
-        println!("\nKeys:");
-        for key in keys.iter() {
-            println!("{:?}", key);
-        }
-        println!("\nGrid height: {}", grid_height);
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
+        let (locks, keys, grid_height) = parse_input(input)?;
+        let mut valid_pairs = 0;
 
         for (i, lock) in locks.iter().enumerate() {
             for (j, key) in keys.iter().enumerate() {
                 if is_compatible(lock, key, grid_height) {
-                    println!("\nCompatible pair found:");
-                    println!("Lock {}: {:?}", i, lock);
-                    println!("Key {}: {:?}", j, key);
+                    crate::explain!(
+                        "fits",
+                        lock = i + 1,
+                        key = j + 1,
+                        lock_heights = lock,
+                        key_heights = key
+                    );
                     valid_pairs += 1;
                 }
             }
         }
 
-        valid_pairs.to_string()
+        Ok(valid_pairs.to_string())
     }
 
-    fn part2(&self, _input: &str) -> String {
+    fn try_part2_with(&self, _input: &str, _params: &Params) -> Result<String, SolveError> {
         // Part 2 not required for Day 25
-        "Not implemented".to_string()
+        Ok("Not implemented".to_string())
     }
 }
 
@@ -150,4 +171,19 @@ mod tests {
     fn test_part1_sample() {
         assert_eq!(Day25.part1(SAMPLE), "3");
     }
+
+    #[test]
+    fn test_explains_fitting_pairs() {
+        let events = crate::explain::explained(|| {
+            Day25.part1(SAMPLE);
+        });
+        assert_eq!(
+            events,
+            [
+                "fits: lock=1, key=3, lock_heights=[0,5,3,4,3], key_heights=[3,0,2,0,1]",
+                "fits: lock=2, key=2, lock_heights=[1,2,0,5,3], key_heights=[4,3,4,0,2]",
+                "fits: lock=2, key=3, lock_heights=[1,2,0,5,3], key_heights=[3,0,2,0,1]"
+            ]
+        );
+    }
 }
@@ -0,0 +1,366 @@
+//! Single-stepping simulations forward and backward.
+//!
+//! Days whose answer comes from running something one move at a time (Day 6's
+//! guard, Day 14's robots, Day 15's warehouse robot, Day 17's computer, Day
+//! 24's circuit) implement [`Steppable`] for it. A [`Debugger`] then steps it
+//! either way, runs to breakpoints and shows the state after each move, which
+//! the `debug` subcommand drives from stdin. `help` lists the commands.
+
+use crate::{get_solution, InputSource, Params, SolveError};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// How many steps back the debugger can go
+const HISTORY: usize = 1000;
+
+const HELP: &str = "\
+step [N]           take N steps forward (default 1), also an empty line
+back [N]           take N steps back
+continue           run forward to the next breakpoint or the end
+reverse            run back to the previous breakpoint or the start
+break #N           stop at step N
+break TEXT         stop where the status line contains TEXT
+clear              remove all breakpoints
+show               show the current state again
+help               show this
+quit               leave";
+
+/// Something that runs one step at a time and can be rewound
+pub trait Steppable {
+    type Snapshot;
+
+    /// Takes one step, or returns `Ok(false)` without changing anything once
+    /// finished
+    fn step(&mut self) -> Result<bool, SolveError>;
+
+    /// The current state, to [`Steppable::restore`] later
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Goes back to a snapshot taken earlier in the same run
+    fn restore(&mut self, snapshot: &Self::Snapshot);
+
+    /// The whole state, e.g. the map
+    fn render(&self) -> String;
+
+    /// Where it is, in one line; breakpoints match against this
+    fn status(&self) -> String;
+}
+
+/// Where to stop when running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    Step(usize),
+    /// The status line contains this text
+    Status(String),
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    /// `#N` for a step number, any other text to match the status line
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.strip_prefix('#') {
+            Some(step) => step
+                .parse()
+                .map(Breakpoint::Step)
+                .map_err(|_| format!("invalid step number '{}'", step)),
+            None if s.is_empty() => Err("break needs #STEP or some status text".to_string()),
+            None => Ok(Breakpoint::Status(s.to_string())),
+        }
+    }
+}
+
+/// A debugging session on one day's input; feed it lines with
+/// [`Session::handle`]
+pub trait Session {
+    /// Runs one command, returning what to show
+    fn handle(&mut self, line: &str) -> Result<String, String>;
+
+    /// Whether `quit` was given
+    fn done(&self) -> bool;
+
+    /// The current step, status and state
+    fn view(&self) -> String;
+}
+
+/// Steps a [`Steppable`] forward and back, keeping the last [`HISTORY`]
+/// snapshots
+pub struct Debugger<S: Steppable> {
+    machine: S,
+    /// Snapshots from before each step still in reach, oldest first
+    history: VecDeque<S::Snapshot>,
+    steps: usize,
+    finished: bool,
+    pub breakpoints: Vec<Breakpoint>,
+    pub done: bool,
+}
+
+impl<S: Steppable> Debugger<S> {
+    pub fn new(machine: S) -> Self {
+        Self {
+            machine,
+            history: VecDeque::new(),
+            steps: 0,
+            finished: false,
+            breakpoints: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub fn machine(&self) -> &S {
+        &self.machine
+    }
+
+    /// Steps taken from the start
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// One step forward; `false` at the end
+    pub fn forward(&mut self) -> Result<bool, SolveError> {
+        let before = self.machine.snapshot();
+        if !self.machine.step()? {
+            self.finished = true;
+            return Ok(false);
+        }
+        self.history.push_back(before);
+        if self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+        self.steps += 1;
+        Ok(true)
+    }
+
+    /// One step back; `false` at the start or the end of the history
+    pub fn back(&mut self) -> bool {
+        let Some(before) = self.history.pop_back() else {
+            return false;
+        };
+        self.machine.restore(&before);
+        self.steps -= 1;
+        self.finished = false;
+        true
+    }
+
+    /// Runs forward until a breakpoint matches or it finishes
+    pub fn run(&mut self) -> Result<(), SolveError> {
+        while self.forward()? {
+            if self.at_breakpoint() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs back until a breakpoint matches or the history runs out
+    pub fn run_back(&mut self) {
+        while self.back() {
+            if self.at_breakpoint() {
+                break;
+            }
+        }
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        let status = self.machine.status();
+        self.breakpoints.iter().any(|breakpoint| match breakpoint {
+            Breakpoint::Step(step) => *step == self.steps,
+            Breakpoint::Status(text) => status.contains(text.as_str()),
+        })
+    }
+}
+
+impl<S: Steppable> Session for Debugger<S> {
+    fn handle(&mut self, line: &str) -> Result<String, String> {
+        let (command, arg) = match line.trim().split_once(' ') {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line.trim(), ""),
+        };
+        let count = || -> Result<usize, String> {
+            match arg {
+                "" => Ok(1),
+                n => n.parse().map_err(|_| format!("invalid count '{}'", n)),
+            }
+        };
+
+        match command {
+            "" | "s" | "step" => {
+                for _ in 0..count()? {
+                    if !self.forward().map_err(|e| e.to_string())? {
+                        break;
+                    }
+                }
+            }
+            "b" | "back" => {
+                for _ in 0..count()? {
+                    if !self.back() {
+                        break;
+                    }
+                }
+            }
+            "c" | "continue" => self.run().map_err(|e| e.to_string())?,
+            "r" | "reverse" => self.run_back(),
+            "break" => {
+                self.breakpoints.push(arg.parse()?);
+                return Ok(format!("{} breakpoint(s)", self.breakpoints.len()));
+            }
+            "clear" => {
+                self.breakpoints.clear();
+                return Ok("Breakpoints cleared".to_string());
+            }
+            "show" => {}
+            "help" => return Ok(HELP.to_string()),
+            "q" | "quit" => {
+                self.done = true;
+                return Ok(String::new());
+            }
+            _ => return Err(format!("unknown command '{}', try help", command)),
+        }
+        Ok(self.view())
+    }
+
+    fn done(&self) -> bool {
+        self.done
+    }
+
+    fn view(&self) -> String {
+        format!(
+            "Step {}{}: {}\n{}",
+            self.steps,
+            if self.finished { " (finished)" } else { "" },
+            self.machine.status(),
+            self.machine.render().trim_end()
+        )
+    }
+}
+
+/// A debugger on one part of a day's input, for the days that can be stepped
+#[allow(unused_variables)]
+pub fn session<'a>(
+    day: u8,
+    part: u8,
+    input: &'a str,
+    params: &Params,
+) -> Option<Result<Box<dyn Session + 'a>, SolveError>> {
+    match day {
+        #[cfg(feature = "day06")]
+        6 => Some(crate::day06::debugger(input)),
+        #[cfg(feature = "day14")]
+        14 => Some(crate::day14::debugger(input, params)),
+        #[cfg(feature = "day15")]
+        15 => Some(crate::day15::debugger(input, part == 2)),
+        #[cfg(feature = "day17")]
+        17 => Some(crate::day17::debugger(input)),
+        #[cfg(feature = "day24")]
+        24 => Some(crate::day24::debugger(input)),
+        _ => None,
+    }
+}
+
+/// The `debug` command: steps through a day's input from stdin
+pub fn run(day: u8, part: u8, source: &InputSource, overrides: &Params) -> Result<(), String> {
+    let solution = get_solution(day).ok_or_else(|| crate::no_solution(day))?;
+    let input = source.load(day)?;
+    let mut params = input.params_for(solution.as_ref())?;
+    params.apply(overrides)?;
+    let text = input.text_for(part);
+    let mut session = session(day, part, text, &params.for_part(part))
+        .ok_or_else(|| format!("Day {} can't be stepped through", day))?
+        .map_err(|e| e.to_string())?;
+
+    println!("{}", session.view());
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while !session.done() {
+        print!("debug> ");
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let Some(line) = lines.next() else {
+            break;
+        };
+        match session.handle(&line.map_err(|e| e.to_string())?) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts up to a limit
+    struct Counter {
+        value: u32,
+        limit: u32,
+    }
+
+    impl Steppable for Counter {
+        type Snapshot = u32;
+
+        fn step(&mut self) -> Result<bool, SolveError> {
+            if self.value == self.limit {
+                return Ok(false);
+            }
+            self.value += 1;
+            Ok(true)
+        }
+
+        fn snapshot(&self) -> u32 {
+            self.value
+        }
+
+        fn restore(&mut self, snapshot: &u32) {
+            self.value = *snapshot;
+        }
+
+        fn render(&self) -> String {
+            "*".repeat(self.value as usize)
+        }
+
+        fn status(&self) -> String {
+            format!("value={}", self.value)
+        }
+    }
+
+    #[test]
+    fn test_stepping_and_breakpoints() {
+        let mut debugger = Debugger::new(Counter {
+            value: 0,
+            limit: 10,
+        });
+        assert_eq!(
+            debugger.handle("step 3"),
+            Ok("Step 3: value=3\n***".to_string())
+        );
+        assert_eq!(
+            debugger.handle("back"),
+            Ok("Step 2: value=2\n**".to_string())
+        );
+
+        debugger.handle("break value=7").unwrap();
+        debugger.handle("break #9").unwrap();
+        debugger.handle("continue").unwrap();
+        assert_eq!(debugger.machine().value, 7);
+        debugger.handle("c").unwrap();
+        assert_eq!(debugger.steps(), 9);
+        assert!(debugger
+            .handle("c")
+            .unwrap()
+            .starts_with("Step 10 (finished)"));
+
+        debugger.handle("reverse").unwrap();
+        assert_eq!(debugger.machine().value, 9);
+        debugger.handle("clear").unwrap();
+        debugger.handle("reverse").unwrap();
+        assert_eq!(debugger.steps(), 0);
+        assert!(!debugger.back());
+
+        assert!(debugger.handle("break #x").is_err());
+        assert!(debugger.handle("jump").is_err());
+        debugger.handle("quit").unwrap();
+        assert!(debugger.done());
+    }
+}
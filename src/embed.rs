@@ -0,0 +1,41 @@
+//! Inputs compiled into the binary, so it runs from any directory. Built with
+//! the `embed-inputs` feature, this holds every file under `inputs/` as it was
+//! at compile time: samples, real inputs and their encrypted copies. Without
+//! the feature it's empty.
+//!
+//! Files on disk take precedence, see [`store::read`](crate::store::read).
+
+use std::path::Path;
+
+include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+
+/// Whether the binary was built with inputs embedded
+pub fn enabled() -> bool {
+    cfg!(feature = "embed-inputs")
+}
+
+/// The embedded copy of an input file, by its path relative to the crate,
+/// e.g. `inputs/sample/01.txt`
+pub fn file(path: &Path) -> Option<&'static [u8]> {
+    let path = path.to_str()?.replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    FILES
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, contents)| *contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_files() {
+        let sample = file(Path::new("./inputs/sample/01.txt"));
+        assert_eq!(sample.is_some(), enabled());
+        if let Some(sample) = sample {
+            assert_eq!(sample, std::fs::read("inputs/sample/01.txt").unwrap());
+        }
+        assert_eq!(file(Path::new("inputs/sample/99.txt")), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Structured events explaining how an answer was reached: which level Day 2
+//! removed, which rule a Day 5 update broke, which operators balanced a Day 7
+//! equation, ...
+//!
+//! Solutions record them with [`explain!`](crate::explain!) while `--explain`
+//! is on. The runner collects each part's events into its
+//! [`PartReport`](crate::PartReport), printed under the answer or written to
+//! the JSON as `events`. Events are collected per thread, from the thread
+//! running the part.
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static EXPLAINING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether solutions record their events, see [`explain!`](crate::explain!)
+pub fn enabled() -> bool {
+    EXPLAINING.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(on: bool) {
+    EXPLAINING.store(on, Ordering::Relaxed);
+}
+
+/// One step towards an answer, e.g. `level_removed: report=4, level=3, value=1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub kind: &'static str,
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+impl Event {
+    pub fn new(kind: &'static str, fields: Vec<(&'static str, Value)>) -> Self {
+        Self { kind, fields }
+    }
+}
+
+/// `{"event": kind, field: value, ...}`
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.fields.len() + 1))?;
+        map.serialize_entry("event", self.kind)?;
+        for (name, value) in &self.fields {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.kind)?;
+        for (i, (name, value)) in self.fields.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            match value {
+                Value::String(text) => write!(f, "{}{}={}", separator, name, text)?,
+                value => write!(f, "{}{}={}", separator, name, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A field's value as JSON, for [`explain!`](crate::explain!)
+pub fn value<T: Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_else(|e| Value::String(e.to_string()))
+}
+
+pub fn record(event: Event) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+
+/// The events recorded on this thread since the last call
+pub fn take() -> Vec<Event> {
+    EVENTS.with(|events| events.take())
+}
+
+/// Runs `f` with explaining on, returning the events it recorded. The switch
+/// is shared by every thread, so tests using it take turns.
+#[cfg(test)]
+pub(crate) fn explained(f: impl FnOnce()) -> Vec<String> {
+    static TURN: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _turn = TURN.lock().unwrap_or_else(|e| e.into_inner());
+    take();
+    set_enabled(true);
+    f();
+    set_enabled(false);
+    take().iter().map(Event::to_string).collect()
+}
+
+/// Records an event while [`enabled`], e.g.
+/// `explain!("fits", lock = i, key = j)`. The values are only evaluated
+/// then, and can be anything serializable.
+#[macro_export]
+macro_rules! explain {
+    ($kind:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        if $crate::explain::enabled() {
+            $crate::explain::record($crate::explain::Event::new(
+                $kind,
+                vec![$((stringify!($name), $crate::explain::value(&$value))),*],
+            ));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_output() {
+        let event = Event::new(
+            "calibrated",
+            vec![
+                ("target", value(&190)),
+                ("expression", value("10 * 19")),
+                ("order", value(&[1, 2])),
+            ],
+        );
+        assert_eq!(
+            event.to_string(),
+            "calibrated: target=190, expression=10 * 19, order=[1,2]"
+        );
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({"event": "calibrated", "target": 190, "expression": "10 * 19", "order": [1, 2]})
+        );
+    }
+
+    #[test]
+    fn test_recording() {
+        record(Event::new("first", vec![]));
+        record(Event::new("second", vec![]));
+        let kinds: Vec<_> = take().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, ["first", "second"]);
+        assert!(take().is_empty());
+    }
+
+    #[test]
+    fn test_macro() {
+        let events = explained(|| crate::explain!("fits", lock = 1, key = [2, 3]));
+        assert_eq!(events, ["fits: lock=1, key=[2,3]"]);
+    }
+}
@@ -0,0 +1,325 @@
+//! Solutions written in other languages, run as external programs.
+//!
+//! A solver is registered by a file `external/DD-NAME.solver` of
+//! `key = value` lines:
+//!
+//! ```text
+//! command = python3 external/day22.py
+//! parts = 2
+//! timeout = 30
+//! ```
+//!
+//! It becomes variant `NAME` of day `DD`, so `--variant`, `--crosscheck`,
+//! `--check` and `scale --variant` treat it like a built-in implementation.
+//! `parts` lists the parts it solves, both by default; the built-in solution
+//! answers the others. `timeout` is in seconds, 60 by default.
+//!
+//! The command is split on whitespace, not run through a shell, and runs once
+//! per part with:
+//!
+//! - the input on stdin
+//! - `AOC_DAY` and `AOC_PART` set, and `AOC_PARAM_<NAME>` for each of the
+//!   day's parameters, e.g. `AOC_PARAM_PART1_BLINKS=25`
+//!
+//! It prints the answer to stdout and exits with status 0. Any other status
+//! is a failure, with the last line of stderr as the reason.
+
+use crate::{get_solution, Params, Solution, SolveError, Variant};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where solver registrations live, relative to the working directory like `inputs/`
+pub const SOLVER_DIR: &str = "external";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An external program standing in for some parts of a day
+pub struct ExternalSolution {
+    day: u8,
+    /// The program and its arguments, or why the registration is unusable
+    command: Result<Vec<String>, String>,
+    parts: Vec<u8>,
+    timeout: Duration,
+    builtin: Box<dyn Solution>,
+}
+
+impl ExternalSolution {
+    /// `None` if the day has no built-in solution to take parameters and the
+    /// remaining parts from
+    pub fn new(day: u8, command: Vec<String>, parts: &[u8], timeout: Duration) -> Option<Self> {
+        Some(Self {
+            day,
+            command: Ok(command),
+            parts: parts.to_vec(),
+            timeout,
+            builtin: get_solution(day)?,
+        })
+    }
+
+    /// Reads a `.solver` registration. A malformed one still loads, so the
+    /// problem is reported wherever the variant is used.
+    pub fn from_config(day: u8, config: &str) -> Option<Self> {
+        let mut solution = Self::new(day, Vec::new(), &[1, 2], DEFAULT_TIMEOUT)?;
+        if let Err(e) = solution.configure(config) {
+            solution.command = Err(e);
+        }
+        Some(solution)
+    }
+
+    fn configure(&mut self, config: &str) -> Result<(), String> {
+        for (name, value) in Params::parse_lines(config)?.iter() {
+            match name {
+                "command" => {
+                    self.command = Ok(value.split_whitespace().map(str::to_string).collect())
+                }
+                "parts" => {
+                    self.parts = value
+                        .split(',')
+                        .map(|part| match part.trim() {
+                            "1" => Ok(1),
+                            "2" => Ok(2),
+                            other => Err(format!("invalid part '{}', expected 1 or 2", other)),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "timeout" => {
+                    let seconds: f64 = value
+                        .parse()
+                        .ok()
+                        .filter(|s: &f64| s.is_finite() && *s > 0.0)
+                        .ok_or_else(|| format!("invalid timeout '{}'", value))?;
+                    self.timeout = Duration::from_secs_f64(seconds);
+                }
+                _ => return Err(format!("unknown key '{}'", name)),
+            }
+        }
+        match &self.command {
+            Ok(command) if command.is_empty() => Err("missing command".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn parts(&self) -> &[u8] {
+        &self.parts
+    }
+
+    fn solve(&self, part: u8, input: &str, params: &Params) -> Result<String, SolveError> {
+        if !self.parts.contains(&part) {
+            return match part {
+                1 => self.builtin.try_part1_with(input, params),
+                _ => self.builtin.try_part2_with(input, params),
+            };
+        }
+        let command = self
+            .command
+            .as_ref()
+            .map_err(|e| SolveError::External(e.clone()))?;
+        let fail = |message: String| SolveError::External(format!("{}: {}", command[0], message));
+
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .env("AOC_DAY", self.day.to_string())
+            .env("AOC_PART", part.to_string())
+            .envs(
+                params
+                    .iter()
+                    .map(|(name, value)| (format!("AOC_PARAM_{}", name.to_uppercase()), value)),
+            )
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| fail(format!("could not start: {}", e)))?;
+
+        // Feed and drain the pipes on their own threads so a chatty solver
+        // can't block on a full pipe while we wait for it
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_string();
+        thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let stdout = drain(child.stdout.take().expect("stdout is piped"));
+        let stderr = drain(child.stderr.take().expect("stderr is piped"));
+
+        let status = wait(&mut child, self.timeout)
+            .map_err(fail)?
+            .ok_or_else(|| fail(format!("timed out after {:?}", self.timeout)))?;
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            let reason = stderr.lines().rev().find(|l| !l.trim().is_empty());
+            return Err(fail(match reason {
+                Some(reason) => format!("{}: {}", status, reason.trim()),
+                None => status.to_string(),
+            }));
+        }
+        match stdout.trim() {
+            "" => Err(fail("printed no answer".to_string())),
+            answer => Ok(answer.to_string()),
+        }
+    }
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// The child's exit status, or `None` if it was killed for taking too long
+fn wait(child: &mut Child, timeout: Duration) -> Result<Option<ExitStatus>, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(2)));
+    }
+}
+
+impl Solution for ExternalSolution {
+    fn params(&self) -> Vec<crate::ParamSpec> {
+        self.builtin.params()
+    }
+
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(1, input, params)
+    }
+
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(2, input, params)
+    }
+}
+
+/// The solvers registered for a day in [`SOLVER_DIR`], by name
+pub fn registered(day: u8) -> Vec<Variant> {
+    registered_in(Path::new(SOLVER_DIR), day)
+}
+
+fn registered_in(dir: &Path, day: u8) -> Vec<Variant> {
+    let Ok(entries) = dir.read_dir() else {
+        return Vec::new();
+    };
+    let prefix = format!("{:02}-", day);
+    let mut variants: Vec<Variant> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".solver")?
+                .strip_prefix(&prefix)?
+                .to_string();
+            let solution = match std::fs::read_to_string(&path) {
+                Ok(config) => ExternalSolution::from_config(day, &config)?,
+                Err(e) => ExternalSolution {
+                    command: Err(e.to_string()),
+                    ..ExternalSolution::new(day, Vec::new(), &[1, 2], DEFAULT_TIMEOUT)?
+                },
+            };
+            Some(Variant {
+                name,
+                parts: solution.parts().to_vec(),
+                solution: Box::new(solution),
+            })
+        })
+        .collect();
+    variants.sort_by(|a, b| a.name.cmp(&b.name));
+    variants
+}
+
+#[cfg(all(test, feature = "day11"))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str, parts: &[u8], timeout: Duration) -> ExternalSolution {
+        let command = vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+        ExternalSolution::new(11, command, parts, timeout).unwrap()
+    }
+
+    #[test]
+    fn test_protocol() {
+        let solver = sh(
+            "read stones; echo \"$AOC_DAY/$AOC_PART/$AOC_PARAM_PART1_BLINKS: $stones\"",
+            &[1],
+            DEFAULT_TIMEOUT,
+        );
+        let params = solver.default_params();
+        assert_eq!(
+            solver.try_part1_with("125 17\n", &params),
+            Ok("11/1/25: 125 17".to_string())
+        );
+        // Part 2 isn't registered, so the built-in solution answers it
+        assert_eq!(
+            solver.try_part2_with("125 17", &params),
+            Ok("65601038650482".to_string())
+        );
+    }
+
+    #[test]
+    fn test_failures() {
+        let params = Params::new();
+        let failed = sh("echo oops >&2; exit 3", &[1, 2], DEFAULT_TIMEOUT)
+            .try_part1_with("", &params)
+            .unwrap_err();
+        assert!(
+            failed.to_string().contains("exit status: 3: oops"),
+            "{}",
+            failed
+        );
+
+        let silent = sh("true", &[1, 2], DEFAULT_TIMEOUT).try_part1_with("", &params);
+        assert!(silent
+            .unwrap_err()
+            .to_string()
+            .contains("printed no answer"));
+
+        let start = Instant::now();
+        let slow = sh("sleep 5", &[1, 2], Duration::from_millis(100)).try_part1_with("", &params);
+        assert!(slow.unwrap_err().to_string().contains("timed out"));
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_registration() {
+        let dir = std::env::temp_dir().join(format!("aoc2024-solvers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("11-echo.solver"), "command = echo 42\nparts = 2\n").unwrap();
+        std::fs::write(
+            dir.join("11-broken.solver"),
+            "command = echo\ntimeout = soon\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("12-other.solver"), "command = echo 1\n").unwrap();
+
+        let variants = registered_in(&dir, 11);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = variants.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["broken", "echo"]);
+        let params = variants[1].solution.default_params();
+        assert_eq!(variants[1].parts, [2]);
+        assert_eq!(
+            variants[1].solution.try_part2_with("", &params),
+            Ok("42".to_string())
+        );
+        let broken = variants[0]
+            .solution
+            .try_part1_with("", &params)
+            .unwrap_err();
+        assert_eq!(
+            broken.to_string(),
+            "external solver failed: invalid timeout 'soon'"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Robustness checks: every day must answer or return a [`SolveError`] on any
+//! input, never panic.
+//!
+//! Inputs come from the [`generators`](crate::generators), optionally bent by
+//! [`mutate`] into something almost, but not quite, well-formed. The same
+//! checks back the property tests below, the `fuzz` subcommand and the
+//! cargo-fuzz targets under `fuzz/`.
+//!
+//! [`SolveError`]: crate::SolveError
+
+use crate::generators::{self, Rng};
+use crate::{get_solution, Params, Solution, SolveError};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Values that tend to sit on some parser's or solver's boundary
+const EDGE_NUMBERS: &[&str] = &[
+    "0",
+    "1",
+    "-1",
+    "7",
+    "8",
+    "255",
+    "65536",
+    "2147483648",
+    "-9223372036854775808",
+    "18446744073709551616",
+    "999999999999999999999999999999",
+];
+
+/// Characters that mean something in at least one day's format
+const EDGE_CHARS: &[char] = &[
+    '#', '.', '@', 'O', 'S', 'E', '^', '<', '>', 'v', 'A', 'X', ' ', ',', ':', '|', '-', '=', '9',
+    '0', 'x', 'é', '\t',
+];
+
+/// One small random edit to `input`: dropping, duplicating or swapping lines,
+/// truncating, or swapping in a character or number from the edge lists.
+pub fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut lines: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+    if lines.is_empty() {
+        lines.push(Vec::new());
+    }
+    let i = rng.below(lines.len());
+
+    match rng.below(8) {
+        0 => {
+            lines.remove(i);
+        }
+        1 => {
+            let line = lines[i].clone();
+            lines.insert(i, line);
+        }
+        2 => {
+            let j = rng.below(lines.len());
+            lines.swap(i, j);
+        }
+        3 => lines.truncate(i),
+        4 => {
+            let len = rng.below(lines[i].len() + 1);
+            lines[i].truncate(len);
+        }
+        5 if !lines[i].is_empty() => {
+            let at = rng.below(lines[i].len());
+            lines[i][at] = *rng.pick(EDGE_CHARS);
+        }
+        6 => {
+            let at = rng.below(lines[i].len() + 1);
+            lines[i].insert(at, *rng.pick(EDGE_CHARS));
+        }
+        _ => replace_number(&mut lines[i], rng),
+    }
+
+    lines
+        .into_iter()
+        .map(String::from_iter)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Swaps a random run of digits for one of the [`EDGE_NUMBERS`], or appends
+/// one if the line has no digits
+fn replace_number(line: &mut Vec<char>, rng: &mut Rng) {
+    let number: Vec<char> = rng.pick(EDGE_NUMBERS).chars().collect();
+    let starts: Vec<usize> = (0..line.len())
+        .filter(|&i| line[i].is_ascii_digit() && (i == 0 || !line[i - 1].is_ascii_digit()))
+        .collect();
+    if starts.is_empty() {
+        line.extend(number);
+        return;
+    }
+    let start = *rng.pick(&starts);
+    let end = (start..line.len())
+        .find(|&i| !line[i].is_ascii_digit())
+        .unwrap_or(line.len());
+    line.splice(start..end, number);
+}
+
+/// How one part fared on an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Answer(String),
+    Error(SolveError),
+    Panic(String),
+}
+
+/// Runs one part, catching a panic instead of unwinding through the caller
+pub fn run_part(solution: &dyn Solution, part: u8, input: &str, params: &Params) -> Outcome {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        crate::solve_part(solution, part, input, params)
+    }));
+    match result {
+        Ok(Ok(answer)) => Outcome::Answer(answer),
+        Ok(Err(e)) => Outcome::Error(e),
+        Err(payload) => Outcome::Panic(
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "(no message)".to_string()),
+        ),
+    }
+}
+
+/// Runs both parts, reporting a panic from either as `Err`. Solver errors are
+/// a fine outcome here; only panics fail the check.
+pub fn check(solution: &dyn Solution, input: &str, params: &Params) -> Result<(), String> {
+    for part in [1, 2] {
+        if let Outcome::Panic(message) = run_part(solution, part, input, params) {
+            return Err(format!("part {} panicked: {}", part, message));
+        }
+    }
+    Ok(())
+}
+
+/// A fuzz input, reproducible from its seed, on which a day panicked
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub day: u8,
+    pub seed: u64,
+    pub input: String,
+    pub message: String,
+}
+
+/// The generated input for `seed` after up to `max_mutations` random edits,
+/// along with the parameters it was generated for
+pub fn mutated_input(
+    solution: &dyn Solution,
+    day: u8,
+    size: usize,
+    seed: u64,
+    max_mutations: usize,
+) -> Option<(String, Params)> {
+    let input = generators::generate(day, size, seed)?;
+    let params = input
+        .params_for(solution)
+        .unwrap_or_else(|_| solution.default_params());
+
+    let mut rng = Rng::new(seed);
+    let mut text = input.text;
+    for _ in 0..rng.below(max_mutations + 1) {
+        text = mutate(&text, &mut rng);
+    }
+    Some((text, params))
+}
+
+/// Checks `iterations` mutated inputs for a day, stopping at the first panic
+pub fn run(day: u8, size: usize, seed: u64, iterations: u64) -> Result<(), Failure> {
+    let Some(solution) = get_solution(day) else {
+        return Ok(());
+    };
+    for seed in seed..seed + iterations {
+        let Some((input, params)) = mutated_input(solution.as_ref(), day, size, seed, 4) else {
+            return Ok(());
+        };
+        check(solution.as_ref(), &input, &params).map_err(|message| Failure {
+            day,
+            seed,
+            input,
+            message,
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Rng;
+    use proptest::prelude::*;
+
+    /// The day a proptest index picks among those in this build, if any
+    fn compiled_day(index: prop::sample::Index) -> Option<u8> {
+        let days: Vec<u8> = (1..=25)
+            .filter(|&day| get_solution(day).is_some())
+            .collect();
+        (!days.is_empty()).then(|| days[index.index(days.len())])
+    }
+
+    fn assert_no_panic(day: u8, input: &str, params: &Params) -> Result<(), TestCaseError> {
+        let solution = get_solution(day).unwrap();
+        let outcome = check(solution.as_ref(), input, params);
+        prop_assert!(
+            outcome.is_ok(),
+            "day {} on {:?}: {}",
+            day,
+            input,
+            outcome.unwrap_err()
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+
+        #[test]
+        fn generated_inputs_never_panic(index in any::<prop::sample::Index>(), size in 1..12usize, seed: u64) {
+            let Some(day) = compiled_day(index) else { return Ok(()) };
+            let solution = get_solution(day).unwrap();
+            let (input, params) = mutated_input(solution.as_ref(), day, size, seed, 0).unwrap();
+            assert_no_panic(day, &input, &params)?;
+        }
+
+        #[test]
+        fn mutated_inputs_never_panic(index in any::<prop::sample::Index>(), size in 1..12usize, seed: u64) {
+            let Some(day) = compiled_day(index) else { return Ok(()) };
+            let solution = get_solution(day).unwrap();
+            let (input, params) = mutated_input(solution.as_ref(), day, size, seed, 6).unwrap();
+            assert_no_panic(day, &input, &params)?;
+        }
+
+        #[test]
+        fn arbitrary_text_never_panics(index in any::<prop::sample::Index>(), input in "[ -~\n]{0,64}") {
+            let Some(day) = compiled_day(index) else { return Ok(()) };
+            let params = get_solution(day).unwrap().default_params();
+            assert_no_panic(day, &input, &params)?;
+        }
+    }
+
+    #[test]
+    fn test_mutate_is_deterministic() {
+        let input = "1 2 3\n4 5 6\n7 8 9";
+        let a = mutate(input, &mut Rng::new(7));
+        let b = mutate(input, &mut Rng::new(7));
+        assert_eq!(a, b);
+    }
+}
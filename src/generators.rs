@@ -0,0 +1,794 @@
+//! Seeded generators for random, format-valid puzzle inputs, for stress tests
+//! and for finding performance cliffs at scale.
+//!
+//! `size` is each day's natural scale knob: a line or item count for list
+//! inputs, the side length for grids, and the bit width for Day 24's adder.
+//! Generators make sure their inputs satisfy whatever the puzzle guarantees,
+//! e.g. Day 6's guard eventually leaves the map and Day 17's program has a
+//! quine.
+
+use crate::geometry::{Direction, Point};
+use crate::graph::Graph;
+use crate::{Input, Origin, Params};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// SplitMix64. Tiny and, unlike an external crate, guaranteed to produce the
+/// same stream forever, so a seed always reproduces the same input.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; panics if `n` is zero
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = (range.end() - range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % span) as i64
+    }
+
+    /// True with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= p
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A random input for `day` at the given scale, with any metadata it needs.
+/// `None` if there is no generator for the day.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<Input> {
+    let rng = &mut Rng::new(seed);
+    let mut meta = Params::new();
+    let text = match day {
+        1 => day01(rng, size),
+        2 => day02(rng, size),
+        3 => day03(rng, size),
+        4 => day04(rng, size),
+        5 => day05(rng, size),
+        6 => day06(rng, size),
+        7 => day07(rng, size),
+        8 => day08(rng, size),
+        9 => day09(rng, size),
+        10 => day10(rng, size),
+        11 => day11(rng, size),
+        12 => day12(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => day17(rng, size),
+        18 => {
+            let (text, largest, bytes) = day18(rng, size);
+            meta.set("size", largest);
+            meta.set("bytes", bytes);
+            text
+        }
+        19 => day19(rng, size),
+        20 => day20(rng, size),
+        21 => day21(rng, size),
+        22 => day22(rng, size),
+        23 => day23(rng, size),
+        24 => day24(rng, size),
+        25 => day25(rng, size),
+        _ => return None,
+    };
+    Some(Input {
+        text,
+        part2_text: None,
+        meta,
+        origin: Origin::Memory,
+    })
+}
+
+fn render(rows: &[Vec<char>]) -> String {
+    rows.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+fn join<T: ToString>(items: &[T], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// `size` pairs of location ids, with some right ids copied from the left list
+fn day01(rng: &mut Rng, size: usize) -> String {
+    let left: Vec<i64> = (0..size).map(|_| rng.range(10000..=99999)).collect();
+    left.iter()
+        .map(|&l| {
+            let r = if rng.chance(0.3) {
+                *rng.pick(&left)
+            } else {
+                rng.range(10000..=99999)
+            };
+            format!("{}   {}\n", l, r)
+        })
+        .collect()
+}
+
+/// `size` reports, mostly safe runs with the odd bad level
+fn day02(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let direction = if rng.chance(0.5) { 1 } else { -1 };
+            let mut level = rng.range(20..=80);
+            let mut report: Vec<i64> = (0..rng.range(5..=8))
+                .map(|_| {
+                    level += direction * rng.range(1..=3);
+                    level
+                })
+                .collect();
+            if rng.chance(0.4) {
+                let i = rng.below(report.len());
+                report[i] = rng.range(1..=99);
+            }
+            join(&report, " ") + "\n"
+        })
+        .collect()
+}
+
+/// `size` instructions buried in junk, including near-miss `mul`s
+fn day03(rng: &mut Rng, size: usize) -> String {
+    const JUNK: &[char] = &[
+        'x', 'm', 'u', 'l', '(', ')', '[', ']', ',', '%', '&', '!', '@', '^', '*', '+', '-', '?',
+        '<', '>', '\'', ' ', 'd', 'o', 'n', 't',
+    ];
+    let mut text = String::new();
+    for i in 0..size {
+        for _ in 0..rng.below(8) {
+            text.push(*rng.pick(JUNK));
+        }
+        let (a, b) = (rng.range(1..=999), rng.range(1..=999));
+        let instruction = match rng.below(10) {
+            0 => "do()".to_string(),
+            1 => "don't()".to_string(),
+            2 => format!("mul({},{}]", a, b),
+            3 => format!("mul ( {},{} )", a, b),
+            4 => format!("mul({}*{})", a, b),
+            _ => format!("mul({},{})", a, b),
+        };
+        text += &instruction;
+        if i % 60 == 59 {
+            text.push('\n');
+        }
+    }
+    text + "\n"
+}
+
+/// A `size` x `size` letter soup
+fn day04(rng: &mut Rng, size: usize) -> String {
+    let rows: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *rng.pick(&['X', 'M', 'A', 'S']))
+                .collect()
+        })
+        .collect();
+    render(&rows)
+}
+
+/// A total order over up to `size` pages given as pairwise rules, and `size`
+/// updates of odd length, about half of them already in order
+fn day05(rng: &mut Rng, size: usize) -> String {
+    let mut pages: Vec<i64> = (10..=99).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(size.clamp(3, 90));
+
+    let mut rules: Vec<String> = (0..pages.len())
+        .flat_map(|i| (i + 1..pages.len()).map(move |j| (i, j)))
+        .map(|(i, j)| format!("{}|{}", pages[i], pages[j]))
+        .collect();
+    rng.shuffle(&mut rules);
+
+    let longest = (pages.len().min(23) - 1) / 2;
+    let updates: Vec<String> = (0..size)
+        .map(|_| {
+            let len = 2 * (1 + rng.below(longest)) + 1;
+            let mut order: Vec<usize> = (0..pages.len()).collect();
+            rng.shuffle(&mut order);
+            order.truncate(len);
+            if rng.chance(0.5) {
+                order.sort();
+            }
+            let update: Vec<i64> = order.iter().map(|&i| pages[i]).collect();
+            join(&update, ",")
+        })
+        .collect();
+
+    rules.join("\n") + "\n\n" + &updates.join("\n") + "\n"
+}
+
+/// A `size` x `size` lab the guard walks out of
+fn day06(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    loop {
+        let mut rows: Vec<Vec<char>> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| if rng.chance(0.08) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        let start = Point::new(rng.below(size) as i32, rng.below(size) as i32);
+        rows[start.y as usize][start.x as usize] = '^';
+
+        if guard_escapes(&rows, start) {
+            return render(&rows);
+        }
+    }
+}
+
+fn guard_escapes(rows: &[Vec<char>], mut pos: Point) -> bool {
+    let mut dir = Direction::Up;
+    let mut seen = HashSet::new();
+    while seen.insert((pos, dir)) {
+        let next = pos.step(dir);
+        let Some((row, col)) = next.to_grid() else {
+            return true;
+        };
+        match rows.get(row).and_then(|r| r.get(col)) {
+            None => return true,
+            Some('#') => dir = dir.turn_right(),
+            Some(_) => pos = next,
+        }
+    }
+    false
+}
+
+/// `size` calibration equations, most of them solvable
+fn day07(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let operands: Vec<u64> = (0..rng.range(2..=6))
+                .map(|_| rng.range(1..=999) as u64)
+                .collect();
+            let mut target = operands[1..]
+                .iter()
+                .fold(operands[0], |acc, &n| match rng.below(3) {
+                    0 => acc + n,
+                    1 => acc * n,
+                    _ => format!("{}{}", acc, n).parse().unwrap(),
+                });
+            if rng.chance(0.3) {
+                target += rng.range(1..=9) as u64;
+            }
+            format!("{}: {}\n", target, join(&operands, " "))
+        })
+        .collect()
+}
+
+/// A `size` x `size` roof with a handful of antennas per frequency
+fn day08(rng: &mut Rng, size: usize) -> String {
+    let frequencies: Vec<char> = ('0'..='9').chain('a'..='z').chain('A'..='Z').collect();
+    let size = size.max(2);
+    let mut rows = vec![vec!['.'; size]; size];
+    for &frequency in &frequencies[..(size / 8).clamp(1, frequencies.len())] {
+        for _ in 0..rng.range(2..=4) {
+            rows[rng.below(size)][rng.below(size)] = frequency;
+        }
+    }
+    render(&rows)
+}
+
+/// A disk map of `size` files
+fn day09(rng: &mut Rng, size: usize) -> String {
+    let mut text: String = (0..size.max(1))
+        .map(|_| format!("{}{}", rng.range(1..=9), rng.range(0..=9)))
+        .collect();
+    text.pop();
+    text + "\n"
+}
+
+/// A `size` x `size` map whose heights mostly change by at most one per step
+fn day10(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut heights = vec![vec![0i64; size]; size];
+    for row in 0..size {
+        for col in 0..size {
+            let base = match (row, col) {
+                (0, 0) => rng.range(0..=9),
+                (0, _) => heights[0][col - 1],
+                (_, 0) => heights[row - 1][0],
+                _ if rng.chance(0.5) => heights[row][col - 1],
+                _ => heights[row - 1][col],
+            };
+            heights[row][col] = (base + rng.range(-1..=1)).clamp(0, 9);
+        }
+    }
+    let rows: Vec<Vec<char>> = heights
+        .iter()
+        .map(|row| row.iter().map(|h| (b'0' + *h as u8) as char).collect())
+        .collect();
+    render(&rows)
+}
+
+/// `size` engraved stones
+fn day11(rng: &mut Rng, size: usize) -> String {
+    let stones: Vec<i64> = (0..size.max(1)).map(|_| rng.range(0..=999_999)).collect();
+    join(&stones, " ") + "\n"
+}
+
+/// A `size` x `size` garden of blobby regions
+fn day12(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let plants: Vec<char> = ('A'..='J').collect();
+    let mut rows = vec![vec!['A'; size]; size];
+    for row in 0..size {
+        for col in 0..size {
+            rows[row][col] = match rng.below(8) {
+                0..=2 if col > 0 => rows[row][col - 1],
+                3..=5 if row > 0 => rows[row - 1][col],
+                _ => *rng.pick(&plants),
+            };
+        }
+    }
+    render(&rows)
+}
+
+/// `size` claw machines, most of them winnable within 100 presses
+fn day13(rng: &mut Rng, size: usize) -> String {
+    let machines: Vec<String> = (0..size)
+        .map(|_| {
+            let a = (rng.range(10..=99), rng.range(10..=99));
+            let b = (rng.range(10..=99), rng.range(10..=99));
+            let prize = if rng.chance(0.6) {
+                let (na, nb) = (rng.range(1..=100), rng.range(1..=100));
+                (na * a.0 + nb * b.0, na * a.1 + nb * b.1)
+            } else {
+                (rng.range(1000..=20000), rng.range(1000..=20000))
+            };
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}\n",
+                a.0, a.1, b.0, b.1, prize.0, prize.1
+            )
+        })
+        .collect();
+    machines.join("\n")
+}
+
+/// `size` robots in the default 101x103 space
+fn day14(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            format!(
+                "p={},{} v={},{}\n",
+                rng.range(0..=100),
+                rng.range(0..=102),
+                rng.range(-99..=99),
+                rng.range(-99..=99)
+            )
+        })
+        .collect()
+}
+
+/// A walled `size` x `size` warehouse and `size`² moves
+fn day15(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(3);
+    let mut rows: Vec<Vec<char>> = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    let border = row == 0 || col == 0 || row == size - 1 || col == size - 1;
+                    if border || rng.chance(0.05) {
+                        '#'
+                    } else if rng.chance(0.25) {
+                        'O'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    rows[rng.range(1..=size as i64 - 2) as usize][rng.range(1..=size as i64 - 2) as usize] = '@';
+
+    let moves: Vec<char> = (0..size * size)
+        .map(|_| *rng.pick(&['^', '>', 'v', '<']))
+        .collect();
+    let moves: Vec<String> = moves.chunks(70).map(|line| line.iter().collect()).collect();
+    render(&rows) + "\n" + &moves.join("\n") + "\n"
+}
+
+/// Carve a maze over the odd cells of a `size` x `size` wall grid (rounded up
+/// to odd), visiting each cell through a randomised depth-first search
+fn carve_maze(rng: &mut Rng, size: usize) -> Vec<Vec<char>> {
+    let size = size.max(5) | 1;
+    let mut rows = vec![vec!['#'; size]; size];
+    let mut stack = vec![(1, 1)];
+    rows[1][1] = '.';
+    while let Some(&(row, col)) = stack.last() {
+        let mut next: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .map(|&(dr, dc)| ((row as i64 + dr) as usize, (col as i64 + dc) as usize))
+            .filter(|&(r, c)| r > 0 && c > 0 && r < size - 1 && c < size - 1)
+            .filter(|&(r, c)| rows[r][c] == '#')
+            .collect();
+        if next.is_empty() {
+            stack.pop();
+            continue;
+        }
+        rng.shuffle(&mut next);
+        let (r, c) = next[0];
+        rows[(row + r) / 2][(col + c) / 2] = '.';
+        rows[r][c] = '.';
+        stack.push((r, c));
+    }
+    rows
+}
+
+/// A `size` x `size` maze with a few extra openings, so several best paths exist
+fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut rows = carve_maze(rng, size);
+    let n = rows.len();
+    for (row, cells) in rows.iter_mut().enumerate().take(n - 1).skip(1) {
+        for (col, cell) in cells.iter_mut().enumerate().take(n - 1).skip(1) {
+            // Walls between two cells sit at one odd and one even coordinate
+            if (row + col) % 2 == 1 && rng.chance(0.08) {
+                *cell = '.';
+            }
+        }
+    }
+    rows[n - 2][1] = 'S';
+    rows[1][n - 2] = 'E';
+    render(&rows)
+}
+
+/// The output of the puzzle's program shape `2,4,1,a,7,5,1,b,4,c,5,5,0,3,3,0`
+/// for register A
+fn day17_output(mut reg_a: u64, a: u64, b: u64) -> Vec<u8> {
+    let mut output = Vec::new();
+    loop {
+        let shift = (reg_a & 7) ^ a;
+        output.push((((reg_a & 7) ^ a ^ b ^ (reg_a >> shift)) & 7) as u8);
+        reg_a >>= 3;
+        if reg_a == 0 {
+            return output;
+        }
+    }
+}
+
+/// Some register A value that makes the program print itself
+fn day17_quine(program: &[u8], a: u64, b: u64, reg_a: u64, i: usize) -> Option<u64> {
+    (0..8).find_map(|digit| {
+        let candidate = reg_a * 8 + digit;
+        if candidate == 0 || day17_output(candidate, a, b) != program[i..] {
+            return None;
+        }
+        if i == 0 {
+            Some(candidate)
+        } else {
+            day17_quine(program, a, b, candidate, i - 1)
+        }
+    })
+}
+
+/// A program of the puzzle's shape that has a quine, with a `size`-digit
+/// (octal) register A
+fn day17(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let (a, b, c) = (rng.range(0..=7), rng.range(0..=7), rng.range(0..=7));
+        let program = [2, 4, 1, a, 7, 5, 1, b, 4, c, 5, 5, 0, 3, 3, 0].map(|n| n as u8);
+        if day17_quine(&program, a as u64, b as u64, 0, program.len() - 1).is_none() {
+            continue;
+        }
+        let digits = size.clamp(1, 20) as u32;
+        let reg_a = rng.range(8i64.pow(digits - 1)..=8i64.pow(digits).saturating_sub(1));
+        return format!(
+            "Register A: {}\nRegister B: 0\nRegister C: 0\n\nProgram: {}\n",
+            reg_a,
+            join(&program, ",")
+        );
+    }
+}
+
+/// Bytes falling on 60% of a `size` x `size` memory space. Returns the text,
+/// the largest coordinate and how many bytes part 1 should use.
+fn day18(rng: &mut Rng, size: usize) -> (String, usize, usize) {
+    let largest = size.max(3) - 1;
+    let mut cells: Vec<(usize, usize)> = (0..=largest)
+        .flat_map(|x| (0..=largest).map(move |y| (x, y)))
+        .filter(|&cell| cell != (0, 0) && cell != (largest, largest))
+        .collect();
+    rng.shuffle(&mut cells);
+    cells.truncate(cells.len() * 3 / 5);
+    let text = cells
+        .iter()
+        .map(|(x, y)| format!("{},{}\n", x, y))
+        .collect();
+    (text, largest, cells.len() / 3)
+}
+
+/// `size` designs, most of them built from the available towels
+fn day19(rng: &mut Rng, size: usize) -> String {
+    const COLORS: &[char] = &['w', 'u', 'b', 'r', 'g'];
+    fn random_stripes(rng: &mut Rng, len: i64) -> String {
+        (0..len).map(|_| *rng.pick(COLORS)).collect()
+    }
+
+    let mut towels = Vec::new();
+    let mut seen = HashSet::new();
+    while towels.len() < 8 + size / 2 {
+        let len = rng.range(1..=8);
+        let towel = random_stripes(rng, len);
+        if seen.insert(towel.clone()) {
+            towels.push(towel);
+        }
+    }
+
+    let designs: Vec<String> = (0..size)
+        .map(|_| {
+            let len = rng.range(20..=60) as usize;
+            if rng.chance(0.3) {
+                return random_stripes(rng, len as i64);
+            }
+            let mut design = String::new();
+            while design.len() < len {
+                design += rng.pick(&towels).as_str();
+            }
+            design
+        })
+        .collect();
+    towels.join(", ") + "\n\n" + &designs.join("\n") + "\n"
+}
+
+/// A single winding race track in a `size` x `size` grid, made by a
+/// self-avoiding walk over the odd cells
+fn day20(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(5) | 1;
+    let mut rows = vec![vec!['#'; size]; size];
+    let (mut row, mut col) = (1, 1);
+    rows[row][col] = 'S';
+    loop {
+        let next: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .map(|&(dr, dc)| ((row as i64 + dr) as usize, (col as i64 + dc) as usize))
+            .filter(|&(r, c)| r > 0 && c > 0 && r < size - 1 && c < size - 1)
+            .filter(|&(r, c)| rows[r][c] == '#')
+            .collect();
+        if next.is_empty() {
+            break;
+        }
+        let (r, c) = *rng.pick(&next);
+        rows[(row + r) / 2][(col + c) / 2] = '.';
+        rows[r][c] = '.';
+        (row, col) = (r, c);
+    }
+    rows[row][col] = 'E';
+    render(&rows)
+}
+
+/// `size` door codes
+fn day21(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{:03}A\n", rng.range(0..=999)))
+        .collect()
+}
+
+/// `size` buyers' initial secrets
+fn day22(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{}\n", rng.range(1..=16_777_215)))
+        .collect()
+}
+
+/// A LAN of `size` computers (at most 676 two-letter names) with random links
+/// and a planted party of up to 13
+fn day23(rng: &mut Rng, size: usize) -> String {
+    let mut names: Vec<String> = ('a'..='z')
+        .flat_map(|a| ('a'..='z').map(move |b| format!("{}{}", a, b)))
+        .collect();
+    rng.shuffle(&mut names);
+    names.truncate(size.clamp(4, names.len()));
+
+    let mut links = HashSet::new();
+    let party = names.len().min(13);
+    for i in 0..party {
+        for j in i + 1..party {
+            links.insert((i, j));
+        }
+    }
+    for _ in 0..names.len() * 4 {
+        let (i, j) = (rng.below(names.len()), rng.below(names.len()));
+        if i != j {
+            links.insert((i.min(j), i.max(j)));
+        }
+    }
+
+    let mut links: Vec<String> = links
+        .into_iter()
+        .map(|(i, j)| format!("{}-{}", names[i], names[j]))
+        .collect();
+    links.sort();
+    rng.shuffle(&mut links);
+    links.join("\n") + "\n"
+}
+
+/// A `size`-bit ripple-carry adder (at most 45 bits) with four pairs of gate
+/// outputs swapped, never in a way that creates a loop
+fn day24(rng: &mut Rng, size: usize) -> String {
+    let bits = size.clamp(2, 45);
+    let mut used = HashSet::new();
+    let mut wire = |rng: &mut Rng| loop {
+        let name: String = (0..3)
+            .map(|_| (b'a' + rng.below(23) as u8) as char)
+            .collect();
+        if used.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    let mut gates: Vec<(String, &str, String, String)> = Vec::new();
+    let mut carry = wire(rng);
+    gates.push(("x00".into(), "XOR", "y00".into(), "z00".into()));
+    gates.push(("x00".into(), "AND", "y00".into(), carry.clone()));
+    for bit in 1..bits {
+        let (x, y, z) = (
+            format!("x{:02}", bit),
+            format!("y{:02}", bit),
+            format!("z{:02}", bit),
+        );
+        let (sum, both, through) = (wire(rng), wire(rng), wire(rng));
+        let next = if bit == bits - 1 {
+            format!("z{:02}", bits)
+        } else {
+            wire(rng)
+        };
+        gates.push((x.clone(), "XOR", y.clone(), sum.clone()));
+        gates.push((x, "AND", y, both.clone()));
+        gates.push((sum.clone(), "XOR", carry.clone(), z));
+        gates.push((sum, "AND", carry, through.clone()));
+        gates.push((both, "OR", through, next.clone()));
+        carry = next;
+    }
+
+    let swaps = 4.min(gates.len() / 2);
+    loop {
+        let mut swapped = gates.clone();
+        let mut order: Vec<usize> = (0..gates.len()).collect();
+        rng.shuffle(&mut order);
+        for pair in order[..2 * swaps].chunks(2) {
+            let out = swapped[pair[0]].3.clone();
+            swapped[pair[0]].3 = std::mem::replace(&mut swapped[pair[1]].3, out);
+        }
+
+        let mut circuit = Graph::directed();
+        for (a, _, b, out) in &swapped {
+            circuit.add_edge(a.as_str(), out.as_str());
+            circuit.add_edge(b.as_str(), out.as_str());
+        }
+        if circuit.toposort().is_err() {
+            continue;
+        }
+
+        rng.shuffle(&mut swapped);
+        let inputs: String = ['x', 'y']
+            .iter()
+            .flat_map(|c| (0..bits).map(move |bit| (c, bit)))
+            .map(|(c, bit)| format!("{}{:02}: {}\n", c, bit, rng.below(2)))
+            .collect();
+        let gates: String = swapped
+            .iter()
+            .map(|(a, op, b, out)| format!("{} {} {} -> {}\n", a, op, b, out))
+            .collect();
+        return inputs + "\n" + &gates;
+    }
+}
+
+/// `size` lock and key schematics
+fn day25(rng: &mut Rng, size: usize) -> String {
+    let schematics: Vec<String> = (0..size)
+        .map(|_| {
+            let is_lock = rng.chance(0.5);
+            let heights: Vec<usize> = (0..5).map(|_| rng.below(6)).collect();
+            let rows: Vec<Vec<char>> = (0..7)
+                .map(|row| {
+                    heights
+                        .iter()
+                        .map(|&h| {
+                            let filled = if is_lock { row <= h } else { row >= 6 - h };
+                            if filled {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            render(&rows)
+        })
+        .collect();
+    schematics.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_solution;
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.range(-3..=3)));
+            assert!(rng.below(5) < 5);
+        }
+        assert_eq!(generate(9, 50, 3), generate(9, 50, 3));
+        assert_ne!(generate(9, 50, 3), generate(9, 50, 4));
+        assert!(generate(26, 10, 0).is_none());
+    }
+
+    #[test]
+    fn test_every_day_solves_generated_input() {
+        for day in (1..=25).filter(|&day| get_solution(day).is_some()) {
+            let input = generate(day, 12, day as u64).unwrap();
+            let solution = get_solution(day).unwrap();
+            let params = input.params_for(solution.as_ref()).unwrap();
+            solution.part1_with(&input.text, &params);
+            solution.part2_with(&input.text, &params);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "day17")]
+    fn test_day17_programs_have_quines() {
+        for seed in 0..5 {
+            let input = generate(17, 6, seed).unwrap();
+            let reg_a: i64 = get_solution(17)
+                .unwrap()
+                .part2(&input.text)
+                .parse()
+                .unwrap();
+            let program = crate::parse::ints(input.text.lines().last().unwrap());
+            let quine = input.text.replacen(
+                input.text.lines().next().unwrap(),
+                &format!("Register A: {}", reg_a),
+                1,
+            );
+            assert_eq!(get_solution(17).unwrap().part1(&quine), join(&program, ","));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "day13")]
+    fn test_variants_agree_on_generated_input() {
+        for seed in 0..3 {
+            let input = generate(13, 40, seed).unwrap();
+            for check in crate::crosscheck(13, &input, &Params::new()).unwrap() {
+                assert!(check.agrees(), "seed {}: {:?}", seed, check);
+            }
+        }
+    }
+}
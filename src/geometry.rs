@@ -0,0 +1,267 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A 2D point or vector. `x` grows to the right and `y` grows downward, so
+/// on a grid `x` is the column and `y` is the row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T = i32> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Copy + Ord + Sub<Output = T> + Add<Output = T>> Point<T> {
+    /// Taxicab distance; works for unsigned coordinates too
+    pub fn manhattan(self, other: Self) -> T {
+        let dx = self.x.max(other.x) - self.x.min(other.x);
+        let dy = self.y.max(other.y) - self.y.min(other.y);
+        dx + dy
+    }
+}
+
+impl<T: Copy + Add<Output = T> + From<i8>> Point<T> {
+    /// The neighboring point one step in `dir`
+    pub fn step(self, dir: Direction) -> Self {
+        self + dir.delta()
+    }
+
+    /// The four orthogonal neighbors, in [`Direction::ALL`] order
+    pub fn neighbors4(self) -> [Self; 4] {
+        Direction::ALL.map(|dir| self.step(dir))
+    }
+}
+
+impl Point<i32> {
+    /// Convert from a [`Grid`](crate::grid::Grid) `(row, col)` position
+    pub fn from_grid(pos: (usize, usize)) -> Self {
+        Self::new(pos.1 as i32, pos.0 as i32)
+    }
+
+    /// Convert to a [`Grid`](crate::grid::Grid) `(row, col)` position, if non-negative
+    pub fn to_grid(self) -> Option<(usize, usize)> {
+        (self.x >= 0 && self.y >= 0).then_some((self.y as usize, self.x as usize))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+/// Scaling a vector by a scalar
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// One of the four grid directions. Ordered clockwise starting from `Up`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    /// Unit vector for this direction (with `y` growing downward)
+    pub fn delta<T: From<i8>>(self) -> Point<T> {
+        let (x, y) = match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        };
+        Point::new(T::from(x), T::from(y))
+    }
+
+    /// Parse one of the arrow characters `^`, `>`, `v` and `<`
+    pub fn from_arrow(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+/// Axis-aligned bounding box with inclusive corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<T = i32> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Copy + Ord> Bounds<T> {
+    /// Smallest box containing all `points`, or `None` if there are none
+    pub fn of(points: impl IntoIterator<Item = Point<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |b, p| Self {
+                min: Point::new(b.min.x.min(p.x), b.min.y.min(p.y)),
+                max: Point::new(b.max.x.max(p.x), b.max.y.max(p.y)),
+            },
+        ))
+    }
+
+    pub fn contains(&self, p: Point<T>) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+}
+
+impl Bounds<i32> {
+    pub fn width(&self) -> i32 {
+        self.max.x - self.min.x + 1
+    }
+
+    pub fn height(&self) -> i32 {
+        self.max.y - self.min.y + 1
+    }
+
+    /// Every point in the box, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (min, max) = (self.min, self.max);
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_arithmetic() {
+        let a = Point::new(3, 4);
+        let b = Point::new(1, -2);
+        assert_eq!(a + b, Point::new(4, 2));
+        assert_eq!(a - b, Point::new(2, 6));
+        assert_eq!(-b, Point::new(-1, 2));
+        assert_eq!(b * 3, Point::new(3, -6));
+        assert_eq!(a.manhattan(b), 8);
+        assert_eq!(Point::<usize>::new(1, 5).manhattan(Point::new(4, 2)), 6);
+    }
+
+    #[test]
+    fn test_direction_turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.reverse().reverse(), dir);
+            assert_eq!(Direction::from_arrow(dir.arrow()), Some(dir));
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Point::new(0, 0).step(Direction::Up), Point::new(0, -1));
+        assert_eq!(Direction::from_arrow('x'), None);
+    }
+
+    #[test]
+    fn test_grid_conversion() {
+        let p = Point::from_grid((2, 5));
+        assert_eq!(p, Point::new(5, 2));
+        assert_eq!(p.to_grid(), Some((2, 5)));
+        assert_eq!(Point::new(-1, 0).to_grid(), None);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = Bounds::of([Point::new(2, 1), Point::new(-1, 3), Point::new(0, 0)]).unwrap();
+        assert_eq!(bounds.min, Point::new(-1, 0));
+        assert_eq!(bounds.max, Point::new(2, 3));
+        assert_eq!((bounds.width(), bounds.height()), (4, 4));
+        assert!(bounds.contains(Point::new(0, 2)));
+        assert!(!bounds.contains(Point::new(3, 2)));
+        assert_eq!(bounds.points().count(), 16);
+        assert_eq!(Bounds::<i32>::of([]), None);
+    }
+}
@@ -0,0 +1,519 @@
+//! Graphs over interned node names. Each distinct key (usually a string) is
+//! assigned a dense [`NodeId`] on first use, so the algorithms work on plain
+//! indices and bitsets instead of hashing and cloning names.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write};
+use std::hash::Hash;
+
+pub type NodeId = usize;
+
+/// Assigns dense ids to keys in order of first appearance
+#[derive(Debug, Clone)]
+pub struct Interner<K = String> {
+    ids: HashMap<K, NodeId>,
+    keys: Vec<K>,
+}
+
+impl<K> Default for Interner<K> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> Interner<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id for `key`, allocating a new one if it hasn't been seen before
+    pub fn intern(&mut self, key: K) -> NodeId {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.keys.len();
+        self.ids.insert(key.clone(), id);
+        self.keys.push(key);
+        id
+    }
+
+    pub fn get(&self, key: &K) -> Option<NodeId> {
+        self.ids.get(key).copied()
+    }
+
+    pub fn key(&self, id: NodeId) -> &K {
+        &self.keys[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Set of node ids stored as one bit per id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, id: NodeId) {
+        let (word, bit) = (id / 64, id % 64);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << bit;
+    }
+
+    pub fn remove(&mut self, id: NodeId) {
+        if let Some(word) = self.words.get_mut(id / 64) {
+            *word &= !(1 << (id % 64));
+        }
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.words
+            .get(id / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| a & b)
+            .collect();
+        BitSet { words }
+    }
+
+    /// Members in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+impl FromIterator<NodeId> for BitSet {
+    fn from_iter<I: IntoIterator<Item = NodeId>>(iter: I) -> Self {
+        let mut set = BitSet::new();
+        for id in iter {
+            set.insert(id);
+        }
+        set
+    }
+}
+
+/// A directed or undirected graph with adjacency lists plus an adjacency
+/// bitset per node for constant-time edge checks. Parallel edges are ignored.
+#[derive(Debug, Clone)]
+pub struct Graph<K = String> {
+    nodes: Interner<K>,
+    directed: bool,
+    adjacency: Vec<Vec<NodeId>>,
+    adjacent: Vec<BitSet>,
+}
+
+impl<K: Clone + Eq + Hash> Graph<K> {
+    pub fn directed() -> Self {
+        Self::with_direction(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::with_direction(false)
+    }
+
+    fn with_direction(directed: bool) -> Self {
+        Self {
+            nodes: Interner::new(),
+            directed,
+            adjacency: Vec::new(),
+            adjacent: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn add_node(&mut self, key: K) -> NodeId {
+        let id = self.nodes.intern(key);
+        if id == self.adjacency.len() {
+            self.adjacency.push(Vec::new());
+            self.adjacent.push(BitSet::new());
+        }
+        id
+    }
+
+    /// Add an edge from `a` to `b` (in both directions if undirected),
+    /// creating either node if needed
+    pub fn add_edge(&mut self, a: K, b: K) -> (NodeId, NodeId) {
+        let a = self.add_node(a);
+        let b = self.add_node(b);
+        self.link(a, b);
+        if !self.directed {
+            self.link(b, a);
+        }
+        (a, b)
+    }
+
+    fn link(&mut self, from: NodeId, to: NodeId) {
+        if !self.adjacent[from].contains(to) {
+            self.adjacent[from].insert(to);
+            self.adjacency[from].push(to);
+        }
+    }
+
+    pub fn id(&self, key: &K) -> Option<NodeId> {
+        self.nodes.get(key)
+    }
+
+    pub fn key(&self, id: NodeId) -> &K {
+        self.nodes.key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacency.is_empty()
+    }
+
+    pub fn ids(&self) -> std::ops::Range<NodeId> {
+        0..self.len()
+    }
+
+    /// Outgoing neighbors, in the order the edges were added
+    pub fn neighbors(&self, id: NodeId) -> &[NodeId] {
+        &self.adjacency[id]
+    }
+
+    pub fn neighbor_set(&self, id: NodeId) -> &BitSet {
+        &self.adjacent[id]
+    }
+
+    pub fn has_edge(&self, from: NodeId, to: NodeId) -> bool {
+        self.adjacent[from].contains(to)
+    }
+
+    /// Edge check by key; false if either node is missing
+    pub fn connects(&self, from: &K, to: &K) -> bool {
+        match (self.id(from), self.id(to)) {
+            (Some(a), Some(b)) => self.has_edge(a, b),
+            _ => false,
+        }
+    }
+
+    /// The subgraph made of `keys` (those that exist) and the edges between them.
+    /// Nodes keep the relative order of `keys`.
+    pub fn induced(&self, keys: &[K]) -> Graph<K> {
+        let mut sub = Self::with_direction(self.directed);
+        let ids: Vec<NodeId> = keys.iter().filter_map(|k| self.id(k)).collect();
+        for &id in &ids {
+            sub.add_node(self.key(id).clone());
+        }
+        for &from in &ids {
+            for &to in &self.adjacency[from] {
+                if sub.id(self.key(to)).is_some() {
+                    sub.add_edge(self.key(from).clone(), self.key(to).clone());
+                }
+            }
+        }
+        sub
+    }
+
+    /// Order the nodes so that every edge points forward (Kahn's algorithm,
+    /// starting from the sources in id order). If the graph has a cycle, returns the nodes of one
+    /// cycle in edge order instead.
+    pub fn toposort(&self) -> Result<Vec<NodeId>, Vec<NodeId>> {
+        let mut in_degree = vec![0; self.len()];
+        for targets in &self.adjacency {
+            for &to in targets {
+                in_degree[to] += 1;
+            }
+        }
+
+        let mut queue: VecDeque<NodeId> = self.ids().filter(|&id| in_degree[id] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+            for &to in &self.adjacency[id] {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+
+        // Every unsorted node still has an unsorted predecessor, so walking
+        // backwards along those must eventually revisit a node
+        let remaining: BitSet = self.ids().filter(|&id| in_degree[id] > 0).collect();
+        let mut predecessor = vec![None; self.len()];
+        for from in remaining.iter() {
+            for &to in &self.adjacency[from] {
+                if remaining.contains(to) {
+                    predecessor[to] = Some(from);
+                }
+            }
+        }
+
+        let mut walk = vec![remaining.iter().next().unwrap()];
+        let mut seen = BitSet::new();
+        while !seen.contains(*walk.last().unwrap()) {
+            let node = *walk.last().unwrap();
+            seen.insert(node);
+            walk.push(predecessor[node].unwrap());
+        }
+        let repeated = walk.pop().unwrap();
+        let start = walk.iter().position(|&id| id == repeated).unwrap();
+        let mut cycle = walk.split_off(start);
+        cycle.reverse();
+        Err(cycle)
+    }
+
+    /// Connected components, ignoring edge direction. Each component lists its
+    /// ids in ascending order; components are ordered by their smallest id.
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        let mut parent: Vec<NodeId> = self.ids().collect();
+        fn root(parent: &mut [NodeId], mut id: NodeId) -> NodeId {
+            while parent[id] != id {
+                parent[id] = parent[parent[id]];
+                id = parent[id];
+            }
+            id
+        }
+
+        for from in self.ids() {
+            for &to in &self.adjacency[from] {
+                let (a, b) = (root(&mut parent, from), root(&mut parent, to));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
+        let mut index = HashMap::new();
+        for id in self.ids() {
+            let r = root(&mut parent, id);
+            let i = *index.entry(r).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[i].push(id);
+        }
+        components
+    }
+
+    /// Every triangle `[a, b, c]` with `a < b < c`, treating the graph as undirected
+    pub fn triangles(&self) -> Vec<[NodeId; 3]> {
+        let mut triangles = Vec::new();
+        for a in self.ids() {
+            for b in self.adjacent[a].iter().filter(|&b| b > a) {
+                let common = self.adjacent[a].intersection(&self.adjacent[b]);
+                triangles.extend(common.iter().filter(|&c| c > b).map(|c| [a, b, c]));
+            }
+        }
+        triangles
+    }
+
+    /// All maximal cliques of an undirected graph (Bron–Kerbosch with pivoting).
+    /// Each clique lists its ids in ascending order.
+    pub fn maximal_cliques(&self) -> Vec<Vec<NodeId>> {
+        let mut cliques = Vec::new();
+        let candidates: BitSet = self.ids().collect();
+        self.bron_kerbosch(&mut Vec::new(), candidates, BitSet::new(), &mut cliques);
+        cliques
+    }
+
+    fn bron_kerbosch(
+        &self,
+        clique: &mut Vec<NodeId>,
+        mut candidates: BitSet,
+        mut excluded: BitSet,
+        cliques: &mut Vec<Vec<NodeId>>,
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() {
+                let mut found = clique.clone();
+                found.sort_unstable();
+                cliques.push(found);
+            }
+            return;
+        }
+
+        // Only branch on candidates that aren't neighbors of the best pivot
+        let pivot = candidates
+            .iter()
+            .chain(excluded.iter())
+            .max_by_key(|&p| candidates.intersection(&self.adjacent[p]).len())
+            .unwrap();
+        let branches: Vec<NodeId> = candidates
+            .iter()
+            .filter(|&v| !self.adjacent[pivot].contains(v))
+            .collect();
+
+        for v in branches {
+            clique.push(v);
+            self.bron_kerbosch(
+                clique,
+                candidates.intersection(&self.adjacent[v]),
+                excluded.intersection(&self.adjacent[v]),
+                cliques,
+            );
+            clique.pop();
+            candidates.remove(v);
+            excluded.insert(v);
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash + fmt::Display> Graph<K> {
+    /// Render in Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = format!("{} {{\n", kind);
+        for id in self.ids() {
+            writeln!(dot, "    \"{}\";", self.key(id)).unwrap();
+        }
+        for from in self.ids() {
+            for &to in &self.adjacency[from] {
+                // Undirected edges are stored both ways; print each once
+                if self.directed || from < to {
+                    writeln!(
+                        dot,
+                        "    \"{}\" {} \"{}\";",
+                        self.key(from),
+                        arrow,
+                        self.key(to)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(edges: &[(&'static str, &'static str)]) -> Graph<&'static str> {
+        let mut graph = Graph::undirected();
+        for &(a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut names = Interner::new();
+        assert_eq!(names.intern("a".to_string()), 0);
+        assert_eq!(names.intern("b".to_string()), 1);
+        assert_eq!(names.intern("a".to_string()), 0);
+        assert_eq!(names.key(1), "b");
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut set: BitSet = [3, 70, 5].into_iter().collect();
+        assert!(set.contains(70));
+        assert!(!set.contains(4));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 5, 70]);
+        set.remove(5);
+        assert_eq!(set.len(), 2);
+        let other: BitSet = [70, 1].into_iter().collect();
+        assert_eq!(
+            set.intersection(&other).iter().collect::<Vec<_>>(),
+            vec![70]
+        );
+    }
+
+    #[test]
+    fn test_toposort() {
+        let mut graph = Graph::directed();
+        graph.add_edge("shirt", "tie");
+        graph.add_edge("tie", "jacket");
+        graph.add_edge("trousers", "shoes");
+        graph.add_edge("shirt", "jacket");
+        let order: Vec<_> = graph
+            .toposort()
+            .unwrap()
+            .into_iter()
+            .map(|id| *graph.key(id))
+            .collect();
+        assert_eq!(order, vec!["shirt", "trousers", "tie", "shoes", "jacket"]);
+
+        graph.add_edge("jacket", "shirt");
+        let cycle: Vec<_> = graph
+            .toposort()
+            .unwrap_err()
+            .into_iter()
+            .map(|id| *graph.key(id))
+            .collect();
+        assert_eq!(cycle.len(), 3);
+        for (i, &from) in cycle.iter().enumerate() {
+            assert!(graph.connects(&from, &cycle[(i + 1) % cycle.len()]));
+        }
+    }
+
+    #[test]
+    fn test_components_and_cliques() {
+        let graph = undirected(&[("a", "b"), ("b", "c"), ("a", "c"), ("c", "d"), ("x", "y")]);
+        assert_eq!(graph.components(), vec![vec![0, 1, 2, 3], vec![4, 5]]);
+        assert_eq!(graph.triangles(), vec![[0, 1, 2]]);
+
+        let mut cliques = graph.maximal_cliques();
+        cliques.sort();
+        assert_eq!(cliques, vec![vec![0, 1, 2], vec![2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_induced_and_dot() {
+        let graph = undirected(&[("a", "b"), ("b", "c")]);
+        let sub = graph.induced(&["c", "b"]);
+        assert_eq!(sub.len(), 2);
+        assert!(sub.connects(&"b", &"c"));
+        assert_eq!(
+            sub.to_dot(),
+            "graph {\n    \"c\";\n    \"b\";\n    \"c\" -- \"b\";\n}\n"
+        );
+
+        let mut directed = Graph::directed();
+        directed.add_edge("x", "y");
+        assert!(directed.to_dot().contains("\"x\" -> \"y\";"));
+    }
+}
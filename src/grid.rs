@@ -0,0 +1,349 @@
+use crate::geometry::Point;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Offsets to the four orthogonal neighbors, as (row, col) deltas: up, right, down, left
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Offsets to all eight surrounding cells, clockwise starting from up
+pub const ALL_DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+/// A rectangular 2D grid stored as a flat, row-major `Vec`.
+/// Positions are `(row, col)` pairs with `(0, 0)` in the top-left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from row-major cells. Panics if `cells` doesn't hold
+    /// exactly `width * height` values.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "a {}x{} grid needs {} cells",
+            width,
+            height,
+            width * height
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse one cell per character, one row per line. Panics on ragged rows.
+    pub fn parse_with(input: &str, mut f: impl FnMut(char) -> T) -> Self {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for line in input.lines() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+            let row_width = cells.len() - before;
+            match width {
+                None => width = Some(row_width),
+                Some(w) => assert_eq!(
+                    w, row_width,
+                    "row {} has width {}, expected {}",
+                    height, row_width, w
+                ),
+            }
+            height += 1;
+        }
+
+        Self::from_vec(width.unwrap_or(0), height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, row: isize, col: isize) -> bool {
+        row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width {
+            Some(&self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            Some(&mut self.cells[row * self.width + col])
+        } else {
+            None
+        }
+    }
+
+    /// Checked lookup for signed coordinates, e.g. after stepping off an edge
+    pub fn get_signed(&self, row: isize, col: isize) -> Option<&T> {
+        if self.contains(row, col) {
+            self.get(row as usize, col as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Checked lookup by [`Point`], where `x` is the column and `y` the row
+    pub fn get_point(&self, p: Point) -> Option<&T> {
+        self.get_signed(p.y as isize, p.x as isize)
+    }
+
+    pub fn contains_point(&self, p: Point) -> bool {
+        self.contains(p.y as isize, p.x as isize)
+    }
+
+    /// Move `(row, col)` by `(dr, dc)`, returning the new position if it is inside the grid
+    pub fn offset(
+        &self,
+        (row, col): (usize, usize),
+        (dr, dc): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let r = row as isize + dr;
+        let c = col as isize + dc;
+        self.contains(r, c).then_some((r as usize, c as usize))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks() panics on a zero chunk size, which an empty grid would have
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Iterate over every cell along with its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// In-bounds orthogonal neighbors of a position
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ORTHOGONAL.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// In-bounds orthogonal and diagonal neighbors of a position
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ALL_DIRECTIONS
+            .iter()
+            .filter_map(move |&d| self.offset(pos, d))
+    }
+
+    /// Position of the first cell (in row-major order) matching `pred`
+    pub fn position(&self, pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .position(pred)
+            .map(|i| (i / self.width, i % self.width))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell equal to `value`
+    pub fn find(&self, value: &T) -> Option<(usize, usize)> {
+        self.position(|cell| cell == value)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self::from_vec(width, height, vec![fill; width * height])
+    }
+
+    /// Swap rows and columns
+    pub fn transpose(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|col| (0..self.height).map(move |row| (row, col)))
+            .map(|(row, col)| self[(row, col)].clone())
+            .collect();
+        Self::from_vec(self.height, self.width, cells)
+    }
+
+    /// Rotate a quarter turn clockwise
+    pub fn rotate_cw(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|col| (0..self.height).rev().map(move |row| (row, col)))
+            .map(|(row, col)| self[(row, col)].clone())
+            .collect();
+        Self::from_vec(self.height, self.width, cells)
+    }
+
+    /// Rotate a quarter turn counter-clockwise
+    pub fn rotate_ccw(&self) -> Self {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|col| (0..self.height).map(move |row| (row, col)))
+            .map(|(row, col)| self[(row, col)].clone())
+            .collect();
+        Self::from_vec(self.height, self.width, cells)
+    }
+}
+
+impl Grid<char> {
+    pub fn parse(input: &str) -> Self {
+        Self::parse_with(input, |c| c)
+    }
+}
+
+/// Unchecked indexing; panics when the position is out of bounds
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.height && col < self.width,
+            "position ({}, {}) is outside a {}x{} grid",
+            row,
+            col,
+            self.width,
+            self.height
+        );
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.height && col < self.width,
+            "position ({}, {}) is outside a {}x{} grid",
+            row,
+            col,
+            self.width,
+            self.height
+        );
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+/// Unchecked indexing by [`Point`]; panics when the point is out of bounds
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        let pos = p
+            .to_grid()
+            .unwrap_or_else(|| panic!("point {} is outside the grid", p));
+        &self[pos]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let pos = p
+            .to_grid()
+            .unwrap_or_else(|| panic!("point {} is outside the grid", p));
+        &mut self[pos]
+    }
+}
+
+/// Renders one line per row with no separators, so a `Grid<char>` prints as it was parsed
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows().take(self.height) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "abc\ndef";
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid = Grid::parse(SAMPLE);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], 'f');
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get_signed(-1, 0), None);
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.find(&'e'), Some((1, 1)));
+        assert_eq!(grid[Point::new(2, 1)], 'f');
+        assert_eq!(grid.get_point(Point::new(3, 0)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1 has width 2")]
+    fn test_parse_ragged() {
+        Grid::parse("abc\nde");
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::parse(SAMPLE);
+        let corner: Vec<_> = grid.neighbors4((0, 0)).collect();
+        assert_eq!(corner, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = Grid::parse(SAMPLE);
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad\n");
+        assert_eq!(
+            grid.map(|c| c.to_ascii_uppercase()).to_string(),
+            "ABC\nDEF\n"
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid = Grid::parse("");
+        assert!(grid.is_empty());
+        assert_eq!(grid.to_string(), "");
+        assert_eq!(grid.positions().count(), 0);
+    }
+}
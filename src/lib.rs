@@ -1,93 +1,1108 @@
-use std::time::Instant;
+use serde::Serialize;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
+pub mod alloc;
+#[cfg(feature = "day01")]
 pub mod day01;
+#[cfg(feature = "day02")]
 pub mod day02;
+#[cfg(feature = "day03")]
 pub mod day03;
+#[cfg(feature = "day04")]
 pub mod day04;
+#[cfg(feature = "day05")]
 pub mod day05;
+#[cfg(feature = "day06")]
 pub mod day06;
+#[cfg(feature = "day07")]
 pub mod day07;
+#[cfg(feature = "day08")]
 pub mod day08;
+#[cfg(feature = "day09")]
 pub mod day09;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day18")]
 pub mod day18;
+#[cfg(feature = "day19")]
 pub mod day19;
+#[cfg(feature = "day20")]
 pub mod day20;
+#[cfg(feature = "day21")]
 pub mod day21;
+#[cfg(feature = "day22")]
 pub mod day22;
+#[cfg(feature = "day23")]
 pub mod day23;
+#[cfg(feature = "day24")]
 pub mod day24;
+#[cfg(feature = "day25")]
 pub mod day25;
+pub mod debugger;
+pub mod embed;
+pub mod explain;
+pub mod external;
+pub mod fuzz;
+pub mod generators;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod math;
+pub mod params;
+pub mod parse;
+pub mod pathfinding;
+pub mod plugin;
+pub mod reduce;
+pub mod repl;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod snapshot;
+pub mod store;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+pub use params::{ParamSpec, Params};
+pub use parse::ParseError;
 
 pub trait Solution {
-    fn part1(&self, input: &str) -> String;
-    fn part2(&self, input: &str) -> String;
+    /// Solve part 1 with explicit parameter values. Malformed input or input
+    /// the puzzle rules out is reported as an error rather than a panic.
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError>;
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError>;
+
+    /// Puzzle constants this day can be tuned with, along with their defaults
+    fn params(&self) -> Vec<ParamSpec> {
+        Vec::new()
+    }
+
+    fn default_params(&self) -> Params {
+        Params::from_specs(&self.params())
+    }
+
+    /// Only parses the input, the way both parts start, so parsing can be
+    /// timed apart from solving. `None` for days that parse as they solve.
+    fn try_parse(&self, _input: &str, _params: &Params) -> Option<Result<(), SolveError>> {
+        None
+    }
+
+    /// Like [`Solution::try_part1_with`], panicking on bad input
+    fn part1_with(&self, input: &str, params: &Params) -> String {
+        self.try_part1_with(input, params)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn part2_with(&self, input: &str, params: &Params) -> String {
+        self.try_part2_with(input, params)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn part1(&self, input: &str) -> String {
+        self.part1_with(input, &self.default_params())
+    }
+
+    fn part2(&self, input: &str) -> String {
+        self.part2_with(input, &self.default_params())
+    }
 }
 
-pub fn read_input(day: u8, is_sample: bool) -> String {
+/// Why a day couldn't answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The input doesn't follow the puzzle's format
+    Parse(ParseError),
+    /// The input parses, but breaks something the puzzle guarantees, e.g. the
+    /// maze has no exit or the guard never leaves
+    Unsolvable(String),
+    /// An [`external`] solver or [`plugin`] failed, timed out or couldn't be
+    /// started
+    External(String),
+    /// A parameter is missing or its value doesn't parse, e.g. `--param
+    /// part1_blinks=abc`
+    Param(String),
+}
+
+impl SolveError {
+    pub fn unsolvable(message: impl Into<String>) -> Self {
+        SolveError::Unsolvable(message.into())
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> Self {
+        SolveError::Parse(e)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => write!(f, "invalid input at {}", e),
+            SolveError::Unsolvable(message) => write!(f, "unsolvable input: {}", message),
+            SolveError::External(message) => write!(f, "external solver failed: {}", message),
+            SolveError::Param(message) => write!(f, "invalid parameter: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+static LOGGING: AtomicBool = AtomicBool::new(true);
+static RENDERING: AtomicBool = AtomicBool::new(false);
+
+/// Whether solutions print their progress and diagnostics, see [`log!`]
+pub fn logging() -> bool {
+    LOGGING.load(Ordering::Relaxed)
+}
+
+pub fn set_logging(on: bool) {
+    LOGGING.store(on, Ordering::Relaxed);
+}
+
+/// Whether solutions draw their grids and maps as they go
+pub fn rendering() -> bool {
+    RENDERING.load(Ordering::Relaxed)
+}
+
+pub fn set_rendering(on: bool) {
+    RENDERING.store(on, Ordering::Relaxed);
+}
+
+/// What a solution shows besides its answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Output {
+    /// A line of progress or diagnostics, from [`log!`]
+    Log(String),
+    /// A drawing of the solution's state, from [`render`]
+    Frame(String),
+}
+
+type OutputSink = Box<dyn Fn(Output) + Send>;
+
+static OUTPUT_SINK: Mutex<Option<OutputSink>> = Mutex::new(None);
+
+/// Send solution output to `sink` instead of stdout, or back to stdout with `None`
+pub fn set_output_sink(sink: Option<OutputSink>) {
+    *OUTPUT_SINK.lock().unwrap_or_else(|e| e.into_inner()) = sink;
+}
+
+pub fn emit(output: Output) {
+    match &*OUTPUT_SINK.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(sink) => sink(output),
+        None => match output {
+            Output::Log(line) => println!("{}", line),
+            Output::Frame(frame) => println!("\n{}", frame),
+        },
+    }
+}
+
+/// Draws a frame while [`rendering`] is on; `frame` isn't called otherwise
+pub fn render(frame: impl FnOnce() -> String) {
+    if rendering() {
+        emit(Output::Frame(frame()));
+    }
+}
+
+/// `println!` for a solution's progress and diagnostics, silent while
+/// [`logging`] is off
+#[macro_export]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if $crate::logging() {
+            $crate::emit($crate::Output::Log(format!($($arg)*)));
+        }
+    };
+}
+
+/// Puzzle input text together with the metadata describing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub text: String,
+    /// Separate part 2 text, for puzzles whose description gives a second example
+    pub part2_text: Option<String>,
+    /// Parameter values this input was written for, e.g. the sample's smaller grid
+    pub meta: Params,
+    pub origin: Origin,
+}
+
+/// Where an input's text was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    /// The file on disk
+    #[default]
+    File,
+    /// The encrypted copy on disk, see [`store`]
+    Decrypted,
+    /// The copy compiled into the binary, see [`embed`]
+    Embedded,
+    /// Not read from anywhere: generated, pasted or passed in
+    Memory,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::File => write!(f, "file"),
+            Origin::Decrypted => write!(f, "decrypted"),
+            Origin::Embedded => write!(f, "embedded"),
+            Origin::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+impl Input {
+    pub fn text_for(&self, part: u8) -> &str {
+        match (part, &self.part2_text) {
+            (2, Some(text)) => text,
+            _ => &self.text,
+        }
+    }
+
+    /// The day's default parameters with this input's metadata applied
+    pub fn params_for(&self, solution: &dyn Solution) -> Result<Params, String> {
+        resolve_params(solution, &self.meta)
+    }
+}
+
+/// Bare input text, with no part 2 text or metadata
+impl From<String> for Input {
+    fn from(text: String) -> Self {
+        Input {
+            text,
+            part2_text: None,
+            meta: Params::new(),
+            origin: Origin::Memory,
+        }
+    }
+}
+
+fn input_path(day: u8, is_sample: bool, extension: &str) -> String {
     let input_type = if is_sample { "sample" } else { "real" };
-    std::fs::read_to_string(format!("inputs/{}/{:02}.txt", input_type, day))
-        .expect("Should have been able to read the file")
+    format!("inputs/{}/{:02}.{}", input_type, day, extension)
+}
+
+fn try_read_input(day: u8, is_sample: bool) -> Result<(String, Origin), String> {
+    let path = input_path(day, is_sample, "txt");
+    store::read(std::path::Path::new(&path)).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn try_read_input_meta(day: u8, is_sample: bool) -> Result<Params, String> {
+    let path = input_path(day, is_sample, "meta");
+    match store::read(std::path::Path::new(&path)) {
+        Ok((text, _)) => {
+            Params::parse_lines(&text).map_err(|e| format!("Invalid metadata in {}: {}", path, e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Params::new()),
+        Err(e) => Err(format!("{}: {}", path, e)),
+    }
+}
+
+fn try_load_input(day: u8, is_sample: bool) -> Result<Input, String> {
+    let input_type = if is_sample { "sample" } else { "real" };
+    let part2_path = format!("inputs/{}/{:02}_2.txt", input_type, day);
+    let (text, origin) = try_read_input(day, is_sample)?;
+    Ok(Input {
+        text,
+        part2_text: store::read(std::path::Path::new(&part2_path))
+            .ok()
+            .map(|(text, _)| text),
+        meta: try_read_input_meta(day, is_sample)?,
+        origin,
+    })
+}
+
+/// Read a day's input, see [`read_input_with_origin`]
+pub fn read_input(day: u8, is_sample: bool) -> String {
+    read_input_with_origin(day, is_sample).0
+}
+
+/// Read a day's input and where it came from: the file, its encrypted copy
+/// in the [`store`], or the copy [`embed`]ded in the binary, in that order
+pub fn read_input_with_origin(day: u8, is_sample: bool) -> (String, Origin) {
+    try_read_input(day, is_sample)
+        .unwrap_or_else(|e| panic!("Should have been able to read the file: {}", e))
+}
+
+/// Read the optional `XX.meta` file next to an input. Missing files mean no metadata.
+pub fn read_input_meta(day: u8, is_sample: bool) -> Params {
+    try_read_input_meta(day, is_sample).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Load an input with its metadata. A part 2 specific example, if any, lives
+/// next to it as `XX_2.txt`.
+pub fn load_input(day: u8, is_sample: bool) -> Input {
+    try_load_input(day, is_sample).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// A day's real input for tests, or `None` when it isn't available here:
+/// not in `inputs/real`, not embedded, and not decryptable from the [`store`]
+pub fn real_input(day: u8) -> Option<Input> {
+    let path = input_path(day, false, "txt");
+    match store::read(std::path::Path::new(&path)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound || !store::can_decrypt() => {
+            eprintln!("skipping: no real input for day {}", day);
+            None
+        }
+        _ => Some(load_input(day, false)),
+    }
+}
+
+/// Path of a named sample, e.g. a reduced reproduction: `inputs/sample/XX-name.<extension>`
+fn named_sample_path(day: u8, name: &str, extension: &str) -> String {
+    format!("inputs/sample/{:02}-{}.{}", day, name, extension)
+}
+
+/// Load a named sample with its optional `XX-name.meta`
+pub fn load_named_sample(day: u8, name: &str) -> Result<Input, String> {
+    load_input_file(std::path::Path::new(&named_sample_path(day, name, "txt")))
+}
+
+/// Load any input file, with the `.meta` file next to it if there is one
+pub fn load_input_file(path: &std::path::Path) -> Result<Input, String> {
+    let (text, origin) = store::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let meta_path = path.with_extension("meta");
+    let meta = match store::read(&meta_path) {
+        Ok((meta, _)) => {
+            Params::parse_lines(&meta).map_err(|e| format!("{}: {}", meta_path.display(), e))?
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Params::new(),
+        Err(e) => return Err(format!("{}: {}", meta_path.display(), e)),
+    };
+    Ok(Input {
+        text,
+        part2_text: None,
+        meta,
+        origin,
+    })
+}
+
+/// Save `text` as a named sample, with `meta` in a `.meta` file next to it if
+/// there is any. Returns the path of the input file.
+pub fn save_named_sample(day: u8, name: &str, text: &str, meta: &Params) -> Result<String, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "invalid sample name '{}', use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    let write = |path: &str, contents: String| {
+        std::fs::write(path, contents).map_err(|e| format!("{}: {}", path, e))
+    };
+    let path = named_sample_path(day, name, "txt");
+    write(&path, format!("{}\n", text.trim_end_matches('\n')))?;
+    if !meta.is_empty() {
+        let lines: String = meta
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+        write(&named_sample_path(day, name, "meta"), lines)?;
+    }
+    Ok(path)
+}
+
+/// Which of a day's input files a run reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Real,
+    Sample,
+    /// A sample saved under a name, see [`save_named_sample`]
+    Named(String),
+}
+
+impl InputSource {
+    fn path(&self, day: u8, extension: &str) -> String {
+        match self {
+            InputSource::Real => input_path(day, false, extension),
+            InputSource::Sample => input_path(day, true, extension),
+            InputSource::Named(name) => named_sample_path(day, name, extension),
+        }
+    }
+
+    /// The known answers to each part, from the `.answers` file next to the
+    /// input: `part1 = ...` and `part2 = ...` lines, both optional
+    pub fn answers(&self, day: u8) -> Result<[Option<String>; 2], String> {
+        let path = self.path(day, "answers");
+        let text = match store::read(std::path::Path::new(&path)) {
+            Ok((text, _)) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok([None, None]),
+            Err(e) => return Err(format!("{}: {}", path, e)),
+        };
+        let mut answers = [None, None];
+        for (name, value) in Params::parse_lines(&text)
+            .map_err(|e| format!("{}: {}", path, e))?
+            .iter()
+        {
+            match name {
+                "part1" => answers[0] = Some(value.to_string()),
+                "part2" => answers[1] = Some(value.to_string()),
+                _ => return Err(format!("{}: unknown key '{}'", path, name)),
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn load(&self, day: u8) -> Result<Input, String> {
+        match self {
+            InputSource::Real | InputSource::Sample => {
+                let is_sample = *self == InputSource::Sample;
+                let path = input_path(day, is_sample, "txt");
+                if !store::exists(std::path::Path::new(&path)) {
+                    return Err(format!("{}: no such file", path));
+                }
+                try_load_input(day, is_sample)
+            }
+            InputSource::Named(name) => load_named_sample(day, name),
+        }
+    }
 }
 
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Real => write!(f, "real"),
+            InputSource::Sample => write!(f, "sample"),
+            InputSource::Named(name) => write!(f, "sample '{}'", name),
+        }
+    }
+}
+
+/// A day's default solution, or `None` if it isn't solved or was compiled out
 pub fn get_solution(day: u8) -> Option<Box<dyn Solution>> {
     match day {
+        #[cfg(feature = "day01")]
         1 => Some(Box::new(day01::Day01)),
+        #[cfg(feature = "day02")]
         2 => Some(Box::new(day02::Day02)),
+        #[cfg(feature = "day03")]
         3 => Some(Box::new(day03::Day03)),
+        #[cfg(feature = "day04")]
         4 => Some(Box::new(day04::Day04)),
+        #[cfg(feature = "day05")]
         5 => Some(Box::new(day05::Day05)),
+        #[cfg(feature = "day06")]
         6 => Some(Box::new(day06::Day06)),
+        #[cfg(feature = "day07")]
         7 => Some(Box::new(day07::Day07)),
+        #[cfg(feature = "day08")]
         8 => Some(Box::new(day08::Day08)),
+        #[cfg(feature = "day09")]
         9 => Some(Box::new(day09::Day09)),
+        #[cfg(feature = "day10")]
         10 => Some(Box::new(day10::Day10)),
+        #[cfg(feature = "day11")]
         11 => Some(Box::new(day11::Day11)),
+        #[cfg(feature = "day12")]
         12 => Some(Box::new(day12::Day12)),
+        #[cfg(feature = "day13")]
         13 => Some(Box::new(day13::Day13)),
+        #[cfg(feature = "day14")]
         14 => Some(Box::new(day14::Day14)),
+        #[cfg(feature = "day15")]
         15 => Some(Box::new(day15::Day15)),
+        #[cfg(feature = "day16")]
         16 => Some(Box::new(day16::Day16)),
+        #[cfg(feature = "day17")]
         17 => Some(Box::new(day17::Day17)),
+        #[cfg(feature = "day18")]
         18 => Some(Box::new(day18::Day18)),
+        #[cfg(feature = "day19")]
         19 => Some(Box::new(day19::Day19)),
+        #[cfg(feature = "day20")]
         20 => Some(Box::new(day20::Day20)),
+        #[cfg(feature = "day21")]
         21 => Some(Box::new(day21::Day21)),
+        #[cfg(feature = "day22")]
         22 => Some(Box::new(day22::Day22)),
+        #[cfg(feature = "day23")]
         23 => Some(Box::new(day23::Day23)),
+        #[cfg(feature = "day24")]
         24 => Some(Box::new(day24::Day24)),
+        #[cfg(feature = "day25")]
         25 => Some(Box::new(day25::Day25)),
         _ => None,
     }
 }
 
-pub fn run_solution(day: u8, is_sample: bool) {
-    if let Some(solution) = get_solution(day) {
-        println!("\nDay {}", day);
+/// The days left out of this build by their `dayXX` features. Every day is
+/// solved, so these are the ones without a solution.
+pub fn compiled_out_days() -> Vec<u8> {
+    (1..=25)
+        .filter(|&day| get_solution(day).is_none())
+        .collect()
+}
+
+/// Says that `what` was left out of this build and which feature brings it in
+pub fn compiled_out(what: &str, feature: &str) -> String {
+    format!(
+        "{} was compiled out of this build, enable feature {}",
+        what, feature
+    )
+}
+
+/// Why a day has no solution, pointing at the feature if it was compiled out
+pub fn no_solution(day: u8) -> String {
+    if compiled_out_days().contains(&day) {
+        compiled_out(&format!("Day {}", day), &format!("day{:02}", day))
+    } else {
+        format!("No solution found for day {}", day)
+    }
+}
+
+/// One named implementation of a day. Every day has a `default` variant;
+/// some also keep slower ones around as oracles for the clever approach.
+pub struct Variant {
+    pub name: String,
+    /// Parts this variant solves its own way. For the others it defers to the
+    /// default, so crosscheck leaves it out there.
+    pub parts: Vec<u8>,
+    pub solution: Box<dyn Solution>,
+}
+
+impl Variant {
+    fn new(name: &str, parts: &[u8], solution: Box<dyn Solution>) -> Self {
+        Self {
+            name: name.to_string(),
+            parts: parts.to_vec(),
+            solution,
+        }
+    }
+}
+
+/// All implementations of a day, default first, then built-in alternatives
+/// and any registered [`external`] solvers and loaded [`plugin`]s. Empty if
+/// the day isn't solved.
+pub fn get_variants(day: u8) -> Vec<Variant> {
+    let Some(default) = get_solution(day) else {
+        return Vec::new();
+    };
+    let mut variants = vec![Variant::new("default", &[1, 2], default)];
+    match day {
+        #[cfg(feature = "day13")]
+        13 => variants.push(Variant::new(
+            "brute-force",
+            &[1],
+            Box::new(day13::Day13BruteForce),
+        )),
+        #[cfg(feature = "day22")]
+        22 => variants.push(Variant::new(
+            "brute-force",
+            &[2],
+            Box::new(day22::Day22BruteForce),
+        )),
+        _ => {}
+    }
+    for solver in external::registered(day)
+        .into_iter()
+        .chain(plugin::registered(day))
+    {
+        if variants.iter().all(|v| v.name != solver.name) {
+            variants.push(solver);
+        }
+    }
+    variants
+}
+
+/// Look up a variant by name, listing the available ones if it doesn't exist
+pub fn get_variant(day: u8, name: &str) -> Result<Box<dyn Solution>, String> {
+    let variants = get_variants(day);
+    if variants.is_empty() {
+        return Err(no_solution(day));
+    }
+    let names: Vec<_> = variants.iter().map(|v| v.name.clone()).collect();
+    variants
+        .into_iter()
+        .find(|v| v.name == name)
+        .map(|v| v.solution)
+        .ok_or_else(|| {
+            format!(
+                "Unknown variant '{}' for day {}, expected one of: {}",
+                name,
+                day,
+                names.join(", ")
+            )
+        })
+}
+
+/// Resolve a day's parameters: its declared defaults with `overrides` applied on top
+pub fn resolve_params(solution: &dyn Solution, overrides: &Params) -> Result<Params, String> {
+    let mut params = solution.default_params();
+    params.apply(overrides)?;
+    Ok(params)
+}
+
+/// Run one part with `params` as that part sees them, see [`Params::for_part`]
+pub fn solve_part(
+    solution: &dyn Solution,
+    part: u8,
+    input: &str,
+    params: &Params,
+) -> Result<String, SolveError> {
+    let params = params.for_part(part);
+    match part {
+        1 => solution.try_part1_with(input, &params),
+        _ => solution.try_part2_with(input, &params),
+    }
+}
+
+/// Solve one part of a day against the given input text, with parameter overrides
+pub fn solve(day: u8, part: u8, input: &str, overrides: &Params) -> Result<String, String> {
+    let solution = get_solution(day).ok_or_else(|| no_solution(day))?;
+    let params = resolve_params(solution.as_ref(), overrides)?;
+    match part {
+        1 | 2 => solve_part(solution.as_ref(), part, input, &params).map_err(|e| e.to_string()),
+        _ => Err(format!("Invalid part {}, expected 1 or 2", part)),
+    }
+}
+
+/// How `run_solution` prints its results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// One JSON object per day, on a line of its own
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown format '{}', expected text or json", s)),
+        }
+    }
+}
+
+/// One part's answer or error, with what it cost
+#[derive(Debug, Clone, Serialize)]
+pub struct PartReport {
+    pub part: u8,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    #[serde(rename = "time_ns", serialize_with = "serialize_nanos")]
+    pub elapsed: Duration,
+    /// Only measured with the `alloc-stats` feature
+    pub memory: Option<alloc::AllocStats>,
+    /// The input's known answer, from its `.answers` file
+    pub expected: Option<String>,
+    /// How the answer was reached, with `--explain`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<explain::Event>,
+}
+
+impl PartReport {
+    /// Whether the answer matches the known one, if there is one
+    pub fn correct(&self) -> Option<bool> {
+        let expected = self.expected.as_ref()?;
+        Some(self.answer.as_ref() == Some(expected))
+    }
+}
+
+fn serialize_nanos<S: serde::Serializer>(elapsed: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u128(elapsed.as_nanos())
+}
+
+/// Both parts of a day run against one input
+#[derive(Debug, Clone, Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    pub input: String,
+    pub origin: Origin,
+    pub variant: String,
+    pub params: Params,
+    pub parts: Vec<PartReport>,
+}
+
+impl DayReport {
+    /// No part contradicts its input's `.answers` file
+    pub fn matches_answers(&self) -> bool {
+        self.parts.iter().all(|part| part.correct() != Some(false))
+    }
+}
+
+/// Run one part, timing it and, if enabled, counting its allocations
+pub(crate) fn measure_part(
+    solution: &dyn Solution,
+    part: u8,
+    input: &str,
+    params: &Params,
+) -> PartReport {
+    // Leftovers from a part that panicked
+    explain::take();
+    let params = params.for_part(part);
+    let start = Instant::now();
+    let (result, memory) = alloc::measure(|| match part {
+        1 => solution.try_part1_with(input, &params),
+        _ => solution.try_part2_with(input, &params),
+    });
+    let elapsed = start.elapsed();
+    let (answer, error) = match result {
+        Ok(answer) => (Some(answer), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    PartReport {
+        part,
+        answer,
+        error,
+        elapsed,
+        memory,
+        expected: None,
+        events: explain::take(),
+    }
+}
+
+/// Run both parts of a day's variant against an input. Errors if the day,
+/// variant, input or parameters can't be set up; a part failing is reported
+/// in its [`PartReport`].
+pub fn run_day(
+    day: u8,
+    source: &InputSource,
+    variant: &str,
+    overrides: &Params,
+) -> Result<DayReport, String> {
+    run_parts(day, source, variant, overrides, &[1, 2])
+}
+
+/// [`run_day`] for just some of the parts
+pub fn run_parts(
+    day: u8,
+    source: &InputSource,
+    variant: &str,
+    overrides: &Params,
+    parts: &[u8],
+) -> Result<DayReport, String> {
+    get_solution(day).ok_or_else(|| no_solution(day))?;
+    let solution = get_variant(day, variant)?;
+    let input = source
+        .load(day)
+        .map_err(|e| format!("Could not read input: {}", e))?;
+    let params = input
+        .params_for(solution.as_ref())
+        .and_then(|mut params| params.apply(overrides).map(|_| params))
+        .map_err(|e| format!("Invalid parameters: {}", e))?;
+    let answers = source.answers(day)?;
+
+    let parts = parts
+        .iter()
+        .map(|&part| PartReport {
+            expected: answers[part as usize - 1].clone(),
+            ..measure_part(solution.as_ref(), part, input.text_for(part), &params)
+        })
+        .collect();
+    Ok(DayReport {
+        day,
+        input: source.to_string(),
+        origin: input.origin,
+        variant: variant.to_string(),
+        params,
+        parts,
+    })
+}
+
+/// Run a day and print its report. Returns whether it ran and every known
+/// answer matched, which is what `--check` asks for.
+pub fn run_solution(
+    day: u8,
+    source: &InputSource,
+    variant: &str,
+    overrides: &Params,
+    format: OutputFormat,
+) -> bool {
+    if format == OutputFormat::Json {
+        let report = run_day(day, source, variant, overrides);
+        let line = match &report {
+            Ok(report) => serde_json::to_string(report),
+            Err(e) => serde_json::to_string(&serde_json::json!({ "day": day, "error": e })),
+        };
+        println!("{}", line.expect("reports always serialize"));
+        return report.is_ok_and(|report| report.matches_answers());
+    }
+
+    if get_solution(day).is_none() {
+        println!("{}", no_solution(day));
+        return false;
+    }
+    println!("\nDay {}", day);
+    println!("Running against {} input", source);
+    if variant != "default" {
+        println!("Variant: {}", variant);
+    }
+
+    let report = match run_day(day, source, variant, overrides) {
+        Ok(report) => report,
+        Err(e) => {
+            println!("{}", e);
+            return false;
+        }
+    };
+    if report.origin != Origin::File {
+        println!("Read from the {} copy", report.origin);
+    }
+    if !report.params.is_empty() {
+        println!("Parameters: {}", report.params);
+    }
+
+    for part in &report.parts {
+        let memory = part
+            .memory
+            .map(|stats| format!(", {}", stats))
+            .unwrap_or_default();
+        let verdict = match (part.correct(), &part.expected) {
+            (Some(true), _) => " ✓".to_string(),
+            (Some(false), Some(expected)) => format!(" ✗ expected {}", expected),
+            _ => String::new(),
+        };
+        match (&part.answer, &part.error) {
+            (Some(answer), _) => println!(
+                "Part {}: {} ({:?}{}){}",
+                part.part, answer, part.elapsed, memory, verdict
+            ),
+            (_, Some(e)) => println!("Part {} failed: {}", part.part, e),
+            (None, None) => unreachable!("a part either answers or fails"),
+        }
+        for event in &part.events {
+            println!("  {}", event);
+        }
+    }
+    report.matches_answers()
+}
+
+/// Every variant's answer to one part, with how long it took
+#[derive(Debug, Clone)]
+pub struct Crosscheck {
+    pub part: u8,
+    pub results: Vec<(String, String, Duration)>,
+}
+
+impl Crosscheck {
+    pub fn agrees(&self) -> bool {
+        self.results
+            .iter()
+            .all(|(_, answer, _)| *answer == self.results[0].1)
+    }
+}
+
+/// Run every variant of a day on the same input, part by part. Parts with
+/// only one implementation are skipped.
+pub fn crosscheck(day: u8, input: &Input, overrides: &Params) -> Result<Vec<Crosscheck>, String> {
+    let variants = get_variants(day);
+    let default = variants.first().ok_or_else(|| no_solution(day))?;
+    let mut params = input.params_for(default.solution.as_ref())?;
+    params.apply(overrides)?;
+
+    let mut checks = Vec::new();
+    for part in [1, 2] {
+        let implementing: Vec<_> = variants
+            .iter()
+            .filter(|v| v.parts.contains(&part))
+            .collect();
+        if implementing.len() < 2 {
+            continue;
+        }
+
+        let text = input.text_for(part);
+        let results = implementing
+            .into_iter()
+            .map(|variant| {
+                let start = Instant::now();
+                let answer = solve_part(variant.solution.as_ref(), part, text, &params);
+                let answer = answer.unwrap_or_else(|e| format!("error: {}", e));
+                (variant.name.clone(), answer, start.elapsed())
+            })
+            .collect();
+        checks.push(Crosscheck { part, results });
+    }
+    Ok(checks)
+}
+
+/// Print a crosscheck of a day's variants. Returns false if any of them disagree.
+pub fn run_crosscheck(day: u8, source: &InputSource, overrides: &Params) -> bool {
+    if get_variants(day).len() < 2 {
+        return true;
+    }
+    println!("\nDay {}", day);
+    println!("Crosschecking against {} input", source);
+
+    let input = match source.load(day) {
+        Ok(input) => input,
+        Err(e) => {
+            println!("Could not read input: {}", e);
+            return false;
+        }
+    };
+    let checks = match crosscheck(day, &input, overrides) {
+        Ok(checks) => checks,
+        Err(e) => {
+            println!("Invalid parameters: {}", e);
+            return false;
+        }
+    };
+
+    let mut all_agree = true;
+    for check in &checks {
+        let verdict = if check.agrees() { "agree" } else { "DISAGREE" };
+        println!("Part {}: {}", check.part, verdict);
+        all_agree &= check.agrees();
+
+        let baseline = check.results[0].2.as_secs_f64();
+        for (name, answer, elapsed) in &check.results {
+            let relative = elapsed.as_secs_f64() / baseline.max(f64::MIN_POSITIVE);
+            println!(
+                "  {:<12} {} ({:?}, {:.2}x)",
+                name, answer, elapsed, relative
+            );
+        }
+    }
+    all_agree
+}
+
+/// Time both parts of a day on generated inputs of each size, printing how
+/// much slower each step got to make performance cliffs stand out
+pub fn run_scaling(day: u8, variant: &str, sizes: &[usize], seed: u64) {
+    let solution = match get_variant(day, variant) {
+        Ok(solution) => solution,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    println!("\nDay {}", day);
+    if variant != "default" {
+        println!("Variant: {}", variant);
+    }
+    println!("Scaling on generated inputs (seed {})", seed);
+
+    let mut previous: Option<[Duration; 2]> = None;
+    for &size in sizes {
+        let Some(input) = generators::generate(day, size, seed) else {
+            println!("No generator for day {}", day);
+            return;
+        };
+        let params = match input.params_for(solution.as_ref()) {
+            Ok(params) => params,
+            Err(e) => {
+                println!("Invalid parameters: {}", e);
+                return;
+            }
+        };
+
+        let times = [1, 2].map(|part| {
+            let start = Instant::now();
+            // Only the timing matters here; failures are reported by the normal run
+            let _ = solve_part(solution.as_ref(), part, input.text_for(part), &params);
+            start.elapsed()
+        });
+
+        let growth = |part: usize| match previous {
+            Some(previous) => format!(
+                "x{:.1}",
+                times[part].as_secs_f64() / previous[part].as_secs_f64().max(f64::MIN_POSITIVE)
+            ),
+            None => "-".to_string(),
+        };
         println!(
-            "Running against {} input",
-            if is_sample { "sample" } else { "real" }
+            "Size {:>7}: part 1 {:>12?} {:>8}, part 2 {:>12?} {:>8}",
+            size,
+            times[0],
+            growth(0),
+            times[1],
+            growth(1)
+        );
+        previous = Some(times);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "day01", feature = "day13"))]
+    fn test_variant_lookup() {
+        assert_eq!(get_variants(1).len(), 1);
+        assert!(get_variant(13, "brute-force").is_ok());
+        let err = get_variant(13, "fast").err().unwrap();
+        assert_eq!(
+            err,
+            "Unknown variant 'fast' for day 13, expected one of: default, brute-force"
         );
+        assert!(get_variant(26, "default").is_err());
+    }
 
-        let input = read_input(day, is_sample);
+    #[test]
+    fn test_compiled_out_days() {
+        assert_eq!(compiled_out_days().is_empty(), cfg!(feature = "all-days"));
+        assert_eq!(no_solution(26), "No solution found for day 26");
+        for day in compiled_out_days() {
+            assert!(get_solution(day).is_none());
+            assert!(no_solution(day).contains(&format!("feature day{:02}", day)));
+        }
+    }
 
-        let start = Instant::now();
-        let p1 = solution.part1(&input);
-        let p1_time = start.elapsed();
-        println!("Part 1: {} ({:?})", p1, p1_time);
+    #[test]
+    #[cfg(feature = "day13")]
+    fn test_crosscheck_sample() {
+        let input = load_input(13, true);
+        let checks = crosscheck(13, &input, &Params::new()).unwrap();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].part, 1);
+        assert!(checks[0].agrees());
+        assert_eq!(checks[0].results[1].0, "brute-force");
+    }
 
-        let start = Instant::now();
-        let p2 = solution.part2(&input);
-        let p2_time = start.elapsed();
-        println!("Part 2: {} ({:?})", p2, p2_time);
-    } else {
-        println!("No solution found for day {}", day);
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_run_day_reports_each_part() {
+        let report = run_day(1, &InputSource::Sample, "default", &Params::new()).unwrap();
+        let answers: Vec<_> = report.parts.iter().map(|p| p.answer.as_deref()).collect();
+        assert_eq!(answers, [Some("11"), Some("31")]);
+        assert_eq!(report.parts[0].memory.is_some(), alloc::enabled());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["parts"][1]["answer"], "31");
+        assert!(run_day(1, &InputSource::Sample, "nope", &Params::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_only_accepts_samples() {
+        for day in (1..=25).filter(|&day| get_solution(day).is_some()) {
+            let solution = get_solution(day).unwrap();
+            let input = load_input(day, true);
+            let params = input.params_for(solution.as_ref()).unwrap();
+            if let Some(parsed) = solution.try_parse(input.text_for(1), &params) {
+                assert!(parsed.is_ok(), "day {}: {:?}", day, parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_real_answers() {
+        for day in (1..=25).filter(|&day| real_input(day).is_some()) {
+            let report = run_day(day, &InputSource::Real, "default", &Params::new()).unwrap();
+            assert!(report.matches_answers(), "day {}: {:?}", day, report.parts);
+        }
     }
 }
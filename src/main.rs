@@ -1,4 +1,7 @@
-use clap::Parser;
+use aoc2024::reduce::Predicate;
+use aoc2024::{InputSource, OutputFormat, Params};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -10,22 +13,379 @@ struct Args {
     /// Use sample input instead of real input
     #[arg(short, long)]
     sample: bool,
+
+    /// Use a named sample, inputs/sample/XX-NAME.txt, e.g. one saved by `reduce`
+    #[arg(long, value_name = "NAME", conflicts_with = "sample", requires = "day")]
+    sample_name: Option<String>,
+
+    /// Override a day parameter, e.g. --param blinks=6 (repeatable)
+    #[arg(short, long = "param", value_name = "KEY=VALUE", value_parser = Params::parse_assignment, requires = "day")]
+    param: Vec<(String, String)>,
+
+    /// Run a named implementation of the day instead of the default
+    #[arg(long, default_value = "default", requires = "day")]
+    variant: String,
+
+    /// Load a solution from a plugin library and run it instead of the
+    /// default (repeatable, see the plugin module docs)
+    #[arg(long, value_name = "PATH", requires = "day")]
+    plugin: Vec<PathBuf>,
+
+    /// Run every implementation of each day on the same input and compare them
+    #[arg(long, conflicts_with = "variant")]
+    crosscheck: bool,
+
+    /// Exit with an error if an answer differs from its input's .answers file
+    #[arg(long, conflicts_with = "crosscheck")]
+    check: bool,
+
+    /// Output format: text, or json for one object per day
+    #[arg(long, default_value = "text")]
+    format: OutputFormat,
+
+    /// Show how each answer was reached, as events under it or in the JSON
+    #[arg(long)]
+    explain: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a random, format-valid input for a day
+    Generate {
+        #[arg(short, long)]
+        day: u8,
+
+        /// Scale of the input: a line count, grid side, ... depending on the day
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Time a day on generated inputs of growing size
+    Scale {
+        #[arg(short, long)]
+        day: u8,
+
+        #[arg(long, value_delimiter = ',', default_value = "10,100,1000")]
+        sizes: Vec<usize>,
+
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Time a named implementation of the day instead of the default
+        #[arg(long, default_value = "default")]
+        variant: String,
+    },
+    /// Shrink an input while it keeps panicking, erroring, disagreeing with a
+    /// variant or giving an answer
+    Reduce {
+        #[arg(short, long)]
+        day: u8,
+
+        /// Input file to shrink; a .meta file next to it is used too
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// panic, error, mismatch:VARIANT or answer:PART=VALUE
+        #[arg(long)]
+        predicate: Predicate,
+
+        /// Only run this part (answer:PART=VALUE picks its own)
+        #[arg(long)]
+        part: Option<u8>,
+
+        /// Override a day parameter, e.g. --param blinks=6 (repeatable)
+        #[arg(short, long = "param", value_name = "KEY=VALUE", value_parser = Params::parse_assignment)]
+        param: Vec<(String, String)>,
+
+        /// Save the result as the named sample inputs/sample/XX-NAME.txt
+        #[arg(long, value_name = "NAME")]
+        save: Option<String>,
+    },
+    /// Feed mutated generated inputs to each day and report the first panic
+    Fuzz {
+        /// Day to fuzz, if not specified all implemented days will be fuzzed
+        #[arg(short, long)]
+        day: Option<u8>,
+
+        #[arg(long, default_value_t = 10)]
+        size: usize,
+
+        /// First seed; each iteration uses the next one
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        #[arg(long, default_value_t = 1000)]
+        iterations: u64,
+    },
+    /// Load inputs, tweak parameters and re-run parts interactively
+    Repl {
+        #[arg(short, long)]
+        day: Option<u8>,
+    },
+    /// Step a day's simulation forward and back, see the debugger module docs
+    Debug {
+        #[arg(short, long)]
+        day: u8,
+
+        /// Step through this part's run, for days where the parts differ
+        #[arg(long, default_value_t = 1)]
+        part: u8,
+
+        /// Use sample input instead of real input
+        #[arg(short, long)]
+        sample: bool,
+
+        /// Use a named sample, inputs/sample/XX-NAME.txt
+        #[arg(long, value_name = "NAME", conflicts_with = "sample")]
+        sample_name: Option<String>,
+
+        /// Override a day parameter, e.g. --param width=11 (repeatable)
+        #[arg(short, long = "param", value_name = "KEY=VALUE", value_parser = Params::parse_assignment)]
+        param: Vec<(String, String)>,
+    },
+    /// Show all days as a calendar to run and check them interactively
+    Tui,
+    /// Answer solve requests over HTTP on localhost, see the serve module docs
+    Serve {
+        #[arg(long, default_value_t = 2024)]
+        port: u16,
+    },
+    /// Manage the encrypted copy of the real inputs, see the store module docs
+    Inputs {
+        #[command(subcommand)]
+        action: InputsAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum InputsAction {
+    /// Encrypt inputs/real into inputs/encrypted
+    Encrypt {
+        /// Encrypt again any encrypted file that doesn't open with the current key
+        #[arg(long)]
+        force: bool,
+    },
+    /// Decrypt inputs/encrypted into inputs/real
+    Decrypt {
+        /// Overwrite real input files that differ from their encrypted copy
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// The day asked for, or every implemented one, noting any compiled out
+fn selected_days(day: Option<u8>) -> Vec<u8> {
+    if let Some(day) = day {
+        return vec![day];
+    }
+    let compiled_out = aoc2024::compiled_out_days();
+    if !compiled_out.is_empty() {
+        let list: Vec<String> = compiled_out.iter().map(u8::to_string).collect();
+        eprintln!("Compiled out of this build: days {}", list.join(", "));
+    }
+    (1..=25)
+        .filter(|&day| aoc2024::get_solution(day).is_some())
+        .collect()
 }
 
 fn main() {
     let args = Args::parse();
 
-    match args.day {
-        Some(day) => {
-            aoc2024::run_solution(day, args.sample);
+    match args.command {
+        Some(Command::Generate { day, size, seed }) => {
+            match aoc2024::generators::generate(day, size, seed) {
+                Some(input) => print!("{}", input.text),
+                None => {
+                    eprintln!("No generator for day {}", day);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Scale {
+            day,
+            sizes,
+            seed,
+            variant,
+        }) => {
+            aoc2024::run_scaling(day, &variant, &sizes, seed);
+            return;
+        }
+        Some(Command::Reduce {
+            day,
+            input,
+            predicate,
+            part,
+            param,
+            save,
+        }) => {
+            let mut overrides = Params::new();
+            for (name, value) in &param {
+                overrides.set(name, value);
+            }
+            let reduced =
+                aoc2024::reduce::run(day, &input, predicate, part, &overrides, save.as_deref());
+            if let Err(e) = reduced {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
         }
-        None => {
-            // Run all implemented solutions
-            for day in 1..=25 {
-                if aoc2024::get_solution(day).is_some() {
-                    aoc2024::run_solution(day, args.sample);
+        Some(Command::Fuzz {
+            day,
+            size,
+            seed,
+            iterations,
+        }) => {
+            if let Some(day) = day.filter(|&day| aoc2024::get_solution(day).is_none()) {
+                eprintln!("{}", aoc2024::no_solution(day));
+                std::process::exit(1);
+            }
+            for day in selected_days(day) {
+                if let Err(failure) = aoc2024::fuzz::run(day, size, seed, iterations) {
+                    eprintln!(
+                        "Day {} {} (size {}, seed {}) on input:\n{}",
+                        failure.day, failure.message, size, failure.seed, failure.input
+                    );
+                    std::process::exit(1);
                 }
+                println!("Day {}: {} inputs, no panics", day, iterations);
+            }
+            return;
+        }
+        Some(Command::Repl { day }) => {
+            if let Err(e) = aoc2024::repl::run(day) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Debug {
+            day,
+            part,
+            sample,
+            sample_name,
+            param,
+        }) => {
+            let mut overrides = Params::new();
+            for (name, value) in &param {
+                overrides.set(name, value);
+            }
+            let source = match (sample_name, sample) {
+                (Some(name), _) => InputSource::Named(name),
+                (None, true) => InputSource::Sample,
+                (None, false) => InputSource::Real,
+            };
+            if let Err(e) = aoc2024::debugger::run(day, part, &source, &overrides) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Tui) => {
+            #[cfg(feature = "tui")]
+            let shown = aoc2024::tui::run();
+            #[cfg(not(feature = "tui"))]
+            let shown: Result<(), String> = Err(aoc2024::compiled_out("The tui command", "tui"));
+            if let Err(e) = shown {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Serve { port }) => {
+            #[cfg(feature = "serve")]
+            let served = aoc2024::serve::serve(port);
+            #[cfg(not(feature = "serve"))]
+            let served: Result<(), String> = {
+                let _ = port;
+                Err(aoc2024::compiled_out("The serve command", "serve"))
+            };
+            if let Err(e) = served {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Inputs { action }) => {
+            let stored = match action {
+                InputsAction::Encrypt { force } => aoc2024::store::run(false, force),
+                InputsAction::Decrypt { force } => aoc2024::store::run(true, force),
+            };
+            if let Err(e) = stored {
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
+            return;
         }
+        None => {}
+    }
+
+    let mut overrides = Params::new();
+    for (name, value) in &args.param {
+        overrides.set(name, value);
+    }
+
+    let mut variant = args.variant;
+    for path in &args.plugin {
+        #[cfg(feature = "plugins")]
+        let loaded = aoc2024::plugin::load(path);
+        #[cfg(not(feature = "plugins"))]
+        let loaded: Result<(u8, String), String> =
+            Err(aoc2024::compiled_out("Loading plugins", "plugins"));
+        match loaded {
+            Ok((day, _)) if Some(day) != args.day => {
+                eprintln!(
+                    "{}: plugin is for day {}, not day {}",
+                    path.display(),
+                    day,
+                    args.day.unwrap_or_default()
+                );
+                std::process::exit(1);
+            }
+            Ok((_, name)) if variant == "default" => variant = name,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let days = selected_days(args.day);
+
+    let source = match (args.sample_name, args.sample) {
+        (Some(name), _) => InputSource::Named(name),
+        (None, true) => InputSource::Sample,
+        (None, false) => InputSource::Real,
+    };
+
+    if args.crosscheck {
+        let mut all_agree = true;
+        for day in days {
+            all_agree &= aoc2024::run_crosscheck(day, &source, &overrides);
+        }
+        if !all_agree {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.format == OutputFormat::Json {
+        // Keep diagnostics out of the JSON
+        aoc2024::set_logging(false);
+    }
+    aoc2024::explain::set_enabled(args.explain);
+    let mut all_match = true;
+    for day in days {
+        all_match &= aoc2024::run_solution(day, &source, &variant, &overrides, args.format);
+    }
+    if args.check && !all_match {
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,331 @@
+//! Number theory helpers and exact solving of small integer linear systems
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Greatest common divisor; always non-negative, and `gcd(0, 0) == 0`
+pub fn gcd(a: i64, b: i64) -> i64 {
+    egcd(a, b).0
+}
+
+/// Least common multiple; zero if either argument is zero
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Extended Euclid: returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`
+pub fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Inverse of `a` modulo `m`, if `a` and `m` are coprime and `m` is positive
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = egcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Chinese remainder theorem. Given `(remainder, modulus)` pairs, returns the
+/// smallest non-negative `x` satisfying every congruence together with the
+/// combined modulus. The moduli don't need to be coprime; returns `None` if
+/// the congruences contradict each other or a modulus isn't positive.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    congruences
+        .iter()
+        .try_fold((0i64, 1i64), |(r1, m1), &(r2, m2)| {
+            if m2 <= 0 {
+                return None;
+            }
+            let r2 = r2.rem_euclid(m2);
+            let (g, p, _) = egcd(m1, m2);
+            if (r2 - r1) % g != 0 {
+                return None;
+            }
+
+            // r1 + m1 * k satisfies both when k ≡ (r2 - r1) / g * p (mod m2 / g)
+            let modulus = (m1 / g) as i128 * m2 as i128;
+            let k = ((r2 - r1) / g) as i128 * p as i128 % (m2 / g) as i128;
+            let x = (r1 as i128 + m1 as i128 * k).rem_euclid(modulus);
+            Some((x as i64, modulus as i64))
+        })
+}
+
+/// An exact fraction, always stored in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+
+    /// Panics if `den` is zero
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "rational with zero denominator");
+        let g = gcd_wide(num, den).max(1) * den.signum();
+        Self {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub fn integer(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+}
+
+fn gcd_wide(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self::integer(n)
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+/// Panics when dividing by zero
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross-multiplying keeps the order
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Solve the square system `a · x = b` exactly by Gaussian elimination.
+/// Returns `None` if the matrix is singular (no unique solution).
+pub fn solve_linear(a: &[Vec<i128>], b: &[i128]) -> Option<Vec<Rational>> {
+    let n = b.len();
+    assert!(
+        a.len() == n && a.iter().all(|row| row.len() == n),
+        "expected a {}x{} matrix",
+        n,
+        n
+    );
+
+    // Augmented matrix [a | b]
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| {
+            row.iter()
+                .chain([&rhs])
+                .map(|&v| Rational::integer(v))
+                .collect()
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| !m[row][col].is_zero())?;
+        m.swap(col, pivot);
+
+        // Clear this column from every other row
+        let pivot_row = m[col].clone();
+        for (row, values) in m.iter_mut().enumerate() {
+            if row != col && !values[col].is_zero() {
+                let factor = values[col] / pivot_row[col];
+                for (value, &p) in values.iter_mut().zip(&pivot_row).skip(col) {
+                    *value = *value - factor * p;
+                }
+            }
+        }
+    }
+
+    Some((0..n).map(|i| m[i][n] / m[i][i]).collect())
+}
+
+/// Like [`solve_linear`], but only accepts a solution whose entries are all
+/// non-negative integers, e.g. counts of button presses
+pub fn solve_non_negative_integer(a: &[Vec<i128>], b: &[i128]) -> Option<Vec<i128>> {
+    solve_linear(a, b)?
+        .into_iter()
+        .map(|x| x.to_integer().filter(|&v| v >= 0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(101, 103), 10403);
+        assert_eq!(lcm(0, 3), 0);
+    }
+
+    #[test]
+    fn test_egcd_and_inverse() {
+        for (a, b) in [(240, 46), (-7, 3), (17, 0), (101, 103)] {
+            let (g, x, y) = egcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_inverse(3, -11), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(-1, 101), (-1, 103)]), Some((10402, 10403)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (0, 0)]), None);
+        assert_eq!(crt(&[(2, -3)]), None);
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, 4);
+        assert_eq!((half.numer(), half.denom()), (1, 2));
+        assert_eq!(Rational::new(3, -6), -half);
+        assert_eq!(half + half, Rational::integer(1));
+        assert_eq!(half * Rational::integer(3) - half, Rational::integer(1));
+        assert_eq!(
+            Rational::integer(1) / Rational::integer(3),
+            Rational::new(1, 3)
+        );
+        assert!(Rational::new(1, 3) < half);
+        assert_eq!(Rational::new(-3, 9).to_string(), "-1/3");
+        assert_eq!(Rational::new(4, 2).to_integer(), Some(2));
+    }
+
+    #[test]
+    fn test_solve_linear() {
+        // 94a + 22b = 8400, 34a + 67b = 5400
+        let a = vec![vec![94, 22], vec![34, 67]];
+        assert_eq!(
+            solve_non_negative_integer(&a, &[8400, 5400]),
+            Some(vec![80, 40])
+        );
+
+        // Fractional and negative solutions are rejected
+        let a = vec![vec![2, 0], vec![0, 1]];
+        assert_eq!(
+            solve_linear(&a, &[1, -1]),
+            Some(vec![Rational::new(1, 2), Rational::integer(-1)])
+        );
+        assert_eq!(solve_non_negative_integer(&a, &[1, 1]), None);
+        assert_eq!(solve_non_negative_integer(&a, &[2, -1]), None);
+
+        // Singular, and a zero pivot that needs a row swap
+        assert_eq!(solve_linear(&[vec![1, 2], vec![2, 4]], &[3, 6]), None);
+        let a = vec![vec![0, 1, 0], vec![1, 0, 0], vec![0, 0, 3]];
+        let x = solve_linear(&a, &[5, 7, 9]).unwrap();
+        assert_eq!(x, [7, 5, 3].map(Rational::integer));
+    }
+}
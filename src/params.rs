@@ -0,0 +1,269 @@
+use crate::SolveError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A named puzzle constant that a day exposes for tuning, e.g. Day 11's blink count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+impl ParamSpec {
+    pub const fn new(name: &'static str, default: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            default,
+            description,
+        }
+    }
+}
+
+/// A set of parameter values keyed by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the default values for a list of declared parameters
+    pub fn from_specs(specs: &[ParamSpec]) -> Self {
+        let values = specs
+            .iter()
+            .map(|spec| (spec.name.to_string(), spec.default.to_string()))
+            .collect();
+        Self { values }
+    }
+
+    pub fn with(mut self, name: &str, value: impl ToString) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set(&mut self, name: &str, value: impl ToString) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Parse a single `key=value` assignment as given on the command line
+    pub fn parse_assignment(s: &str) -> Result<(String, String), String> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", s))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("missing parameter name in '{}'", s));
+        }
+        Ok((key.to_string(), value.trim().to_string()))
+    }
+
+    /// Parse a metadata file of `key = value` lines. Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse_lines(text: &str) -> Result<Self, String> {
+        let mut params = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) =
+                Self::parse_assignment(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            params.set(&key, value);
+        }
+        Ok(params)
+    }
+
+    /// Apply `overrides` on top of these values. Only names that are already
+    /// present (i.e. declared by the day) can be overridden, or scoped to one
+    /// part as `part1.name` or `part2.name`, see [`Params::for_part`]. A plain
+    /// value replaces the part-specific ones set before it.
+    pub fn apply(&mut self, overrides: &Params) -> Result<(), String> {
+        let (scoped, plain): (Vec<_>, Vec<_>) = overrides
+            .iter()
+            .partition(|(name, _)| scoped_name(name).is_some());
+        for (name, value) in plain.into_iter().chain(scoped) {
+            if let Some((_, base)) = scoped_name(name) {
+                if self.values.contains_key(base) {
+                    self.values.insert(name.to_string(), value.to_string());
+                    continue;
+                }
+            }
+            match self.values.get_mut(name) {
+                Some(slot) => {
+                    *slot = value.to_string();
+                    self.values
+                        .retain(|other, _| scoped_name(other).is_none_or(|(_, base)| base != name));
+                }
+                None => {
+                    let known: Vec<&str> = self
+                        .values
+                        .keys()
+                        .map(String::as_str)
+                        .filter(|name| scoped_name(name).is_none())
+                        .collect();
+                    return Err(if known.is_empty() {
+                        format!("unknown parameter '{}' (this day has none)", name)
+                    } else {
+                        format!(
+                            "unknown parameter '{}' (expected one of: {})",
+                            name,
+                            known.join(", ")
+                        )
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The values `part` runs with: `partN.name` replaces `name` for part N
+    /// and is left out for the other part
+    pub fn for_part(&self, part: u8) -> Params {
+        let mut params = Params::new();
+        for (name, value) in self.iter() {
+            if scoped_name(name).is_none() {
+                params.set(name, value);
+            }
+        }
+        for (name, value) in self.iter() {
+            if let Some((scope, base)) = scoped_name(name) {
+                if scope == part {
+                    params.set(base, value);
+                }
+            }
+        }
+        params
+    }
+
+    /// Look up and parse a parameter. Values come from the command line,
+    /// `.meta` files and the server, so a malformed one is an error, not a bug.
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, SolveError> {
+        let raw = self
+            .values
+            .get(name)
+            .ok_or_else(|| SolveError::Param(format!("{} was not provided", name)))?;
+        raw.parse().map_err(|_| {
+            SolveError::Param(format!(
+                "{}={} is not a valid {}",
+                name,
+                raw,
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+}
+
+/// The part and the plain name of a `part1.name` or `part2.name`
+fn scoped_name(name: &str) -> Option<(u8, &str)> {
+    let (scope, base) = name.split_once('.')?;
+    match scope {
+        "part1" => Some((1, base)),
+        "part2" => Some((2, base)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        write!(f, "{}", pairs.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPECS: &[ParamSpec] = &[
+        ParamSpec::new("width", "101", "Grid width"),
+        ParamSpec::new("height", "103", "Grid height"),
+    ];
+
+    #[test]
+    fn test_defaults_and_overrides() {
+        let mut params = Params::from_specs(SPECS);
+        assert_eq!(params.get::<i32>("width"), Ok(101));
+
+        params.apply(&Params::new().with("width", 11)).unwrap();
+        assert_eq!(params.get::<i32>("width"), Ok(11));
+        assert_eq!(params.get::<i32>("height"), Ok(103));
+    }
+
+    #[test]
+    fn test_malformed_value_is_an_error() {
+        let mut params = Params::from_specs(SPECS);
+        params.apply(&Params::new().with("width", "abc")).unwrap();
+        assert_eq!(
+            params.get::<i32>("width").unwrap_err().to_string(),
+            "invalid parameter: width=abc is not a valid i32"
+        );
+        params.apply(&Params::new().with("height", -1)).unwrap();
+        assert!(params.get::<usize>("height").is_err());
+        assert!(params.get::<usize>("depth").is_err());
+    }
+
+    #[test]
+    fn test_unknown_parameter_is_rejected() {
+        let mut params = Params::from_specs(SPECS);
+        let err = params.apply(&Params::new().with("depth", 3)).unwrap_err();
+        assert!(err.contains("depth"));
+    }
+
+    #[test]
+    fn test_part_specific_values() {
+        let mut params = Params::from_specs(SPECS);
+        let meta = Params::new().with("part2.width", 50).with("width", 11);
+        params.apply(&meta).unwrap();
+        assert_eq!(
+            params.for_part(1),
+            Params::from_specs(SPECS).with("width", 11)
+        );
+        assert_eq!(params.for_part(2).get::<i32>("width"), Ok(50));
+        assert_eq!(params.for_part(2).get::<i32>("height"), Ok(103));
+
+        // A later plain value is for both parts again
+        params.apply(&Params::new().with("width", 7)).unwrap();
+        assert_eq!(params.for_part(2).get::<i32>("width"), Ok(7));
+
+        let err = params
+            .apply(&Params::new().with("part2.depth", 1))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "unknown parameter 'part2.depth' (expected one of: height, width)"
+        );
+        assert!(params.apply(&Params::new().with("part3.width", 1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            Params::parse_assignment("blinks=6"),
+            Ok(("blinks".to_string(), "6".to_string()))
+        );
+        assert!(Params::parse_assignment("blinks").is_err());
+        assert!(Params::parse_assignment("=6").is_err());
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let params = Params::parse_lines("# sample grid\nwidth = 11\n\nheight=7\n").unwrap();
+        assert_eq!(params, Params::new().with("width", 11).with("height", 7));
+        assert!(Params::parse_lines("width 11").is_err());
+    }
+}
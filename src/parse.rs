@@ -0,0 +1,477 @@
+//! Small helpers for reading puzzle inputs. Every failure is reported as a
+//! [`ParseError`] carrying the 1-based line and column it happened at.
+//!
+//! Line formats are read with a [`Line`] cursor whose methods chain:
+//!
+//! ```
+//! use aoc2024::parse::Line;
+//!
+//! let mut line = Line::new(1, "Button A: X+94, Y+34");
+//! let x: i64 = line.literal("Button A: X+")?.int()?;
+//! let y: i64 = line.literal(", Y+")?.int()?;
+//! line.end()?;
+//! assert_eq!((x, y), (94, 34));
+//! # Ok::<(), aoc2024::parse::ParseError>(())
+//! ```
+
+use crate::grid::Grid;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Cursor over a single input line
+#[derive(Debug, Clone)]
+pub struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Line<'a> {
+    /// `number` is the 1-based line number used in errors
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Self {
+            number,
+            text,
+            pos: 0,
+        }
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// The whole line, regardless of how much has been consumed
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// 1-based column of the cursor
+    pub fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    /// The unconsumed rest of the line
+    pub fn remaining(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// An error at the cursor
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.column(), message)
+    }
+
+    pub fn error_at(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.number, column, message)
+    }
+
+    pub fn starts_with(&self, s: &str) -> bool {
+        self.remaining().starts_with(s)
+    }
+
+    /// Consume `s` if it comes next, reporting whether it did
+    pub fn eat(&mut self, s: &str) -> bool {
+        let found = self.starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    /// Consume exactly `s`
+    pub fn literal(&mut self, s: &str) -> ParseResult<&mut Self> {
+        if self.eat(s) {
+            Ok(self)
+        } else {
+            Err(self.error(format!("expected '{}', found '{}'", s, self.remaining())))
+        }
+    }
+
+    pub fn skip_whitespace(&mut self) -> &mut Self {
+        let rest = self.remaining();
+        self.pos += rest.len() - rest.trim_start().len();
+        self
+    }
+
+    /// A run of ASCII letters, digits and underscores
+    pub fn word(&mut self) -> ParseResult<&'a str> {
+        let rest = self.remaining();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error(format!("expected a word, found '{}'", rest)));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// A single decimal digit
+    pub fn digit(&mut self) -> ParseResult<u32> {
+        match self.remaining().chars().next() {
+            Some(c) if c.is_ascii_digit() => {
+                self.pos += 1;
+                Ok(c as u32 - '0' as u32)
+            }
+            Some(c) => Err(self.error(format!("expected a digit, found '{}'", c))),
+            None => Err(self.error("expected a digit, found the end of the line")),
+        }
+    }
+
+    /// An optionally signed decimal integer
+    pub fn int<T: FromStr>(&mut self) -> ParseResult<T> {
+        let rest = self.remaining();
+        let sign = usize::from(rest.starts_with(['-', '+']));
+        let digits = rest[sign..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - sign);
+        if digits == 0 {
+            return Err(self.error(format!("expected an integer, found '{}'", rest)));
+        }
+        let token = &rest[..sign + digits];
+        let value = token
+            .parse()
+            .map_err(|_| self.error(format!("invalid integer '{}'", token)))?;
+        self.pos += token.len();
+        Ok(value)
+    }
+
+    /// Everything up to (not including) `delimiter`, which is consumed too
+    pub fn until(&mut self, delimiter: &str) -> ParseResult<&'a str> {
+        let rest = self.remaining();
+        match rest.find(delimiter) {
+            Some(i) => {
+                self.pos += i + delimiter.len();
+                Ok(&rest[..i])
+            }
+            None => Err(self.error(format!("expected '{}'", delimiter))),
+        }
+    }
+
+    /// Read a `label<separator>` prefix such as `Register A: ` and return the
+    /// label, leaving the cursor at the start of the value
+    pub fn label(&mut self, separator: &str) -> ParseResult<&'a str> {
+        let label = self.until(separator)?;
+        Ok(label.trim())
+    }
+
+    /// One or more items separated by `separator`
+    pub fn list<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Fail unless the whole line has been consumed (trailing whitespace is allowed)
+    pub fn end(&self) -> ParseResult<()> {
+        let rest = self.remaining();
+        if rest.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(format!("unexpected '{}'", rest)))
+        }
+    }
+
+    /// Every signed integer left on the line, requiring exactly `N` of them
+    pub fn ints<const N: usize>(&mut self) -> ParseResult<[i64; N]> {
+        let column = self.column();
+        let values = ints(self.remaining());
+        self.pos = self.text.len();
+        values.try_into().map_err(|values: Vec<i64>| {
+            self.error_at(
+                column,
+                format!("expected {} integers, found {}", N, values.len()),
+            )
+        })
+    }
+}
+
+/// Every line of `input`, numbered from 1
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line::new(i + 1, text))
+}
+
+/// Parse each non-blank line with `f`, stopping at the first error
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut f: impl FnMut(&mut Line<'a>) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+    lines(input)
+        .filter(|line| !line.text().trim().is_empty())
+        .map(|mut line| f(&mut line))
+        .collect()
+}
+
+/// A run of non-blank lines
+#[derive(Debug, Clone)]
+pub struct Block<'a> {
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Block<'a> {
+    /// Line number of the block's first line
+    pub fn first_line(&self) -> usize {
+        self.lines[0].number()
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Line<'a>> + '_ {
+        self.lines.iter().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The block as a grid, keeping the line numbers of the whole input in errors
+    pub fn grid_with<T>(&self, cell: impl FnMut(char) -> Option<T>) -> ParseResult<Grid<T>> {
+        grid_from_lines(self.lines(), cell)
+    }
+
+    /// The block's lines, requiring exactly `N` of them
+    pub fn exact_lines<const N: usize>(&self) -> ParseResult<[Line<'a>; N]> {
+        self.lines.clone().try_into().map_err(|lines: Vec<_>| {
+            ParseError::new(
+                self.first_line(),
+                1,
+                format!("expected a block of {} lines, found {}", N, lines.len()),
+            )
+        })
+    }
+}
+
+/// Split `input` into blocks separated by one or more blank lines
+pub fn blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut current = Vec::new();
+    for line in lines(input) {
+        if line.text().trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(Block {
+                    lines: std::mem::take(&mut current),
+                });
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(Block { lines: current });
+    }
+    blocks
+}
+
+/// Split `input` into exactly `N` blocks, e.g. a header and a body
+pub fn sections<const N: usize>(input: &str) -> ParseResult<[Block<'_>; N]> {
+    blocks(input).try_into().map_err(|blocks: Vec<_>| {
+        let line = input.lines().count().max(1);
+        ParseError::new(
+            line,
+            1,
+            format!(
+                "expected {} sections separated by blank lines, found {}",
+                N,
+                blocks.len()
+            ),
+        )
+    })
+}
+
+/// Every signed integer in `s`, in order. A `-` only counts as a sign when it
+/// directly precedes a digit and doesn't follow one, so `3-4` is `[3, 4]`.
+pub fn ints(s: &str) -> Vec<i64> {
+    let bytes = s.as_bytes();
+    let mut values = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_digit() {
+            let negative =
+                i > 0 && bytes[i - 1] == b'-' && (i < 2 || !bytes[i - 2].is_ascii_digit());
+            let start = i;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            // Saturate rather than fail on absurdly long digit runs
+            let magnitude: i64 = s[start..i].parse().unwrap_or(i64::MAX);
+            values.push(if negative { -magnitude } else { magnitude });
+        } else {
+            i += 1;
+        }
+    }
+    values
+}
+
+/// Parse a rectangular grid, one cell per character. `cell` returns `None`
+/// for characters that aren't allowed.
+pub fn grid_with<T>(input: &str, cell: impl FnMut(char) -> Option<T>) -> ParseResult<Grid<T>> {
+    // Trailing blank lines aren't rows
+    grid_from_lines(lines(input.trim_end_matches(['\r', '\n'])), cell)
+}
+
+fn grid_from_lines<'a, T>(
+    lines: impl Iterator<Item = Line<'a>>,
+    mut cell: impl FnMut(char) -> Option<T>,
+) -> ParseResult<Grid<T>> {
+    let mut width = None;
+    let mut cells = Vec::new();
+    let mut height = 0;
+
+    for line in lines {
+        let row = line.text();
+        let mut row_width = 0;
+        for (i, c) in row.chars().enumerate() {
+            let value = cell(c)
+                .ok_or_else(|| line.error_at(i + 1, format!("unexpected character '{}'", c)))?;
+            cells.push(value);
+            row_width += 1;
+        }
+        match width {
+            None => width = Some(row_width),
+            Some(w) if w != row_width => {
+                return Err(line.error_at(
+                    row_width.min(w) + 1,
+                    format!("row has width {}, expected {}", row_width, w),
+                ));
+            }
+            Some(_) => {}
+        }
+        height += 1;
+    }
+
+    Ok(Grid::from_vec(width.unwrap_or(0), height, cells))
+}
+
+pub fn grid(input: &str) -> ParseResult<Grid<char>> {
+    grid_with(input, Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_combinators() {
+        let mut line = Line::new(3, "a XOR b -> z00");
+        let a = line.word().unwrap();
+        let op = line.skip_whitespace().word().unwrap();
+        let b = line.skip_whitespace().word().unwrap();
+        let out = line.literal(" -> ").unwrap().word().unwrap();
+        assert_eq!((a, op, b, out), ("a", "XOR", "b", "z00"));
+        assert!(line.end().is_ok());
+
+        let mut line = Line::new(7, "Program: 0,3,5,4");
+        line.literal("Program: ").unwrap();
+        let program: Vec<u8> = line.list(",", |l| l.int()).unwrap();
+        assert_eq!(program, vec![0, 3, 5, 4]);
+
+        let mut line = Line::new(1, "42");
+        assert_eq!((line.digit().unwrap(), line.digit().unwrap()), (4, 2));
+        assert!(line.digit().is_err());
+
+        let mut line = Line::new(1, "Register A: 729");
+        assert_eq!(line.label(": ").unwrap(), "Register A");
+        assert_eq!(line.int::<i64>().unwrap(), 729);
+    }
+
+    #[test]
+    fn test_errors_are_positioned() {
+        let mut line = Line::new(4, "p=1,x");
+        let err = line.literal("p=").unwrap().int::<i32>().and_then(|_| {
+            line.literal(",")?;
+            line.int::<i32>()
+        });
+        assert_eq!(
+            err.unwrap_err(),
+            ParseError::new(4, 5, "expected an integer, found 'x'")
+        );
+
+        let err = Line::new(2, "300").int::<u8>().unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 1: invalid integer '300'");
+
+        let err = Line::new(1, "ab").literal("ac").unwrap_err();
+        assert_eq!(err.column, 1);
+        assert_eq!(Line::new(1, "12 x").ints::<1>().unwrap(), [12]);
+        assert!(Line::new(1, "1 2 3").ints::<2>().is_err());
+    }
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints("p=0,4 v=3,-3"), vec![0, 4, 3, -3]);
+        assert_eq!(ints("X+94, Y=-34"), vec![94, -34]);
+        assert_eq!(ints("2024-12-25"), vec![2024, 12, 25]);
+        assert!(ints("none here").is_empty());
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = "a\nb\n\n\nc\n";
+        let blocks = blocks(input);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].first_line(), 5);
+        let [a, b] = blocks[0].exact_lines().unwrap();
+        assert_eq!((a.text(), b.text()), ("a", "b"));
+        assert!(blocks[1].exact_lines::<2>().is_err());
+
+        let [_, second] = sections(input).unwrap();
+        assert_eq!(second.len(), 1);
+        assert!(sections::<3>(input).is_err());
+        assert_eq!(
+            parse_lines("1\n\n2", |l| l.int::<i32>()).unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let digits = grid_with("12\n34", |c| c.to_digit(10)).unwrap();
+        assert_eq!(digits[(1, 0)], 3);
+
+        let err = grid_with("12\n3x", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+
+        let err = grid("abc\nab").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+}
@@ -0,0 +1,405 @@
+//! Shortest-path searches that are generic over the node type. Callers pass a
+//! successor function instead of building an explicit graph, so the same code
+//! works for grid positions, `(position, direction)` states and so on.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Breadth-first search from `start` to the nearest node satisfying `is_goal`.
+/// Returns the path including both ends, so its length in steps is `len() - 1`.
+pub fn bfs<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents: HashMap<N, Option<N>> = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct(&parents, node));
+        }
+        for next in successors(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Number of steps from `start` to every reachable node
+pub fn bfs_distances<N, I>(start: N, mut successors: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        let next_distance = distances[&node] + 1;
+        for next in successors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), next_distance);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Cheapest path from `start` to a node satisfying `is_goal`, where
+/// `successors` yields `(next, edge_cost)` pairs. Returns the path and its cost.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// Cheapest cost from `start` to every reachable node
+pub fn dijkstra_distances<N, C, I>(start: N, mut successors: impl FnMut(&N) -> I) -> HashMap<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut distances = HashMap::from([(start.clone(), C::default())]);
+    let mut heap = BinaryHeap::from([Entry::new(C::default(), C::default(), start)]);
+
+    while let Some(Entry { cost, node, .. }) = heap.pop() {
+        if cost > distances[&node] {
+            continue;
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if distances.get(&next).is_none_or(|&best| next_cost < best) {
+                distances.insert(next.clone(), next_cost);
+                heap.push(Entry::new(next_cost, next_cost, next));
+            }
+        }
+    }
+
+    distances
+}
+
+/// A* search. `heuristic` must never overestimate the remaining cost to a
+/// goal, otherwise the returned path may not be the cheapest.
+pub fn astar<N, C, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut best: HashMap<N, (C, Option<N>)> =
+        HashMap::from([(start.clone(), (C::default(), None))]);
+    let mut heap = BinaryHeap::from([Entry::new(heuristic(&start), C::default(), start)]);
+
+    while let Some(Entry { cost, node, .. }) = heap.pop() {
+        if cost > best[&node].0 {
+            continue;
+        }
+        if is_goal(&node) {
+            let parents: HashMap<N, Option<N>> = best
+                .into_iter()
+                .map(|(n, (_, parent))| (n, parent))
+                .collect();
+            return Some((reconstruct(&parents, node), cost));
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if best.get(&next).is_none_or(|&(c, _)| next_cost < c) {
+                best.insert(next.clone(), (next_cost, Some(node.clone())));
+                heap.push(Entry::new(next_cost + heuristic(&next), next_cost, next));
+            }
+        }
+    }
+
+    None
+}
+
+/// Like [`dijkstra`], but keeps every optimal predecessor of each node, so all
+/// cheapest paths to the goal can be recovered. Every goal node reachable at
+/// the optimal cost becomes an end of the returned [`ShortestPaths`].
+pub fn dijkstra_all<N, C, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(ShortestPaths<N, C>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut distances = HashMap::from([(start.clone(), C::default())]);
+    let mut predecessors: HashMap<N, Vec<N>> = HashMap::new();
+    let mut heap = BinaryHeap::from([Entry::new(C::default(), C::default(), start.clone())]);
+    let mut goal_cost = None;
+    let mut ends = Vec::new();
+
+    while let Some(Entry { cost, node, .. }) = heap.pop() {
+        if cost > distances[&node] {
+            continue;
+        }
+        if goal_cost.is_some_and(|goal| cost > goal) {
+            break;
+        }
+        if is_goal(&node) {
+            goal_cost = Some(cost);
+            ends.push(node);
+            continue;
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            match distances.get(&next) {
+                Some(&best) if next_cost > best => {}
+                Some(&best) if next_cost == best => {
+                    predecessors.entry(next).or_default().push(node.clone());
+                }
+                _ => {
+                    distances.insert(next.clone(), next_cost);
+                    predecessors.insert(next.clone(), vec![node.clone()]);
+                    heap.push(Entry::new(next_cost, next_cost, next));
+                }
+            }
+        }
+    }
+
+    let cost = goal_cost?;
+    let paths = ShortestPaths {
+        start,
+        ends,
+        distances,
+        predecessors,
+    };
+    Some((paths, cost))
+}
+
+/// The predecessor DAG of all cheapest paths found by [`dijkstra_all`]
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N, C> {
+    start: N,
+    ends: Vec<N>,
+    distances: HashMap<N, C>,
+    predecessors: HashMap<N, Vec<N>>,
+}
+
+impl<N, C> ShortestPaths<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord,
+{
+    /// The goal nodes reached at the optimal cost
+    pub fn ends(&self) -> &[N] {
+        &self.ends
+    }
+
+    /// Every node that lies on at least one optimal path
+    pub fn nodes(&self) -> HashSet<N> {
+        let mut seen: HashSet<N> = self.ends.iter().cloned().collect();
+        let mut stack = self.ends.clone();
+        while let Some(node) = stack.pop() {
+            for prev in self.predecessors.get(&node).into_iter().flatten() {
+                if seen.insert(prev.clone()) {
+                    stack.push(prev.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Number of distinct optimal paths, without enumerating them
+    pub fn count_paths(&self) -> usize {
+        let mut nodes: Vec<N> = self.nodes().into_iter().collect();
+        nodes.sort_by_key(|node| self.distances[node]);
+
+        // Predecessors are always strictly closer to the start unless edges
+        // have zero cost, so counting in distance order sees them first
+        let mut counts: HashMap<N, usize> = HashMap::new();
+        for node in nodes {
+            let count = if node == self.start {
+                1
+            } else {
+                self.predecessors[&node]
+                    .iter()
+                    .map(|prev| counts.get(prev).copied().unwrap_or(0))
+                    .sum()
+            };
+            counts.insert(node, count);
+        }
+
+        self.ends.iter().map(|end| counts[end]).sum()
+    }
+
+    /// Every optimal path from the start to one of the ends. The number of
+    /// paths can grow exponentially, so prefer [`Self::count_paths`] or
+    /// [`Self::nodes`] when they are enough.
+    pub fn paths(&self) -> Vec<Vec<N>> {
+        let mut paths = Vec::new();
+        let mut stack: Vec<Vec<N>> = self.ends.iter().map(|end| vec![end.clone()]).collect();
+        while let Some(partial) = stack.pop() {
+            let head = partial.last().unwrap();
+            if *head == self.start {
+                paths.push(partial.into_iter().rev().collect());
+                continue;
+            }
+            for prev in &self.predecessors[head] {
+                let mut extended = partial.clone();
+                extended.push(prev.clone());
+                stack.push(extended);
+            }
+        }
+        paths
+    }
+}
+
+/// Walk parent links back from `node` to the start
+fn reconstruct<N: Clone + Eq + Hash>(parents: &HashMap<N, Option<N>>, node: N) -> Vec<N> {
+    let mut path = vec![node];
+    while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Heap entry ordered so that `BinaryHeap` pops the lowest priority first.
+/// Only the priority takes part in comparisons, so nodes needn't be `Ord`.
+struct Entry<C, N> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<C, N> Entry<C, N> {
+    fn new(priority: C, cost: C, node: N) -> Self {
+        Self {
+            priority,
+            cost,
+            node,
+        }
+    }
+}
+
+impl<C: Ord, N> Ord for Entry<C, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<C: Ord, N> PartialOrd for Entry<C, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord, N> PartialEq for Entry<C, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<C: Ord, N> Eq for Entry<C, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+S..#
+.#..
+...E";
+
+    fn open_neighbors(grid: &Grid<char>, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors4()
+            .into_iter()
+            .filter(move |&n| grid.get_point(n).is_some_and(|&c| c != '#'))
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = Grid::parse(MAZE);
+        let end = Point::new(3, 2);
+        let path = bfs(
+            Point::new(0, 0),
+            |&p| open_neighbors(&grid, p),
+            |&p| p == end,
+        )
+        .unwrap();
+        assert_eq!(path.len() - 1, 5);
+        assert_eq!(path.first(), Some(&Point::new(0, 0)));
+        assert_eq!(path.last(), Some(&end));
+
+        let distances = bfs_distances(Point::new(0, 0), |&p| open_neighbors(&grid, p));
+        assert_eq!(distances[&end], 5);
+        assert_eq!(distances.len(), 10);
+        assert_eq!(bfs(Point::new(0, 0), |_| [], |&p| p == end), None);
+    }
+
+    #[test]
+    fn test_weighted_searches_agree() {
+        // Moving right is cheap, every other move is expensive
+        let grid = Grid::parse(MAZE);
+        let successors = |p: &Point| {
+            open_neighbors(&grid, *p)
+                .map(|n| (n, if n.x > p.x { 1 } else { 5 }))
+                .collect::<Vec<_>>()
+        };
+        let end = Point::new(3, 2);
+
+        let (path, cost) = dijkstra(Point::new(0, 0), successors, |&p| p == end).unwrap();
+        assert_eq!(cost, 13);
+        assert_eq!(path.len(), 6);
+
+        let heuristic = |p: &Point| p.manhattan(end);
+        let (_, astar_cost) =
+            astar(Point::new(0, 0), successors, heuristic, |&p| p == end).unwrap();
+        assert_eq!(astar_cost, cost);
+
+        let distances = dijkstra_distances(Point::new(0, 0), successors);
+        assert_eq!(distances[&end], cost);
+    }
+
+    #[test]
+    fn test_all_shortest_paths() {
+        // Open 3x3 room: there are 6 shortest paths between opposite corners
+        let grid = Grid::parse("...\n...\n...");
+        let end = Point::new(2, 2);
+        let (paths, cost) = dijkstra_all(
+            Point::new(0, 0),
+            |&p| open_neighbors(&grid, p).map(|n| (n, 1)),
+            |&p| p == end,
+        )
+        .unwrap();
+
+        assert_eq!(cost, 4);
+        assert_eq!(paths.ends(), &[end]);
+        assert_eq!(paths.count_paths(), 6);
+        assert_eq!(paths.paths().len(), 6);
+        assert!(paths.paths().iter().all(|path| path.len() == 5));
+        assert_eq!(paths.nodes().len(), 9);
+    }
+}
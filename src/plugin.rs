@@ -0,0 +1,415 @@
+//! Solutions loaded at runtime from dynamic libraries, so an implementation
+//! can be swapped without rebuilding the runner.
+//!
+//! A plugin is a `cdylib` exporting `aoc_plugin`, a C function returning a
+//! pointer to a static [`PluginV1`]. Rust plugins depend on this crate and
+//! use [`export_plugin!`](crate::export_plugin) to wrap a [`Solution`]:
+//!
+//! ```ignore
+//! aoc2024::export_plugin!(day: 1, name: "fast", parts: [1, 2], solution: Day01Fast);
+//! ```
+//!
+//! The host checks the plugin's ABI version before using anything else in
+//! it. Loaded plugins stay loaded, and their solution becomes a variant of
+//! its day named after the plugin, like an [`external`](crate::external)
+//! solver. Parameters come from the built-in solution, which also answers the
+//! parts the plugin doesn't solve.
+
+use crate::{get_solution, Params, Solution, SolveError, Variant};
+use std::ffi::{c_char, CStr, CString};
+#[cfg(feature = "plugins")]
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Bumped whenever [`PluginV1`] or the meaning of its fields changes
+pub const ABI_VERSION: u32 = 1;
+
+/// The symbol every plugin exports
+pub const ENTRY_POINT: &[u8] = b"aoc_plugin";
+
+/// [`PluginV1::solve`] answered; `out` holds the answer
+pub const SOLVED: i32 = 0;
+/// [`PluginV1::solve`] failed; `out` holds the reason
+pub const FAILED: i32 = 1;
+
+/// What a plugin exports. `abi_version` comes first so any version of the
+/// host can read it before trusting the rest of the layout.
+#[repr(C)]
+pub struct PluginV1 {
+    pub abi_version: u32,
+    pub day: u8,
+    /// Bit 0 set for part 1, bit 1 for part 2
+    pub parts: u8,
+    /// NUL-terminated variant name
+    pub name: *const c_char,
+    /// Solves `part` of the UTF-8 `input` with `params` given as `key = value`
+    /// lines. Returns [`SOLVED`] or [`FAILED`] and stores a NUL-terminated
+    /// string in `out`, to be released with `free_string`.
+    pub solve: unsafe extern "C" fn(
+        part: u8,
+        input: *const u8,
+        input_len: usize,
+        params: *const u8,
+        params_len: usize,
+        out: *mut *mut c_char,
+    ) -> i32,
+    pub free_string: unsafe extern "C" fn(*mut c_char),
+}
+
+// Only ever built as an immutable static
+unsafe impl Sync for PluginV1 {}
+
+/// Exports a [`Solution`] as a plugin. Expands to the `aoc_plugin` entry
+/// point, so use it once per `cdylib`.
+#[macro_export]
+macro_rules! export_plugin {
+    (day: $day:expr, name: $name:expr, parts: [$($part:expr),+], solution: $solution:expr) => {
+        #[no_mangle]
+        pub extern "C" fn aoc_plugin() -> *const $crate::plugin::PluginV1 {
+            unsafe extern "C" fn solve(
+                part: u8,
+                input: *const u8,
+                input_len: usize,
+                params: *const u8,
+                params_len: usize,
+                out: *mut *mut ::std::ffi::c_char,
+            ) -> i32 {
+                $crate::plugin::solve_with(&$solution, part, input, input_len, params, params_len, out)
+            }
+
+            static PLUGIN: $crate::plugin::PluginV1 = $crate::plugin::PluginV1 {
+                abi_version: $crate::plugin::ABI_VERSION,
+                day: $day,
+                parts: 0 $(| 1 << ($part - 1))+,
+                name: concat!($name, "\0").as_ptr() as *const ::std::ffi::c_char,
+                solve,
+                free_string: $crate::plugin::free_string,
+            };
+            &PLUGIN
+        }
+    };
+}
+
+/// The plugin side of [`PluginV1::solve`], used by [`export_plugin!`](crate::export_plugin)
+///
+/// # Safety
+///
+/// `input` and `params` must point to `input_len` and `params_len` readable
+/// bytes, and `out` must be writable.
+#[doc(hidden)]
+pub unsafe fn solve_with(
+    solution: &dyn Solution,
+    part: u8,
+    input: *const u8,
+    input_len: usize,
+    params: *const u8,
+    params_len: usize,
+    out: *mut *mut c_char,
+) -> i32 {
+    let input = std::slice::from_raw_parts(input, input_len);
+    let params = std::slice::from_raw_parts(params, params_len);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let input = std::str::from_utf8(input).map_err(|_| "input is not UTF-8".to_string())?;
+        let params = std::str::from_utf8(params).map_err(|_| "params are not UTF-8".to_string())?;
+        let params = Params::parse_lines(params)?;
+        match part {
+            1 => solution.try_part1_with(input, &params),
+            _ => solution.try_part2_with(input, &params),
+        }
+        .map_err(|e| e.to_string())
+    }))
+    .unwrap_or_else(|_| Err("panicked".to_string()));
+
+    let (status, text) = match result {
+        Ok(answer) => (SOLVED, answer),
+        Err(reason) => (FAILED, reason),
+    };
+    // Interior NULs can't cross the boundary; nothing legitimate contains them
+    let text = CString::new(text.replace('\0', "")).expect("NULs were removed");
+    *out = text.into_raw();
+    status
+}
+
+/// Releases a string from [`solve_with`]
+///
+/// # Safety
+///
+/// `s` must come from [`solve_with`] in the same library and not be freed yet.
+#[doc(hidden)]
+pub unsafe extern "C" fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// A loaded plugin's solution
+pub struct PluginSolution {
+    plugin: &'static PluginV1,
+    name: String,
+    builtin: Box<dyn Solution>,
+}
+
+impl PluginSolution {
+    /// Checks the plugin's day and name before using it
+    pub fn new(plugin: &'static PluginV1) -> Result<Self, String> {
+        let builtin = get_solution(plugin.day)
+            .ok_or_else(|| format!("plugin is for day {}, which has no solution", plugin.day))?;
+        if plugin.name.is_null() {
+            return Err("plugin has no name".to_string());
+        }
+        // SAFETY: a plugin of this ABI version promises a NUL-terminated name
+        let name = unsafe { CStr::from_ptr(plugin.name) }
+            .to_str()
+            .map_err(|_| "plugin name is not UTF-8".to_string())?
+            .to_string();
+        Ok(Self {
+            plugin,
+            name,
+            builtin,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn day(&self) -> u8 {
+        self.plugin.day
+    }
+
+    pub fn parts(&self) -> Vec<u8> {
+        [1, 2]
+            .into_iter()
+            .filter(|part| self.plugin.parts & (1 << (part - 1)) != 0)
+            .collect()
+    }
+
+    fn solve(&self, part: u8, input: &str, params: &Params) -> Result<String, SolveError> {
+        if !self.parts().contains(&part) {
+            return match part {
+                1 => self.builtin.try_part1_with(input, params),
+                _ => self.builtin.try_part2_with(input, params),
+            };
+        }
+        let params: String = params
+            .iter()
+            .map(|(name, value)| format!("{} = {}\n", name, value))
+            .collect();
+        let mut out = std::ptr::null_mut();
+        // SAFETY: the pointers and lengths describe live buffers, and the
+        // plugin passed the ABI check in `new`
+        let status = unsafe {
+            (self.plugin.solve)(
+                part,
+                input.as_ptr(),
+                input.len(),
+                params.as_ptr(),
+                params.len(),
+                &mut out,
+            )
+        };
+        if out.is_null() {
+            return Err(SolveError::External(format!("{}: no result", self.name)));
+        }
+        // SAFETY: `out` is a NUL-terminated string from the plugin, released
+        // by the plugin's own allocator once copied
+        let text = unsafe {
+            let text = CStr::from_ptr(out).to_string_lossy().into_owned();
+            (self.plugin.free_string)(out);
+            text
+        };
+        match status {
+            SOLVED => Ok(text),
+            _ => Err(SolveError::External(format!("{}: {}", self.name, text))),
+        }
+    }
+}
+
+impl Solution for PluginSolution {
+    fn params(&self) -> Vec<crate::ParamSpec> {
+        self.builtin.params()
+    }
+
+    fn try_part1_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(1, input, params)
+    }
+
+    fn try_part2_with(&self, input: &str, params: &Params) -> Result<String, SolveError> {
+        self.solve(2, input, params)
+    }
+}
+
+static LOADED: Mutex<Vec<&'static PluginV1>> = Mutex::new(Vec::new());
+
+/// Loads a plugin library and registers its solution as a variant of its
+/// day. Returns the day and variant name.
+#[cfg(feature = "plugins")]
+pub fn load(path: &Path) -> Result<(u8, String), String> {
+    let fail = |e: String| format!("{}: {}", path.display(), e);
+    // SAFETY: loading runs the library's initialisers; plugins are trusted
+    // code, like the runner itself
+    // A bare file name would make the loader search the system library paths
+    let local = Path::new(".").join(path);
+    let library = unsafe { libloading::Library::new(&local) }.map_err(|e| fail(e.to_string()))?;
+    // Plugins are never unloaded, which keeps `&'static PluginV1` honest
+    let library: &'static libloading::Library = Box::leak(Box::new(library));
+    // SAFETY: the entry point's signature is part of the ABI
+    let entry = unsafe { library.get::<extern "C" fn() -> *const PluginV1>(ENTRY_POINT) }
+        .map_err(|_| fail("not a plugin, it has no aoc_plugin entry point".to_string()))?;
+    // SAFETY: the library stays loaded, so what it points to lives on
+    let plugin = unsafe { checked(entry()) }.map_err(fail)?;
+    register(plugin).map_err(fail)
+}
+
+/// The plugin behind an entry point's pointer, once its version is known to
+/// match. Only the leading `abi_version` is read before that, since the rest
+/// of the layout can differ between versions.
+///
+/// # Safety
+///
+/// `plugin` must be null or point to a `u32` version followed, if it is
+/// [`ABI_VERSION`], by the rest of a [`PluginV1`] that lives forever.
+#[cfg(feature = "plugins")]
+unsafe fn checked(plugin: *const PluginV1) -> Result<&'static PluginV1, String> {
+    if plugin.is_null() {
+        return Err("aoc_plugin returned null".to_string());
+    }
+    let version = std::ptr::addr_of!((*plugin).abi_version).read();
+    if version != ABI_VERSION {
+        return Err(format!(
+            "plugin is built for ABI version {}, this runner supports version {}",
+            version, ABI_VERSION
+        ));
+    }
+    Ok(&*plugin)
+}
+
+/// Adds a plugin to its day's variants, refusing a name the day already has
+#[cfg(feature = "plugins")]
+fn register(plugin: &'static PluginV1) -> Result<(u8, String), String> {
+    let solution = PluginSolution::new(plugin)?;
+    let (day, name) = (solution.day(), solution.name().to_string());
+    if crate::get_variants(day)
+        .iter()
+        .any(|variant| variant.name == name)
+    {
+        return Err(format!(
+            "day {} already has a variant named '{}'",
+            day, name
+        ));
+    }
+    LOADED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(plugin);
+    Ok((day, name))
+}
+
+/// The loaded plugins' variants for a day
+pub fn registered(day: u8) -> Vec<Variant> {
+    LOADED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|plugin| plugin.day == day)
+        .filter_map(|&plugin| {
+            let solution = PluginSolution::new(plugin).ok()?;
+            Some(Variant {
+                name: solution.name().to_string(),
+                parts: solution.parts(),
+                solution: Box::new(solution),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Doubled;
+
+    impl Solution for Doubled {
+        fn try_part1_with(&self, input: &str, _: &Params) -> Result<String, SolveError> {
+            let n: u64 = input
+                .trim()
+                .parse()
+                .map_err(|_| SolveError::unsolvable("NaN"))?;
+            Ok((n * 2).to_string())
+        }
+
+        fn try_part2_with(&self, _: &str, _: &Params) -> Result<String, SolveError> {
+            panic!("not this part")
+        }
+    }
+
+    crate::export_plugin!(day: 3, name: "doubled", parts: [1], solution: Doubled);
+
+    #[cfg(feature = "day03")]
+    fn plugin() -> &'static PluginV1 {
+        unsafe { &*aoc_plugin() }
+    }
+
+    #[test]
+    #[cfg(feature = "day03")]
+    fn test_calls_through_the_abi() {
+        let solution = PluginSolution::new(plugin()).unwrap();
+        assert_eq!((solution.day(), solution.name()), (3, "doubled"));
+        assert_eq!(solution.parts(), [1]);
+
+        let params = solution.default_params();
+        assert_eq!(solution.try_part1_with("21", &params), Ok("42".to_string()));
+        let error = solution.try_part1_with("x", &params).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "external solver failed: doubled: unsolvable input: NaN"
+        );
+        // Part 2 isn't exported, so the built-in Day 3 answers it
+        assert_eq!(
+            solution.try_part2_with("do()mul(2,3)", &params),
+            Ok("6".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "plugins", feature = "day03"))]
+    fn test_checks_versions() {
+        unsafe extern "C" fn solve(
+            _: u8,
+            _: *const u8,
+            _: usize,
+            _: *const u8,
+            _: usize,
+            _: *mut *mut c_char,
+        ) -> i32 {
+            unreachable!("never called on a mismatched plugin")
+        }
+        static FUTURE: PluginV1 = PluginV1 {
+            abi_version: ABI_VERSION + 1,
+            day: 3,
+            parts: 1,
+            name: c"future".as_ptr(),
+            solve,
+            free_string,
+        };
+        let error = unsafe { checked(&FUTURE) }.err().unwrap();
+        assert!(error.contains("ABI version 2"), "{}", error);
+        assert!(unsafe { checked(std::ptr::null()) }.is_err());
+        assert!(unsafe { checked(plugin()) }.is_ok());
+
+        static DEFAULT: PluginV1 = PluginV1 {
+            abi_version: ABI_VERSION,
+            name: c"default".as_ptr(),
+            ..FUTURE
+        };
+        let error = register(&DEFAULT).unwrap_err();
+        assert_eq!(error, "day 3 already has a variant named 'default'");
+    }
+
+    #[test]
+    #[cfg(feature = "plugins")]
+    fn test_rejects_non_plugins() {
+        let error = load(Path::new("Cargo.toml")).unwrap_err();
+        assert!(error.starts_with("Cargo.toml: "), "{}", error);
+    }
+}
@@ -0,0 +1,364 @@
+//! Delta debugging for puzzle inputs: shrink an input for as long as it keeps
+//! showing some behaviour, e.g. "part 2 panics" or "the brute-force variant
+//! disagrees".
+//!
+//! Reduction knows the shape of AoC inputs. It drops whole blank-line
+//! separated blocks first, then lines within each block, then columns of any
+//! block that looks like a grid, and repeats until nothing more can go. A
+//! single line counts as a grid, so one-line inputs lose characters.
+
+use crate::fuzz::{self, Outcome};
+use crate::{
+    get_variants, load_input_file, resolve_params, save_named_sample, Input, Params, Variant,
+};
+use std::fmt;
+use std::panic;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The behaviour a reduced input must keep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// A part panics
+    Panic,
+    /// A part returns a `SolveError`
+    Error,
+    /// The named variant answers differently from the default
+    Mismatch(String),
+    /// The part gives exactly this answer
+    Answer(u8, String),
+}
+
+impl FromStr for Predicate {
+    type Err = String;
+
+    /// `panic`, `error`, `mismatch:VARIANT` or `answer:PART=VALUE`
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.split_once(':') {
+            None if s == "panic" => Ok(Predicate::Panic),
+            None if s == "error" => Ok(Predicate::Error),
+            Some(("mismatch", variant)) if !variant.is_empty() => {
+                Ok(Predicate::Mismatch(variant.to_string()))
+            }
+            Some(("answer", answer)) => match answer.split_once('=') {
+                Some(("1", value)) => Ok(Predicate::Answer(1, value.to_string())),
+                Some(("2", value)) => Ok(Predicate::Answer(2, value.to_string())),
+                _ => Err(format!("expected answer:PART=VALUE, got '{}'", s)),
+            },
+            _ => Err(format!(
+                "unknown predicate '{}', expected panic, error, mismatch:VARIANT or answer:PART=VALUE",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Predicate::Panic => write!(f, "panic"),
+            Predicate::Error => write!(f, "error"),
+            Predicate::Mismatch(variant) => write!(f, "mismatch:{}", variant),
+            Predicate::Answer(part, value) => write!(f, "answer:{}={}", part, value),
+        }
+    }
+}
+
+/// Tests inputs against a predicate for one day
+pub struct Reducer {
+    predicate: Predicate,
+    parts: Vec<u8>,
+    params: Params,
+    default: Variant,
+    other: Option<Variant>,
+    /// How many candidate inputs have been tried
+    pub tests: usize,
+}
+
+impl Reducer {
+    /// `part` limits the check to one part; `overrides` are applied on top of
+    /// the day's default parameters
+    pub fn new(
+        day: u8,
+        predicate: Predicate,
+        part: Option<u8>,
+        overrides: &Params,
+    ) -> Result<Self, String> {
+        let mut variants = get_variants(day).into_iter();
+        let default = variants.next().ok_or_else(|| crate::no_solution(day))?;
+        let params = resolve_params(default.solution.as_ref(), overrides)?;
+
+        let mut parts: Vec<u8> = part.map_or(vec![1, 2], |part| vec![part]);
+        let other = match &predicate {
+            Predicate::Mismatch(name) => {
+                let other = variants
+                    .find(|v| v.name == *name)
+                    .ok_or_else(|| format!("Unknown variant '{}' for day {}", name, day))?;
+                parts.retain(|part| other.parts.contains(part));
+                Some(other)
+            }
+            Predicate::Answer(answer_part, _) => {
+                parts = vec![*answer_part];
+                None
+            }
+            _ => None,
+        };
+        if parts.is_empty() {
+            return Err("the variant doesn't implement that part itself".to_string());
+        }
+
+        Ok(Self {
+            predicate,
+            parts,
+            params,
+            default,
+            other,
+            tests: 0,
+        })
+    }
+
+    /// Whether `input` still shows the behaviour
+    pub fn holds(&mut self, input: &str) -> bool {
+        self.tests += 1;
+        let run = |variant: &Variant, part| {
+            fuzz::run_part(variant.solution.as_ref(), part, input, &self.params)
+        };
+
+        self.parts.iter().any(|&part| {
+            let outcome = run(&self.default, part);
+            match &self.predicate {
+                Predicate::Panic => matches!(outcome, Outcome::Panic(_)),
+                Predicate::Error => matches!(outcome, Outcome::Error(_)),
+                Predicate::Answer(_, expected) => outcome == Outcome::Answer(expected.clone()),
+                Predicate::Mismatch(_) => {
+                    let other = run(self.other.as_ref().unwrap(), part);
+                    // Two different errors on junk input aren't a disagreement
+                    let answered = matches!(outcome, Outcome::Answer(_))
+                        || matches!(other, Outcome::Answer(_));
+                    answered && outcome != other
+                }
+            }
+        })
+    }
+
+    /// The smallest input found that still shows the behaviour. Errors if the
+    /// original input doesn't show it to begin with.
+    pub fn reduce(&mut self, input: &str) -> Result<String, String> {
+        if !self.holds(input) {
+            return Err(format!("the input doesn't show '{}'", self.predicate));
+        }
+
+        let mut blocks = split_blocks(input);
+        loop {
+            let before = join_blocks(&blocks);
+
+            blocks = ddmin(blocks, |candidate| self.holds(&join_blocks(candidate)));
+
+            for i in 0..blocks.len() {
+                let lines = ddmin(blocks[i].clone(), |candidate| {
+                    self.holds(&join_blocks_with(&blocks, i, candidate.to_vec()))
+                });
+                blocks[i] = lines;
+            }
+
+            for i in 0..blocks.len() {
+                let Some(width) = grid_width(&blocks[i]) else {
+                    continue;
+                };
+                let grid = blocks[i].clone();
+                let columns = ddmin((0..width).collect(), |kept| {
+                    let narrowed = keep_columns(&grid, kept);
+                    self.holds(&join_blocks_with(&blocks, i, narrowed))
+                });
+                blocks[i] = keep_columns(&grid, &columns);
+            }
+
+            if join_blocks(&blocks) == before {
+                return Ok(before);
+            }
+        }
+    }
+}
+
+/// The `reduce` command: shrinks the input file, prints the result and, if
+/// `save` names one, stores it as a named sample. A `.meta` file next to the
+/// input is used and saved along with it, with `overrides` on top.
+pub fn run(
+    day: u8,
+    path: &Path,
+    predicate: Predicate,
+    part: Option<u8>,
+    overrides: &Params,
+    save: Option<&str>,
+) -> Result<(), String> {
+    let Input {
+        text: input,
+        mut meta,
+        ..
+    } = load_input_file(path)?;
+    for (name, value) in overrides.iter() {
+        meta.set(name, value);
+    }
+
+    let mut reducer = Reducer::new(day, predicate, part, &meta)?;
+    // Panics are expected here, so keep the default hook from reporting each one
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let reduced = reducer.reduce(&input);
+    panic::set_hook(hook);
+    let reduced = reduced?;
+
+    println!("{}", reduced);
+    eprintln!(
+        "Reduced {} lines ({} bytes) to {} lines ({} bytes) in {} tests",
+        input.lines().count(),
+        input.len(),
+        reduced.lines().count(),
+        reduced.len(),
+        reducer.tests
+    );
+    if let Some(name) = save {
+        let saved = save_named_sample(day, name, &reduced, &meta)?;
+        eprintln!("Saved as {}", saved);
+    }
+    Ok(())
+}
+
+/// Splits on blank lines into blocks of lines
+fn split_blocks(input: &str) -> Vec<Vec<String>> {
+    let mut blocks = vec![Vec::new()];
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            if !blocks.last().unwrap().is_empty() {
+                blocks.push(Vec::new());
+            }
+        } else {
+            blocks.last_mut().unwrap().push(line.to_string());
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks
+}
+
+fn join_blocks(blocks: &[Vec<String>]) -> String {
+    blocks
+        .iter()
+        .filter(|block| !block.is_empty())
+        .map(|block| block.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `blocks` with block `i` replaced
+fn join_blocks_with(blocks: &[Vec<String>], i: usize, block: Vec<String>) -> String {
+    let mut blocks = blocks.to_vec();
+    blocks[i] = block;
+    join_blocks(&blocks)
+}
+
+/// The width of a block whose lines all have the same length
+fn grid_width(block: &[String]) -> Option<usize> {
+    let width = block.first()?.chars().count();
+    let rectangular = block.iter().all(|line| line.chars().count() == width);
+    (rectangular && width >= 2).then_some(width)
+}
+
+fn keep_columns(grid: &[String], columns: &[usize]) -> Vec<String> {
+    grid.iter()
+        .map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            columns.iter().map(|&c| chars[c]).collect()
+        })
+        .collect()
+}
+
+/// Removes as many items as possible while `test` still holds on what's left.
+/// Drops chunks of half the items, then quarters, and so on down to single
+/// items, which it repeats until none can go.
+fn ddmin<T: Clone>(mut items: Vec<T>, mut test: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut chunk = items.len().div_ceil(2);
+    while chunk > 0 {
+        let mut removed_any = false;
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + chunk).min(items.len());
+            let candidate: Vec<T> = items[..start]
+                .iter()
+                .chain(&items[end..])
+                .cloned()
+                .collect();
+            if test(&candidate) {
+                items = candidate;
+                removed_any = true;
+            } else {
+                start = end;
+            }
+        }
+        if chunk > 1 {
+            chunk = chunk.div_ceil(2);
+        } else if !removed_any {
+            break;
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddmin_finds_minimal_subset() {
+        let items: Vec<u32> = (0..50).collect();
+        let reduced = ddmin(items, |kept| kept.contains(&7) && kept.contains(&42));
+        assert_eq!(reduced, vec![7, 42]);
+    }
+
+    #[test]
+    fn test_predicate_parsing() {
+        assert_eq!("panic".parse(), Ok(Predicate::Panic));
+        assert_eq!(
+            "mismatch:brute-force".parse(),
+            Ok(Predicate::Mismatch("brute-force".to_string()))
+        );
+        assert_eq!(
+            "answer:2=117440".parse(),
+            Ok(Predicate::Answer(2, "117440".to_string()))
+        );
+        assert!("answer:3=1".parse::<Predicate>().is_err());
+        assert!("explode".parse::<Predicate>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_reduces_to_the_bad_line() {
+        let input = "3   4\n4   3\n2   5\n1   x3\n3   9\n3   3";
+        let mut reducer = Reducer::new(1, Predicate::Error, None, &Params::new()).unwrap();
+        let reduced = reducer.reduce(input).unwrap();
+        assert!(reducer.holds(&reduced));
+        assert_eq!(reduced.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "day04")]
+    fn test_shrinks_grid_columns() {
+        // The XMAS in the first row is the only match
+        let input = "..XMAS..\n.M..S...\n..A..X..";
+        let mut reducer = Reducer::new(
+            4,
+            Predicate::Answer(1, "1".to_string()),
+            None,
+            &Params::new(),
+        )
+        .unwrap();
+        let reduced = reducer.reduce(input).unwrap();
+        assert_eq!(reduced, "XMAS");
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_predicate_must_hold_initially() {
+        let mut reducer = Reducer::new(1, Predicate::Panic, None, &Params::new()).unwrap();
+        assert!(reducer.reduce("3   4").is_err());
+    }
+}
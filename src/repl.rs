@@ -0,0 +1,375 @@
+//! An interactive session for poking at one day: load an input, tweak
+//! parameters, toggle rendering and logging, and re-run without restarting.
+//! `help` lists the commands.
+
+use crate::{
+    get_solution, get_variant, get_variants, load_input_file, measure_part, set_logging,
+    set_rendering, Input, InputSource, Params, PartReport,
+};
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+const HELP: &str = "\
+day N              switch to day N (clears the input and parameters)
+sample [NAME]      load the day's sample, or the named sample NAME
+real               load the day's real input
+load PATH          load an input file, with its .meta if there is one
+paste              type or paste an input, ending with a line holding only '.'
+run [1|2]          run both parts, or just one
+set KEY=VALUE      override a parameter
+unset KEY          drop an override
+params             show the parameters a run would use
+variant [NAME]     show the variants, or switch to one
+render [on|off]    toggle drawing grids and maps
+log [on|off]       toggle progress and diagnostic output
+history            list this session's results
+help               show this
+quit               leave";
+
+/// One part's run, kept for `history`
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub day: u8,
+    pub variant: String,
+    pub input: String,
+    pub params: Params,
+    pub report: PartReport,
+}
+
+/// The session state; feed it lines with [`Repl::handle`]
+pub struct Repl {
+    day: Option<u8>,
+    variant: String,
+    /// The loaded input and where it came from
+    input: Option<(String, Input)>,
+    overrides: Params,
+    rendering: bool,
+    logging: bool,
+    /// Lines of an input being pasted
+    pasting: Option<Vec<String>>,
+    pub history: Vec<Entry>,
+    pub done: bool,
+}
+
+impl Repl {
+    pub fn new(day: Option<u8>) -> Self {
+        Self {
+            day,
+            variant: "default".to_string(),
+            input: None,
+            overrides: Params::new(),
+            rendering: crate::rendering(),
+            logging: crate::logging(),
+            pasting: None,
+            history: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        match (&self.pasting, self.day) {
+            (Some(_), _) => "... ".to_string(),
+            (None, Some(day)) => format!("day {}> ", day),
+            (None, None) => "> ".to_string(),
+        }
+    }
+
+    /// Runs one line of input, returning what to show
+    pub fn handle(&mut self, line: &str) -> Result<String, String> {
+        if let Some(lines) = &mut self.pasting {
+            if line.trim_end() != "." {
+                lines.push(line.to_string());
+                return Ok(String::new());
+            }
+            let text = lines.join("\n");
+            self.pasting = None;
+            return self.set_input("pasted text", Input::from(text));
+        }
+
+        let line = line.trim();
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        match command {
+            "" => Ok(String::new()),
+            "day" => {
+                let day = arg
+                    .parse()
+                    .map_err(|_| format!("No solution found for day {}", arg))?;
+                if get_solution(day).is_none() {
+                    return Err(crate::no_solution(day));
+                }
+                self.day = Some(day);
+                self.variant = "default".to_string();
+                self.input = None;
+                self.overrides = Params::new();
+                Ok(format!("Day {}", day))
+            }
+            "sample" if arg.is_empty() => self.load_source(InputSource::Sample),
+            "sample" => self.load_source(InputSource::Named(arg.to_string())),
+            "real" => self.load_source(InputSource::Real),
+            "load" if !arg.is_empty() => {
+                let input = load_input_file(Path::new(arg))?;
+                self.set_input(arg, input)
+            }
+            "paste" => {
+                self.pasting = Some(Vec::new());
+                Ok("Paste the input, then a line with only '.'".to_string())
+            }
+            "run" => match arg {
+                "" => self.run(&[1, 2]),
+                "1" => self.run(&[1]),
+                "2" => self.run(&[2]),
+                _ => Err(format!("Invalid part {}, expected 1 or 2", arg)),
+            },
+            "set" => {
+                let (name, value) = Params::parse_assignment(arg)?;
+                let mut overrides = self.overrides.clone();
+                overrides.set(&name, value);
+                self.params_with(&overrides)?;
+                self.overrides = overrides;
+                self.show_params()
+            }
+            "unset" if !arg.is_empty() => {
+                let mut overrides = Params::new();
+                for (name, value) in self.overrides.iter().filter(|(name, _)| *name != arg) {
+                    overrides.set(name, value);
+                }
+                self.overrides = overrides;
+                self.show_params()
+            }
+            "params" => self.show_params(),
+            "variant" => self.variant(arg),
+            "render" => {
+                self.rendering = toggle(self.rendering, arg)?;
+                Ok(format!("Rendering {}", on_off(self.rendering)))
+            }
+            "log" => {
+                self.logging = toggle(self.logging, arg)?;
+                Ok(format!("Logging {}", on_off(self.logging)))
+            }
+            "history" => Ok(self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| format!("[{}] {}", i + 1, describe(entry)))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "help" => Ok(HELP.to_string()),
+            "quit" | "exit" => {
+                self.done = true;
+                Ok(String::new())
+            }
+            _ => Err(format!("Unknown command '{}', try help", line)),
+        }
+    }
+
+    fn day(&self) -> Result<u8, String> {
+        self.day
+            .ok_or_else(|| "Pick a day first: day N".to_string())
+    }
+
+    fn load_source(&mut self, source: InputSource) -> Result<String, String> {
+        let input = source.load(self.day()?)?;
+        self.set_input(&format!("{} input", source), input)
+    }
+
+    fn set_input(&mut self, label: &str, input: Input) -> Result<String, String> {
+        let loaded = format!("Loaded {} ({} lines)", label, input.text.lines().count());
+        self.input = Some((label.to_string(), input));
+        Ok(loaded)
+    }
+
+    /// The parameters a run would use: the day's defaults, then the input's
+    /// metadata, then `overrides`
+    fn params_with(&self, overrides: &Params) -> Result<Params, String> {
+        let solution = get_variant(self.day()?, &self.variant)?;
+        let mut params = match &self.input {
+            Some((_, input)) => input.params_for(solution.as_ref())?,
+            None => solution.default_params(),
+        };
+        params.apply(overrides)?;
+        Ok(params)
+    }
+
+    fn show_params(&self) -> Result<String, String> {
+        let params = self.params_with(&self.overrides)?;
+        Ok(if params.is_empty() {
+            "No parameters".to_string()
+        } else {
+            params.to_string()
+        })
+    }
+
+    fn variant(&mut self, name: &str) -> Result<String, String> {
+        let day = self.day()?;
+        if name.is_empty() {
+            let names: Vec<String> = get_variants(day)
+                .iter()
+                .map(|v| {
+                    if v.name == self.variant {
+                        format!("{} (current)", v.name)
+                    } else {
+                        v.name.to_string()
+                    }
+                })
+                .collect();
+            return Ok(names.join("\n"));
+        }
+        get_variant(day, name)?;
+        self.variant = name.to_string();
+        Ok(format!("Variant: {}", name))
+    }
+
+    fn run(&mut self, parts: &[u8]) -> Result<String, String> {
+        let day = self.day()?;
+        let Some((label, input)) = &self.input else {
+            return Err("Load an input first: sample, real, load PATH or paste".to_string());
+        };
+        let solution = get_variant(day, &self.variant)?;
+        let params = self.params_with(&self.overrides)?;
+
+        set_rendering(self.rendering);
+        set_logging(self.logging);
+        let mut lines = Vec::new();
+        for &part in parts {
+            let text = input.text_for(part);
+            let report = panic::catch_unwind(AssertUnwindSafe(|| {
+                measure_part(solution.as_ref(), part, text, &params)
+            }))
+            .map_err(|_| format!("Part {} panicked", part))?;
+            let entry = Entry {
+                day,
+                variant: self.variant.clone(),
+                input: label.clone(),
+                params: params.clone(),
+                report,
+            };
+            lines.push(format!("[{}] {}", self.history.len() + 1, describe(&entry)));
+            self.history.push(entry);
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+fn toggle(current: bool, arg: &str) -> Result<bool, String> {
+    match arg {
+        "" => Ok(!current),
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Expected on or off, got '{}'", arg)),
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn describe(entry: &Entry) -> String {
+    let report = &entry.report;
+    let result = match (&report.answer, &report.error) {
+        (Some(answer), _) => format!("{} ({:?})", answer, report.elapsed),
+        (_, Some(e)) => format!("failed: {}", e),
+        (None, None) => unreachable!("a part either answers or fails"),
+    };
+    let mut context = vec![entry.input.clone()];
+    if entry.variant != "default" {
+        context.push(format!("variant {}", entry.variant));
+    }
+    if !entry.params.is_empty() {
+        context.push(entry.params.to_string());
+    }
+    format!(
+        "Day {} part {}: {} [{}]",
+        entry.day,
+        report.part,
+        result,
+        context.join("; ")
+    )
+}
+
+/// The `repl` command, reading commands from stdin until `quit` or EOF
+pub fn run(day: Option<u8>) -> Result<(), String> {
+    let mut repl = Repl::new(day);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    // Solutions may panic on odd input; the message is shown as an error instead
+    panic::set_hook(Box::new(|_| {}));
+    while !repl.done {
+        print!("{}", repl.prompt());
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let Some(line) = lines.next() else {
+            break;
+        };
+        match repl.handle(&line.map_err(|e| e.to_string())?) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(e) => println!("error: {}", e),
+        }
+    }
+    let _ = panic::take_hook();
+    Ok(())
+}
+
+#[cfg(all(test, feature = "day11"))]
+mod tests {
+    use super::*;
+
+    fn run_all(repl: &mut Repl, lines: &[&str]) -> Vec<Result<String, String>> {
+        lines.iter().map(|line| repl.handle(line)).collect()
+    }
+
+    #[test]
+    fn test_session() {
+        let mut repl = Repl::new(None);
+        assert!(repl.handle("run").is_err());
+        let replies = run_all(
+            &mut repl,
+            &[
+                "day 11",
+                "paste",
+                "125 17",
+                ".",
+                "set part1_blinks=6",
+                "run 1",
+            ],
+        );
+        assert_eq!(replies[3], Ok("Loaded pasted text (1 lines)".to_string()));
+        assert!(replies[5].as_ref().unwrap().contains("part 1: 22"));
+
+        assert!(repl.handle("set nope=1").is_err());
+        repl.handle("unset part1_blinks").unwrap();
+        repl.handle("run 1").unwrap();
+        let answers: Vec<_> = repl
+            .history
+            .iter()
+            .map(|e| e.report.answer.as_deref())
+            .collect();
+        assert_eq!(answers, [Some("22"), Some("55312")]);
+        assert!(repl.handle("history").unwrap().starts_with("[1] Day 11"));
+    }
+
+    #[test]
+    #[cfg(all(feature = "day01", feature = "day22"))]
+    fn test_variants_and_toggles() {
+        let mut repl = Repl::new(Some(22));
+        assert_eq!(
+            repl.handle("variant").unwrap(),
+            "default (current)\nbrute-force"
+        );
+        assert!(repl.handle("variant nope").is_err());
+        repl.handle("variant brute-force").unwrap();
+        assert_eq!(repl.handle("log off"), Ok("Logging off".to_string()));
+        assert_eq!(repl.handle("render"), Ok("Rendering on".to_string()));
+        assert!(repl.handle("render maybe").is_err());
+        repl.handle("day 1").unwrap();
+        assert_eq!(repl.variant, "default");
+        assert!(repl.handle("frobnicate").is_err());
+        repl.handle("quit").unwrap();
+        assert!(repl.done);
+    }
+}
@@ -0,0 +1,368 @@
+//! A small HTTP front end to the solutions, for running them without a Rust
+//! toolchain. It only listens on localhost.
+//!
+//! - `GET /days` lists the solved days with their variants and parameters
+//! - `GET /days/DAY` is one entry of that list
+//! - `POST /days/DAY/PART` solves the request body as input. Query arguments
+//!   set parameters, except `variant`, which picks the implementation:
+//!   `POST /days/11/1?part1_blinks=6`
+//!
+//! Every response is JSON. A solved or failed part is reported like one part
+//! of `--format json`, with status 200 for an answer and 422 for a
+//! [`SolveError`](crate::SolveError). Bodies over 16 MiB are refused with 413.
+
+use crate::{get_variants, measure_part, resolve_params, ParamSpec, Params, PartReport};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use tiny_http::{Header, Server};
+
+/// Largest input body accepted, well above any real puzzle input
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+/// A status code and JSON body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::to_value(body).expect("responses always serialize"),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DayInfo {
+    day: u8,
+    variants: Vec<VariantInfo>,
+    params: Vec<ParamSpec>,
+}
+
+#[derive(Serialize)]
+struct VariantInfo {
+    name: String,
+    parts: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct Answer {
+    day: u8,
+    variant: String,
+    params: Params,
+    #[serde(flatten)]
+    part: PartReport,
+}
+
+fn day_info(day: u8) -> Option<DayInfo> {
+    let variants = get_variants(day);
+    let params = variants.first()?.solution.params();
+    Some(DayInfo {
+        day,
+        variants: variants
+            .into_iter()
+            .map(|v| VariantInfo {
+                name: v.name,
+                parts: v.parts,
+            })
+            .collect(),
+        params,
+    })
+}
+
+/// Answers one request. This is the whole server apart from the socket, so
+/// it can be called directly.
+pub fn handle(method: &str, url: &str, body: &str) -> Response {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["days"]) => Response::ok((1..=25).filter_map(day_info).collect::<Vec<_>>()),
+        ("GET", ["days", day]) => match day.parse().ok().and_then(day_info) {
+            Some(info) => Response::ok(info),
+            None => unknown_day(day),
+        },
+        ("POST", ["days", day, part]) => solve(day, part, query, body),
+        (_, ["days"] | ["days", _] | ["days", _, _]) => {
+            Response::error(405, format!("{} is not allowed on {}", method, path))
+        }
+        _ => Response::error(404, format!("No such endpoint: {}", path)),
+    }
+}
+
+fn unknown_day(day: &str) -> Response {
+    let message = day.parse().map_or_else(
+        |_| format!("No solution found for day {}", day),
+        crate::no_solution,
+    );
+    Response::error(404, message)
+}
+
+fn solve(day: &str, part: &str, query: &str, body: &str) -> Response {
+    let Some(day) = day.parse().ok().filter(|&day| day_info(day).is_some()) else {
+        return unknown_day(day);
+    };
+    let part = match part.parse() {
+        Ok(part @ (1 | 2)) => part,
+        _ => return Response::error(400, format!("Invalid part {}, expected 1 or 2", part)),
+    };
+
+    let mut variant = "default".to_string();
+    let mut overrides = Params::new();
+    for (name, value) in parse_query(query) {
+        if name == "variant" {
+            variant = value;
+        } else {
+            overrides.set(&name, value);
+        }
+    }
+
+    let Some(found) = get_variants(day).into_iter().find(|v| v.name == variant) else {
+        return Response::error(
+            400,
+            format!("Unknown variant '{}' for day {}", variant, day),
+        );
+    };
+    let params = match resolve_params(found.solution.as_ref(), &overrides) {
+        Ok(params) => params,
+        Err(e) => return Response::error(400, format!("Invalid parameters: {}", e)),
+    };
+
+    // A panic is a bug, but it shouldn't take the server down with it
+    let report = panic::catch_unwind(AssertUnwindSafe(|| {
+        measure_part(found.solution.as_ref(), part, body, &params)
+    }));
+    let Ok(report) = report else {
+        return Response::error(500, format!("day {} part {} panicked", day, part));
+    };
+
+    let status = if report.error.is_some() { 422 } else { 200 };
+    Response {
+        status,
+        ..Response::ok(Answer {
+            day,
+            variant,
+            params,
+            part: report,
+        })
+    }
+}
+
+/// `a=1&b=x+y` into name/value pairs, undoing form encoding
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Reads a body of at most [`MAX_BODY`] bytes. A longer one is refused with
+/// 413 rather than cut short, which would solve part of the input; one whose
+/// `Content-Length` says so is refused before reading it.
+fn read_body(reader: impl Read, length: Option<usize>) -> Result<String, Response> {
+    let too_large = || Response::error(413, format!("Body is over {} bytes", MAX_BODY));
+    if length.is_some_and(|length| length as u64 > MAX_BODY) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    reader
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| Response::error(400, format!("Could not read body: {}", e)))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(too_large());
+    }
+    String::from_utf8(body).map_err(|e| Response::error(400, format!("Body is not UTF-8: {}", e)))
+}
+
+/// Listens on `127.0.0.1:port`; port 0 picks a free one
+pub fn bind(port: u16) -> Result<Server, String> {
+    Server::http(("127.0.0.1", port))
+        .map_err(|e| format!("Could not listen on port {}: {}", port, e))
+}
+
+/// Answers requests until the server is unblocked
+pub fn run(server: &Server) {
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("header is valid");
+    for mut request in server.incoming_requests() {
+        let length = request.body_length();
+        let response = match read_body(request.as_reader(), length) {
+            Ok(body) => handle(request.method().as_str(), request.url(), &body),
+            Err(response) => response,
+        };
+        let reply = tiny_http::Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type.clone());
+        // The client hanging up early is its own problem
+        let _ = request.respond(reply);
+    }
+}
+
+/// The `serve` command
+pub fn serve(port: u16) -> Result<(), String> {
+    let server = bind(port)?;
+    eprintln!("Listening on http://{}", server.server_addr());
+    run(&server);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    #[cfg(all(feature = "day11", feature = "day13"))]
+    fn test_lists_days() {
+        let response = handle("GET", "/days", "");
+        assert_eq!(response.status, 200);
+        let days = response.body.as_array().unwrap();
+        assert_eq!(days.len(), 25 - crate::compiled_out_days().len());
+        let day = |n: u8| days.iter().find(|info| info["day"] == n).unwrap();
+        assert_eq!(day(11)["params"][0]["name"], "part1_blinks");
+        assert_eq!(day(13)["variants"][1]["name"], "brute-force");
+        assert_eq!(handle("GET", "/days/26", "").status, 404);
+    }
+
+    #[test]
+    #[cfg(all(feature = "day11", feature = "day22"))]
+    fn test_solves_with_params_and_variants() {
+        let response = handle("POST", "/days/11/1?part1_blinks=6", "125 17");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["answer"], "22");
+        assert_eq!(response.body["params"]["part1_blinks"], "6");
+        assert!(response.body["time_ns"].is_u64());
+
+        let input = "1\n10\n100\n2024";
+        let response = handle("POST", "/days/22/1?variant=brute-force", input);
+        assert_eq!(response.body["variant"], "brute-force");
+        assert_eq!(response.body["answer"], "37327623");
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_reports_errors() {
+        let response = handle("POST", "/days/1/1", "3 x");
+        assert_eq!(response.status, 422);
+        assert!(response.body["answer"].is_null());
+        assert!(response.body["error"].is_string());
+
+        assert_eq!(handle("POST", "/days/1/3", "").status, 400);
+        assert_eq!(handle("POST", "/days/1/1?nope=1", "").status, 400);
+        assert_eq!(handle("POST", "/days/1/1?variant=nope", "").status, 400);
+        assert_eq!(handle("DELETE", "/days", "").status, 405);
+        assert_eq!(handle("GET", "/", "").status, 404);
+    }
+
+    #[test]
+    fn test_rejects_oversized_bodies() {
+        let body = |size: u64| io::repeat(b'1').take(size);
+        assert_eq!(
+            read_body(body(MAX_BODY), None).unwrap().len() as u64,
+            MAX_BODY
+        );
+        assert_eq!(read_body(body(MAX_BODY + 1), None).unwrap_err().status, 413);
+        let length = Some(MAX_BODY as usize + 1);
+        assert_eq!(read_body(body(0), length).unwrap_err().status, 413);
+        assert_eq!(read_body(&[0xff][..], None).unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b+c%2"), "a b c%2");
+        assert_eq!(
+            parse_query("variant=brute-force&x=%3D"),
+            [
+                ("variant".to_string(), "brute-force".to_string()),
+                ("x".to_string(), "=".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_serves_over_http() {
+        use std::io::Write;
+        use std::net::TcpStream;
+        use std::sync::Arc;
+        use std::thread;
+
+        let server = Arc::new(bind(0).unwrap());
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = thread::spawn({
+            let server = Arc::clone(&server);
+            move || run(&server)
+        });
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let body = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+        write!(
+            stream,
+            "POST /days/1/2 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /days/1/2 HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        )
+        .unwrap();
+        let mut too_large = String::new();
+        stream.read_to_string(&mut too_large).unwrap();
+        drop(stream);
+
+        server.unblock();
+        handle.join().unwrap();
+
+        assert!(reply.starts_with("HTTP/1.1 200"));
+        assert!(reply.contains("application/json"));
+        assert!(reply.contains(r#""answer":"31""#));
+        assert!(too_large.starts_with("HTTP/1.1 413"));
+    }
+}
@@ -0,0 +1,147 @@
+//! Golden-file tests for rendered states: maps, paths, intermediate layouts.
+//!
+//! Days list what they render with a `snapshots()` function. Each
+//! [`Snapshot`] is compared with its golden file next to the samples,
+//! `inputs/sample/XX-NAME.snap`:
+//!
+//! ```bash
+//! cargo test --test snapshots                        # compare
+//! cargo test --test snapshots -- --update-snapshots  # rewrite the golden files
+//! ```
+//!
+//! A missing golden file fails the check too, so a new snapshot is only
+//! accepted by updating on purpose.
+
+use std::path::PathBuf;
+
+/// One rendered state of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub day: u8,
+    pub name: String,
+    pub text: String,
+}
+
+impl Snapshot {
+    pub fn new(day: u8, name: &str, text: impl Into<String>) -> Self {
+        Self {
+            day,
+            name: name.to_string(),
+            text: text.into(),
+        }
+    }
+
+    /// Its golden file
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("inputs/sample/{:02}-{}.snap", self.day, self.name))
+    }
+
+    /// The text as stored: trailing whitespace trimmed, one final newline
+    fn normalized(&self) -> String {
+        format!("{}\n", self.text.trim_end())
+    }
+}
+
+/// The snapshots of every compiled day
+pub fn all() -> Vec<Snapshot> {
+    #[allow(unused_mut)]
+    let mut snapshots = Vec::new();
+    #[cfg(feature = "day08")]
+    snapshots.extend(crate::day08::snapshots());
+    #[cfg(feature = "day14")]
+    snapshots.extend(crate::day14::snapshots());
+    #[cfg(feature = "day15")]
+    snapshots.extend(crate::day15::snapshots());
+    snapshots
+}
+
+/// How a snapshot compared with its golden file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Matched,
+    /// Written because it was missing or different, in update mode
+    Written,
+    Missing,
+    /// The differing lines, see [`diff`]
+    Changed(String),
+}
+
+/// Compares a snapshot with its golden file, or writes the file if `update`
+/// is set and they differ
+pub fn check(snapshot: &Snapshot, update: bool) -> Result<Outcome, String> {
+    let path = snapshot.path();
+    let actual = snapshot.normalized();
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => Some(expected),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    match expected {
+        Some(expected) if expected == actual => Ok(Outcome::Matched),
+        _ if update => {
+            std::fs::write(&path, actual).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(Outcome::Written)
+        }
+        Some(expected) => Ok(Outcome::Changed(diff(&expected, &actual))),
+        None => Ok(Outcome::Missing),
+    }
+}
+
+/// The lines that differ, as `-expected` / `+actual` pairs with line numbers
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut lines = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (old, new) = (expected.get(i), actual.get(i));
+        if old == new {
+            continue;
+        }
+        if let Some(old) = old {
+            lines.push(format!("{:>4} -{}", i + 1, old));
+        }
+        if let Some(new) = new {
+            lines.push(format!("{:>4} +{}", i + 1, new));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Several states in one snapshot, each under its own heading
+pub fn sequence(states: impl IntoIterator<Item = (String, String)>) -> String {
+    states
+        .into_iter()
+        .map(|(heading, state)| format!("{}:\n{}\n", heading, state.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_sequence() {
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+            "   2 -b\n   2 +x\n   4 +d"
+        );
+        assert_eq!(diff("same\n", "same\n"), "");
+        assert_eq!(
+            sequence([
+                ("Start".to_string(), "#.\n".to_string()),
+                ("End".to_string(), ".#".to_string())
+            ]),
+            "Start:\n#.\n\nEnd:\n.#\n"
+        );
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut paths: Vec<_> = all().iter().map(Snapshot::path).collect();
+        let count = paths.len();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), count);
+    }
+}
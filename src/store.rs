@@ -0,0 +1,504 @@
+//! An encrypted copy of the real inputs that can be committed, since puzzle
+//! inputs shouldn't be published in plaintext.
+//!
+//! `inputs encrypt` seals every file in `inputs/real` (inputs, `.meta` and
+//! `.answers`) into `inputs/encrypted/NAME.enc`, and `inputs decrypt` restores
+//! them. The key is a passphrase from the `AOC_INPUTS_KEY` environment
+//! variable, or else the first line of `.inputs-key`.
+//!
+//! The sealed files are public, so the passphrase goes through Argon2id with
+//! a random salt to make guessing it offline expensive. The salt is chosen
+//! once per store and kept in each file's header.
+//!
+//! Reading any input file goes through [`read`], so a clone with the
+//! encrypted store and the key runs as if `inputs/real` were there, and a
+//! binary with [embedded](crate::embed) inputs runs from anywhere.
+//!
+//! Without the `encrypted-inputs` feature plain and embedded files are still
+//! read, but encrypted ones can't be opened or written.
+
+use crate::{embed, Origin};
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "encrypted-inputs")]
+use sealing::salt_of;
+#[cfg(feature = "encrypted-inputs")]
+pub use sealing::{decrypt, encrypt, new_salt, Salt};
+
+/// Where the plaintext real inputs live
+pub const PLAIN_DIR: &str = "inputs/real";
+/// Where their encrypted copies live
+pub const STORE_DIR: &str = "inputs/encrypted";
+/// Environment variable holding the passphrase
+pub const KEY_VAR: &str = "AOC_INPUTS_KEY";
+/// File holding the passphrase when [`KEY_VAR`] isn't set
+pub const KEY_FILE: &str = ".inputs-key";
+
+/// The passphrase that keys are derived from, one per salt
+#[derive(Clone)]
+pub struct Key(
+    // Only read to derive keys, which needs the feature
+    #[cfg_attr(not(feature = "encrypted-inputs"), allow(dead_code))] String,
+);
+
+impl Key {
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Key(passphrase.trim().to_string())
+    }
+
+    /// The configured key, or `None` if there is none
+    pub fn load() -> Option<Self> {
+        let passphrase = match std::env::var(KEY_VAR) {
+            Ok(passphrase) => passphrase,
+            Err(_) => std::fs::read_to_string(KEY_FILE)
+                .ok()?
+                .lines()
+                .next()?
+                .to_string(),
+        };
+        (!passphrase.trim().is_empty()).then(|| Self::from_passphrase(&passphrase))
+    }
+
+    /// [`Key::load`], or an error saying how to provide one
+    pub fn require() -> Result<Self, String> {
+        Self::load().ok_or_else(|| format!("no key: set {} or write it to {}", KEY_VAR, KEY_FILE))
+    }
+}
+
+/// The encryption itself, which is what the `encrypted-inputs` feature adds
+#[cfg(feature = "encrypted-inputs")]
+mod sealing {
+    use super::Key;
+    use argon2::Argon2;
+    use chacha20poly1305::aead::rand_core::RngCore;
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+    use std::sync::Mutex;
+
+    /// Starts every encrypted file, so a format change can be detected
+    pub(super) const MAGIC: &[u8] = b"aoc-enc2";
+    /// The first format, whose key was a plain hash of the passphrase
+    const OLD_MAGIC: &[u8] = b"aoc-enc1";
+    pub(super) const SALT_LEN: usize = 16;
+    const NONCE_LEN: usize = 12;
+
+    /// Random bytes mixed into the passphrase, one per store
+    pub type Salt = [u8; SALT_LEN];
+
+    /// Keys already derived, since Argon2 is slow on purpose and every read
+    /// of an encrypted file needs one
+    static DERIVED: Mutex<Vec<(String, Salt, [u8; 32])>> = Mutex::new(Vec::new());
+
+    impl Key {
+        /// The key for files sealed with `salt`
+        fn cipher(&self, salt: &Salt) -> ChaCha20Poly1305 {
+            let mut derived = DERIVED.lock().unwrap_or_else(|e| e.into_inner());
+            let key = match derived.iter().find(|(p, s, _)| *p == self.0 && s == salt) {
+                Some((_, _, key)) => *key,
+                None => {
+                    let mut key = [0; 32];
+                    Argon2::default()
+                        .hash_password_into(self.0.as_bytes(), salt, &mut key)
+                        .expect("the salt and key lengths are valid");
+                    derived.push((self.0.clone(), *salt, key));
+                    key
+                }
+            };
+            ChaCha20Poly1305::new(&key.into())
+        }
+    }
+
+    /// A fresh random salt, for a new store
+    pub fn new_salt() -> Salt {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    }
+
+    /// Seals `plaintext`, bound to the file `name` so encrypted files can't
+    /// be swapped for one another
+    pub fn encrypt(key: &Key, salt: &Salt, name: &str, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = key.cipher(salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext,
+            aad: name.as_bytes(),
+        };
+        let sealed = cipher
+            .encrypt(&nonce, payload)
+            .expect("encrypting to memory can't fail");
+        [MAGIC, salt, nonce.as_slice(), &sealed].concat()
+    }
+
+    /// The salt an encrypted file was sealed with
+    pub(super) fn salt_of(data: &[u8]) -> Result<Salt, String> {
+        if data.starts_with(OLD_MAGIC) {
+            return Err("sealed in an older format, encrypt it again from inputs/real".to_string());
+        }
+        data.strip_prefix(MAGIC)
+            .and_then(|data| data.get(..SALT_LEN)?.try_into().ok())
+            .ok_or_else(|| "not an encrypted input".to_string())
+    }
+
+    /// Opens what [`encrypt`] sealed under the same `name`
+    pub fn decrypt(key: &Key, name: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+        let salt = salt_of(data)?;
+        let data = &data[MAGIC.len() + SALT_LEN..];
+        if data.len() < NONCE_LEN {
+            return Err("not an encrypted input".to_string());
+        }
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: sealed,
+            aad: name.as_bytes(),
+        };
+        key.cipher(&salt)
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| "wrong key or corrupted file".to_string())
+    }
+}
+
+/// Without the feature nothing can be opened, whatever the key
+#[cfg(not(feature = "encrypted-inputs"))]
+pub fn decrypt(_key: &Key, _name: &str, _data: &[u8]) -> Result<Vec<u8>, String> {
+    Err(crate::compiled_out("Decrypting inputs", "encrypted-inputs"))
+}
+
+/// Whether encrypted files can be opened: the feature is on and there's a key
+pub fn can_decrypt() -> bool {
+    cfg!(feature = "encrypted-inputs") && Key::load().is_some()
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()
+}
+
+fn sealed_path(store: &Path, name: &str) -> PathBuf {
+    store.join(format!("{}.enc", name))
+}
+
+/// The encrypted copy of a file in [`PLAIN_DIR`], if `path` is one
+fn encrypted_counterpart(path: &Path) -> Option<(PathBuf, &str)> {
+    let name = file_name(path)?;
+    (path.parent()? == Path::new(PLAIN_DIR))
+        .then(|| (sealed_path(Path::new(STORE_DIR), name), name))
+}
+
+/// Reads an input file, and says where it came from. The first of these
+/// that exists is used:
+///
+/// 1. the file itself
+/// 2. for a file in [`PLAIN_DIR`], its encrypted copy in [`STORE_DIR`],
+///    which needs the key
+/// 3. the same two [embedded](crate::embed) in the binary
+pub fn read(path: &Path) -> io::Result<(String, Origin)> {
+    read_with(path, encrypted_counterpart(path), Key::load)
+}
+
+fn read_with(
+    path: &Path,
+    sealed: Option<(PathBuf, &str)>,
+    key: impl FnOnce() -> Option<Key>,
+) -> io::Result<(String, Origin)> {
+    match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        read => return read.map(|text| (text, Origin::File)),
+    }
+    let (sealed, name, data, origin) = match (sealed, embed::file(path)) {
+        (Some((sealed, name)), _) if sealed.exists() => {
+            let data = std::fs::read(&sealed)?;
+            (sealed, name, data, Origin::Decrypted)
+        }
+        (_, Some(text)) => {
+            let text = String::from_utf8(text.to_vec()).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "embedded copy is not UTF-8")
+            })?;
+            return Ok((text, Origin::Embedded));
+        }
+        (Some((sealed, name)), None) if embed::file(&sealed).is_some() => {
+            let data = embed::file(&sealed).unwrap_or_default().to_vec();
+            (sealed, name, data, Origin::Embedded)
+        }
+        _ => return Err(io::Error::new(io::ErrorKind::NotFound, "no such file")),
+    };
+    let Some(key) = key() else {
+        return Err(io::Error::other(format!(
+            "only an encrypted copy exists and there is no key: set {} or write it to {}",
+            KEY_VAR, KEY_FILE
+        )));
+    };
+    let fail = |e: String| io::Error::other(format!("{}: {}", sealed.display(), e));
+    let plaintext = decrypt(&key, name, &data).map_err(fail)?;
+    let text = String::from_utf8(plaintext).map_err(|_| fail("not UTF-8".to_string()))?;
+    Ok((text, origin))
+}
+
+/// Whether [`read`] could find the file, in any of the places it looks
+pub fn exists(path: &Path) -> bool {
+    path.exists()
+        || embed::file(path).is_some()
+        || encrypted_counterpart(path)
+            .is_some_and(|(sealed, _)| sealed.exists() || embed::file(&sealed).is_some())
+}
+
+#[cfg(feature = "encrypted-inputs")]
+/// One file handled by [`encrypt_dir`] or [`decrypt_dir`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Written(String),
+    /// Already up to date
+    Unchanged(String),
+    /// Left alone because it differs, see `force`
+    Kept(String),
+}
+
+#[cfg(feature = "encrypted-inputs")]
+fn sorted_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = dir
+        .read_dir()
+        .map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(feature = "encrypted-inputs")]
+/// Encrypts every file in `plain` into `store`. Files whose encrypted copy
+/// already holds the same text are left alone, so re-running doesn't churn
+/// the store with fresh nonces. The store keeps its salt, or gets a new one
+/// if it's empty.
+///
+/// An encrypted copy that doesn't open with `key` is an error, checked before
+/// anything is written, so a mistyped passphrase can't leave the store under
+/// a mix of keys. With `force` those are encrypted again with `key`, which is
+/// how a store moves to a new passphrase.
+pub fn encrypt_dir(
+    key: &Key,
+    plain: &Path,
+    store: &Path,
+    force: bool,
+) -> Result<Vec<Outcome>, String> {
+    std::fs::create_dir_all(store).map_err(|e| format!("{}: {}", store.display(), e))?;
+    let salt = sorted_files(store)?
+        .iter()
+        .find_map(|sealed| salt_of(&std::fs::read(sealed).ok()?).ok())
+        .unwrap_or_else(new_salt);
+    let mut changed = Vec::new();
+    for path in sorted_files(plain)? {
+        let Some(name) = file_name(&path) else {
+            continue;
+        };
+        let plaintext = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let sealed = sealed_path(store, name);
+        let current = match std::fs::read(&sealed) {
+            Ok(data) => match decrypt(key, name, &data) {
+                Ok(current) => Some(current),
+                Err(_) if force => None,
+                Err(e) => {
+                    return Err(format!(
+                        "{}: {} (use --force to encrypt it again with this key)",
+                        sealed.display(),
+                        e
+                    ))
+                }
+            },
+            Err(_) => None,
+        };
+        let plaintext = (current.as_ref() != Some(&plaintext)).then_some(plaintext);
+        changed.push((name.to_string(), sealed, plaintext));
+    }
+    let mut outcomes = Vec::new();
+    for (name, sealed, plaintext) in changed {
+        let Some(plaintext) = plaintext else {
+            outcomes.push(Outcome::Unchanged(name));
+            continue;
+        };
+        std::fs::write(&sealed, encrypt(key, &salt, &name, &plaintext))
+            .map_err(|e| format!("{}: {}", sealed.display(), e))?;
+        outcomes.push(Outcome::Written(name));
+    }
+    Ok(outcomes)
+}
+
+#[cfg(feature = "encrypted-inputs")]
+/// Decrypts every file in `store` into `plain`. A plaintext file that
+/// differs from its encrypted copy is kept unless `force` is set, since it
+/// may hold changes that were never encrypted.
+pub fn decrypt_dir(
+    key: &Key,
+    store: &Path,
+    plain: &Path,
+    force: bool,
+) -> Result<Vec<Outcome>, String> {
+    std::fs::create_dir_all(plain).map_err(|e| format!("{}: {}", plain.display(), e))?;
+    let mut outcomes = Vec::new();
+    for sealed in sorted_files(store)? {
+        let Some(name) = file_name(&sealed).and_then(|name| name.strip_suffix(".enc")) else {
+            continue;
+        };
+        let data = std::fs::read(&sealed).map_err(|e| format!("{}: {}", sealed.display(), e))?;
+        let plaintext =
+            decrypt(key, name, &data).map_err(|e| format!("{}: {}", sealed.display(), e))?;
+        let path = plain.join(name);
+        let outcome = match std::fs::read(&path) {
+            Ok(current) if current == plaintext => Outcome::Unchanged(name.to_string()),
+            Ok(_) if !force => Outcome::Kept(name.to_string()),
+            _ => {
+                std::fs::write(&path, plaintext)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                Outcome::Written(name.to_string())
+            }
+        };
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+#[cfg(feature = "encrypted-inputs")]
+/// The `inputs encrypt` and `inputs decrypt` commands
+pub fn run(decrypting: bool, force: bool) -> Result<(), String> {
+    let key = Key::require()?;
+    let (plain, store) = (Path::new(PLAIN_DIR), Path::new(STORE_DIR));
+    let outcomes = if decrypting {
+        decrypt_dir(&key, store, plain, force)?
+    } else {
+        encrypt_dir(&key, plain, store, force)?
+    };
+    let (from, to) = if decrypting {
+        (store, plain)
+    } else {
+        (plain, store)
+    };
+    let mut kept = 0;
+    for outcome in &outcomes {
+        match outcome {
+            Outcome::Written(name) => println!("{}: written to {}", name, to.display()),
+            Outcome::Unchanged(name) => println!("{}: unchanged", name),
+            Outcome::Kept(name) => {
+                kept += 1;
+                println!(
+                    "{}: differs from {}, kept (use --force to overwrite)",
+                    name,
+                    from.display()
+                );
+            }
+        }
+    }
+    match kept {
+        0 => Ok(()),
+        _ => Err(format!("{} file(s) kept", kept)),
+    }
+}
+
+#[cfg(not(feature = "encrypted-inputs"))]
+pub fn run(_decrypting: bool, _force: bool) -> Result<(), String> {
+    Err(crate::compiled_out(
+        "The inputs command",
+        "encrypted-inputs",
+    ))
+}
+
+#[cfg(all(test, feature = "encrypted-inputs"))]
+mod tests {
+    use super::sealing::{MAGIC, SALT_LEN};
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = Key::from_passphrase("hunter2");
+        let salt = new_salt();
+        let sealed = encrypt(&key, &salt, "01.txt", b"3   4\n");
+        assert!(!sealed.windows(5).any(|w| w == b"3   4"));
+        assert_eq!(decrypt(&key, "01.txt", &sealed), Ok(b"3   4\n".to_vec()));
+        assert_eq!(salt_of(&sealed), Ok(salt));
+
+        // The same passphrase under another store's salt is another key
+        let other = encrypt(&key, &new_salt(), "01.txt", b"3   4\n");
+        assert_eq!(decrypt(&key, "01.txt", &other), Ok(b"3   4\n".to_vec()));
+        let mut swapped = other.clone();
+        swapped[MAGIC.len()..MAGIC.len() + SALT_LEN].copy_from_slice(&salt);
+        assert!(decrypt(&key, "01.txt", &swapped).is_err());
+
+        let wrong = Key::from_passphrase("hunter3");
+        assert!(decrypt(&wrong, "01.txt", &sealed).is_err());
+        // Bound to its name, so it can't pass for another day's input
+        assert!(decrypt(&key, "02.txt", &sealed).is_err());
+        assert!(decrypt(&key, "01.txt", b"3   4").is_err());
+        let old = decrypt(&key, "01.txt", b"aoc-enc1............").unwrap_err();
+        assert!(old.contains("older format"), "{}", old);
+    }
+
+    #[test]
+    fn test_directories() {
+        let dir = std::env::temp_dir().join(format!("aoc2024-store-{}", std::process::id()));
+        let (plain, store, restored) = (
+            dir.join("real"),
+            dir.join("encrypted"),
+            dir.join("restored"),
+        );
+        std::fs::create_dir_all(&plain).unwrap();
+        std::fs::write(plain.join("01.txt"), "1 2\n").unwrap();
+        std::fs::write(plain.join("01.answers"), "part1 = 1\n").unwrap();
+        let key = Key::from_passphrase("key");
+
+        let written = encrypt_dir(&key, &plain, &store, false).unwrap();
+        let sealed = std::fs::read(store.join("01.txt.enc")).unwrap();
+        let answers_salt = salt_of(&std::fs::read(store.join("01.answers.enc")).unwrap());
+        let again = encrypt_dir(&key, &plain, &store, false).unwrap();
+        let resealed = std::fs::read(store.join("01.txt.enc")).unwrap();
+
+        decrypt_dir(&key, &store, &restored, false).unwrap();
+        let text = read_with(
+            &restored.join("missing.txt"),
+            Some((store.join("01.txt.enc"), "01.txt")),
+            || Some(key.clone()),
+        );
+        let keyless = read_with(
+            &restored.join("missing.txt"),
+            Some((store.join("01.txt.enc"), "01.txt")),
+            || None,
+        );
+        std::fs::write(restored.join("01.txt"), "edited\n").unwrap();
+        let kept = decrypt_dir(&key, &store, &restored, false).unwrap();
+        let edited = std::fs::read_to_string(restored.join("01.txt")).unwrap();
+        let restored_answers = std::fs::read_to_string(restored.join("01.answers")).unwrap();
+
+        // Another passphrase leaves the store alone unless forced
+        let other = Key::from_passphrase("other key");
+        let mistyped = encrypt_dir(&other, &plain, &store, false);
+        let untouched = std::fs::read(store.join("01.txt.enc")).unwrap();
+        let rekeyed = encrypt_dir(&other, &plain, &store, true).unwrap();
+        let reopened = decrypt(
+            &other,
+            "01.txt",
+            &std::fs::read(store.join("01.txt.enc")).unwrap(),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names = ["01.answers".to_string(), "01.txt".to_string()];
+        assert_eq!(written, names.clone().map(Outcome::Written));
+        assert_eq!(again, names.clone().map(Outcome::Unchanged));
+        assert_eq!(sealed, resealed);
+        assert_eq!(salt_of(&sealed), answers_salt);
+        assert_eq!(text.unwrap(), ("1 2\n".to_string(), Origin::Decrypted));
+        assert!(keyless.unwrap_err().to_string().contains(KEY_VAR));
+        assert_eq!(
+            kept,
+            [
+                Outcome::Unchanged("01.answers".to_string()),
+                Outcome::Kept("01.txt".to_string())
+            ]
+        );
+        assert_eq!(edited, "edited\n");
+        assert_eq!(restored_answers, "part1 = 1\n");
+        let error = mistyped.unwrap_err();
+        assert!(error.contains("01.answers.enc: wrong key"), "{}", error);
+        assert_eq!(untouched, sealed);
+        assert_eq!(rekeyed, names.clone().map(Outcome::Written));
+        assert_eq!(reopened, Ok(b"1 2\n".to_vec()));
+    }
+}
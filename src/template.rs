@@ -1,23 +1,23 @@
-use crate::Solution;
+use crate::{Params, Solution, SolveError};
 
 pub struct DayXX;
 
 impl Solution for DayXX {
-    fn part1(&self, input: &str) -> String {
+    fn try_part1_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
         // TODO: Implement solution
-        "Not implemented".to_string()
+        Ok("Not implemented".to_string())
     }
 
-    fn part2(&self, input: &str) -> String {
+    fn try_part2_with(&self, input: &str, _params: &Params) -> Result<String, SolveError> {
         // TODO: Implement solution
-        "Not implemented".to_string()
+        Ok("Not implemented".to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::read_input;
+    use crate::{read_input, real_input};
 
     #[test]
     fn test_part1_sample() {
@@ -27,8 +27,11 @@ mod tests {
 
     #[test]
     fn test_part1_real() {
-        let input = read_input(XX, false);
-        assert_eq!(DayXX.part1(&input), "Not implemented");
+        // Skipped where the real input isn't available, e.g. CI without the key
+        let Some(input) = real_input(XX) else {
+            return;
+        };
+        assert_eq!(DayXX.part1(&input.text), "Not implemented");
     }
 
     #[test]
@@ -39,7 +42,10 @@ mod tests {
 
     #[test]
     fn test_part2_real() {
-        let input = read_input(XX, false);
-        assert_eq!(DayXX.part2(&input), "Not implemented");
+        // Skipped where the real input isn't available, e.g. CI without the key
+        let Some(input) = real_input(XX) else {
+            return;
+        };
+        assert_eq!(DayXX.part2(&input.text), "Not implemented");
     }
 }
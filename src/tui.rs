@@ -0,0 +1,557 @@
+//! A terminal calendar of all 25 days. Each day shows whether it's solved,
+//! whether its sample and real answers match their `.answers` files, and its
+//! stars. Parts run on a worker thread, with their logs and rendered frames
+//! shown live in panes.
+
+use crate::{
+    get_solution, run_parts, set_output_sink, set_rendering, InputSource, Output, Params,
+    PartReport,
+};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Log lines kept for the log pane
+const MAX_LOGS: usize = 1000;
+/// Frames kept for the renderer
+const MAX_FRAMES: usize = 200;
+
+const HELP: &str = "arrows move  enter run  1/2 run part  s sample/real  a run all  r rendering  v renderer  c clear  q quit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Job {
+    day: u8,
+    source: InputSource,
+    part: u8,
+}
+
+enum Event {
+    Output(Output),
+    Started(Job),
+    Finished(Job, Result<PartReport, String>),
+}
+
+/// A part's latest report, or why it couldn't run
+type PartResult = Option<Result<PartReport, String>>;
+
+/// How far along a day is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Missing,
+    Implemented,
+    /// A part answered wrong or failed
+    Failing,
+    SamplePassing,
+    RealPassing,
+}
+
+/// The latest result of each part of a day, per input
+#[derive(Debug, Clone, Default)]
+pub struct DayResults {
+    pub implemented: bool,
+    pub sample: [PartResult; 2],
+    pub real: [PartResult; 2],
+}
+
+impl DayResults {
+    /// Both parts ran, none answered wrong and at least one answer is known
+    /// to be right. Some samples have no answer for part 2.
+    fn passing(results: &[PartResult; 2]) -> bool {
+        let verdicts: Vec<_> = results
+            .iter()
+            .map(|r| match r {
+                Some(Ok(report)) if report.error.is_none() => report.correct(),
+                _ => Some(false),
+            })
+            .collect();
+        !verdicts.contains(&Some(false)) && verdicts.contains(&Some(true))
+    }
+
+    /// Parts of the real input answered correctly
+    pub fn stars(&self) -> usize {
+        self.real
+            .iter()
+            .filter(|r| matches!(r, Some(Ok(report)) if report.correct() == Some(true)))
+            .count()
+    }
+
+    pub fn status(&self) -> Status {
+        let failing = self.sample.iter().chain(&self.real).any(|r| match r {
+            Some(Ok(report)) => report.correct() == Some(false) || report.error.is_some(),
+            Some(Err(_)) => true,
+            None => false,
+        });
+        if !self.implemented {
+            Status::Missing
+        } else if failing {
+            Status::Failing
+        } else if Self::passing(&self.real) {
+            Status::RealPassing
+        } else if Self::passing(&self.sample) {
+            Status::SamplePassing
+        } else {
+            Status::Implemented
+        }
+    }
+
+    fn results_mut(&mut self, source: &InputSource) -> &mut [PartResult; 2] {
+        match source {
+            InputSource::Real => &mut self.real,
+            _ => &mut self.sample,
+        }
+    }
+}
+
+/// The TUI state, separate from the terminal so it can be driven by tests
+pub struct App {
+    days: Vec<DayResults>,
+    selected: u8,
+    source: InputSource,
+    logs: Vec<String>,
+    frames: Vec<String>,
+    /// Frame shown in the renderer, if it's open
+    viewing: Option<usize>,
+    rendering: bool,
+    running: Option<(Job, Instant)>,
+    queued: usize,
+    jobs: Sender<Job>,
+    events: Receiver<Event>,
+    done: bool,
+}
+
+impl App {
+    fn new(jobs: Sender<Job>, events: Receiver<Event>) -> Self {
+        let days = (1..=25)
+            .map(|day| DayResults {
+                implemented: get_solution(day).is_some(),
+                ..DayResults::default()
+            })
+            .collect();
+        Self {
+            days,
+            selected: 1,
+            source: InputSource::Sample,
+            logs: Vec::new(),
+            frames: Vec::new(),
+            viewing: None,
+            rendering: false,
+            running: None,
+            queued: 0,
+            jobs,
+            events,
+            done: false,
+        }
+    }
+
+    pub fn day(&self, day: u8) -> &DayResults {
+        &self.days[day as usize - 1]
+    }
+
+    fn queue(&mut self, day: u8, parts: &[u8]) {
+        if !self.day(day).implemented {
+            return;
+        }
+        for &part in parts {
+            let job = Job {
+                day,
+                source: self.source.clone(),
+                part,
+            };
+            if self.jobs.send(job).is_ok() {
+                self.queued += 1;
+            }
+        }
+    }
+
+    /// Applies everything the worker has sent so far
+    fn drain_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Output(Output::Log(line)) => {
+                    self.logs.push(line);
+                    if self.logs.len() > MAX_LOGS {
+                        self.logs.remove(0);
+                    }
+                }
+                Event::Output(Output::Frame(frame)) => {
+                    self.frames.push(frame);
+                    if self.frames.len() > MAX_FRAMES {
+                        self.frames.remove(0);
+                    }
+                }
+                Event::Started(job) => {
+                    self.queued = self.queued.saturating_sub(1);
+                    self.running = Some((job, Instant::now()));
+                }
+                Event::Finished(job, result) => {
+                    self.running = None;
+                    let day = &mut self.days[job.day as usize - 1];
+                    day.results_mut(&job.source)[job.part as usize - 1] = Some(result);
+                }
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        if let Some(frame) = self.viewing {
+            match key {
+                KeyCode::Left | KeyCode::Char('h') => self.viewing = Some(frame.saturating_sub(1)),
+                KeyCode::Right | KeyCode::Char('l') => {
+                    self.viewing = Some((frame + 1).min(self.frames.len().saturating_sub(1)))
+                }
+                KeyCode::Char('q') | KeyCode::Char('v') | KeyCode::Esc => self.viewing = None,
+                _ => {}
+            }
+            return;
+        }
+
+        let (row, col) = ((self.selected - 1) / 5, (self.selected - 1) % 5);
+        match key {
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.selected = row * 5 + col.saturating_sub(1) + 1
+            }
+            KeyCode::Right | KeyCode::Char('l') => self.selected = row * 5 + (col + 1).min(4) + 1,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = row.saturating_sub(1) * 5 + col + 1,
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (row + 1).min(4) * 5 + col + 1,
+            KeyCode::Enter => self.queue(self.selected, &[1, 2]),
+            KeyCode::Char('1') => self.queue(self.selected, &[1]),
+            KeyCode::Char('2') => self.queue(self.selected, &[2]),
+            KeyCode::Char('a') => {
+                for day in 1..=25 {
+                    self.queue(day, &[1, 2]);
+                }
+            }
+            KeyCode::Char('s') => {
+                self.source = match self.source {
+                    InputSource::Real => InputSource::Sample,
+                    _ => InputSource::Real,
+                }
+            }
+            KeyCode::Char('r') => {
+                self.rendering = !self.rendering;
+                set_rendering(self.rendering);
+            }
+            KeyCode::Char('v') if !self.frames.is_empty() => {
+                self.viewing = Some(self.frames.len() - 1)
+            }
+            KeyCode::Char('c') => {
+                self.logs.clear();
+                self.frames.clear();
+            }
+            KeyCode::Char('q') | KeyCode::Esc => self.done = true,
+            _ => {}
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [title, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [calendar, side] =
+            Layout::horizontal([Constraint::Length(52), Constraint::Min(0)]).areas(body);
+        let [details, logs] =
+            Layout::vertical([Constraint::Length(10), Constraint::Min(0)]).areas(side);
+
+        let stars: usize = self.days.iter().map(DayResults::stars).sum();
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled("Advent of Code 2024", Style::new().bold()),
+                Span::raw(format!("  {}/50 ★  input: {}", stars, self.source)),
+            ]),
+            title,
+        );
+        self.draw_calendar(frame, calendar);
+        self.draw_details(frame, details);
+
+        let height = logs.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = self.logs[self.logs.len().saturating_sub(height)..]
+            .iter()
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Log")),
+            logs,
+        );
+
+        let status = match &self.running {
+            Some((job, started)) => format!(
+                "Running day {} part {} on {} input, {:.1}s ({} queued)  ",
+                job.day,
+                job.part,
+                job.source,
+                started.elapsed().as_secs_f64(),
+                self.queued
+            ),
+            None => String::new(),
+        };
+        frame.render_widget(Line::raw(format!("{}{}", status, HELP)).dim(), footer);
+
+        if let Some(index) = self.viewing {
+            let area = frame.area().inner(ratatui::layout::Margin::new(4, 2));
+            let title = format!(
+                "Renderer: frame {}/{} (left/right to step, v to close)",
+                index + 1,
+                self.frames.len()
+            );
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(self.frames[index].as_str()).block(Block::bordered().title(title)),
+                area,
+            );
+        }
+    }
+
+    fn draw_calendar(&self, frame: &mut Frame, area: Rect) {
+        let rows = Layout::vertical([Constraint::Length(4); 5]).split(area);
+        for (row, &row_area) in rows.iter().enumerate() {
+            let cells = Layout::horizontal([Constraint::Length(10); 5]).split(row_area);
+            for (col, &cell) in cells.iter().enumerate() {
+                let day = (row * 5 + col + 1) as u8;
+                let results = self.day(day);
+                let color = match results.status() {
+                    Status::Missing => Color::DarkGray,
+                    Status::Implemented => Color::White,
+                    Status::Failing => Color::Red,
+                    Status::SamplePassing => Color::Yellow,
+                    Status::RealPassing => Color::Green,
+                };
+                let mut block = Block::bordered().border_style(Style::new().fg(color));
+                if day == self.selected {
+                    block = block.border_style(Style::new().fg(color).add_modifier(Modifier::BOLD));
+                    block = block.border_type(ratatui::widgets::BorderType::Double);
+                }
+                let stars = "★".repeat(results.stars()) + &"☆".repeat(2 - results.stars());
+                let text = vec![
+                    Line::styled(format!("Day {}", day), Style::new().fg(color)),
+                    Line::raw(if results.implemented {
+                        stars
+                    } else {
+                        String::new()
+                    }),
+                ];
+                frame.render_widget(Paragraph::new(text).block(block), cell);
+            }
+        }
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let day = self.day(self.selected);
+        let mut lines = Vec::new();
+        if !day.implemented {
+            lines.push(Line::raw(crate::no_solution(self.selected)));
+        }
+        for (name, results) in [("Sample", &day.sample), ("Real", &day.real)] {
+            lines.push(Line::styled(name, Style::new().bold()));
+            for (i, result) in results.iter().enumerate() {
+                let (text, color) = match result {
+                    None => ("not run".to_string(), Color::DarkGray),
+                    Some(Err(e)) => (e.clone(), Color::Red),
+                    Some(Ok(report)) => describe(report),
+                };
+                lines.push(Line::styled(
+                    format!("  Part {}: {}", i + 1, text),
+                    Style::new().fg(color),
+                ));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(format!("Day {}", self.selected))),
+            area,
+        );
+    }
+}
+
+fn describe(report: &PartReport) -> (String, Color) {
+    let verdict = match (report.correct(), &report.expected) {
+        (Some(true), _) => " ✓".to_string(),
+        (Some(false), Some(expected)) => format!(" ✗ expected {}", expected),
+        _ => String::new(),
+    };
+    let color = match report.correct() {
+        Some(true) => Color::Green,
+        Some(false) => Color::Red,
+        None => Color::White,
+    };
+    match (&report.answer, &report.error) {
+        (Some(answer), _) => (
+            format!("{} ({:?}){}", answer, report.elapsed, verdict),
+            color,
+        ),
+        (_, Some(e)) => (format!("failed: {}", e), Color::Red),
+        (None, None) => unreachable!("a part either answers or fails"),
+    }
+}
+
+/// Runs jobs one at a time until the app hangs up
+fn worker(jobs: Receiver<Job>, events: Sender<Event>) {
+    for job in jobs {
+        if events.send(Event::Started(job.clone())).is_err() {
+            return;
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            run_parts(job.day, &job.source, "default", &Params::new(), &[job.part])
+        }))
+        .unwrap_or_else(|_| Err("panicked".to_string()))
+        .map(|mut report| report.parts.remove(0));
+        if events.send(Event::Finished(job, result)).is_err() {
+            return;
+        }
+    }
+}
+
+fn event_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    while !app.done {
+        app.drain_events();
+        terminal.draw(|frame| app.draw(frame))?;
+        if event::poll(Duration::from_millis(100))? {
+            if let TermEvent::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key.code);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `tui` command. Starts by running every day's sample.
+pub fn run() -> Result<(), String> {
+    let (job_tx, job_rx) = mpsc::channel();
+    let (event_tx, event_rx) = mpsc::channel();
+    let output_tx = event_tx.clone();
+    set_output_sink(Some(Box::new(move |output| {
+        let _ = output_tx.send(Event::Output(output));
+    })));
+    thread::spawn(move || worker(job_rx, event_tx));
+
+    let mut app = App::new(job_tx, event_rx);
+    for day in 1..=25 {
+        app.queue(day, &[1, 2]);
+    }
+
+    // Anything printed on panic would land in the middle of the screen
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = (|| {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        event_loop(&mut terminal, &mut app)
+    })();
+    let restored = disable_raw_mode().and(execute!(io::stdout(), LeaveAlternateScreen));
+    panic::set_hook(hook);
+    set_output_sink(None);
+    result.and(restored).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let (jobs, _) = mpsc::channel();
+        let (_, events) = mpsc::channel();
+        App::new(jobs, events)
+    }
+
+    fn report(answer: &str, expected: &str) -> PartResult {
+        Some(Ok(PartReport {
+            part: 1,
+            answer: Some(answer.to_string()),
+            error: None,
+            elapsed: Duration::ZERO,
+            memory: None,
+            expected: Some(expected.to_string()),
+            events: Vec::new(),
+        }))
+    }
+
+    #[test]
+    fn test_status_and_stars() {
+        let mut day = DayResults {
+            implemented: true,
+            ..DayResults::default()
+        };
+        assert_eq!(day.status(), Status::Implemented);
+        day.sample = [report("11", "11"), report("31", "31")];
+        assert_eq!(day.status(), Status::SamplePassing);
+        day.real = [report("7", "7"), None];
+        assert_eq!((day.status(), day.stars()), (Status::SamplePassing, 1));
+        day.real[1] = report("8", "9");
+        assert_eq!((day.status(), day.stars()), (Status::Failing, 1));
+        day.real[1] = report("9", "9");
+        assert_eq!((day.status(), day.stars()), (Status::RealPassing, 2));
+
+        // A part without a known answer doesn't hold the others back
+        day.real[1] = Some(Ok(PartReport {
+            expected: None,
+            ..report("9", "9").unwrap().unwrap()
+        }));
+        assert_eq!((day.status(), day.stars()), (Status::RealPassing, 1));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut app = app();
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Right);
+        assert_eq!(app.selected, 7);
+        for _ in 0..10 {
+            app.handle_key(KeyCode::Down);
+            app.handle_key(KeyCode::Right);
+        }
+        assert_eq!(app.selected, 25);
+        app.handle_key(KeyCode::Char('s'));
+        assert_eq!(app.source, InputSource::Real);
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.done);
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn test_runs_jobs_and_draws() {
+        use ratatui::backend::TestBackend;
+
+        let (job_tx, job_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let worker = thread::spawn(move || worker(job_rx, event_tx));
+        let mut app = App::new(job_tx, event_rx);
+        app.handle_key(KeyCode::Enter);
+        drop(std::mem::replace(&mut app.jobs, mpsc::channel().0));
+        worker.join().unwrap();
+        app.drain_events();
+
+        let day = app.day(1);
+        assert_eq!(day.status(), Status::SamplePassing);
+
+        let mut terminal = Terminal::new(TestBackend::new(110, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Day 25"));
+        assert!(screen.contains("Part 2: 31"));
+    }
+}
@@ -0,0 +1,40 @@
+//! Checks every day's rendered states against their golden files. Run with
+//! `-- --update-snapshots` to rewrite the golden files from the current code.
+
+use aoc2024::snapshot::{self, Outcome};
+
+fn main() {
+    let update = std::env::args().any(|arg| arg == "--update-snapshots");
+    let snapshots = snapshot::all();
+    println!("\nrunning {} snapshots", snapshots.len());
+
+    let mut failed = 0;
+    for snapshot in &snapshots {
+        let label = snapshot.path().display().to_string();
+        match snapshot::check(snapshot, update) {
+            Ok(Outcome::Matched) => println!("snapshot {} ... ok", label),
+            Ok(Outcome::Written) => println!("snapshot {} ... written", label),
+            Ok(Outcome::Missing) => {
+                failed += 1;
+                println!("snapshot {} ... MISSING", label);
+            }
+            Ok(Outcome::Changed(diff)) => {
+                failed += 1;
+                println!("snapshot {} ... CHANGED\n{}", label, diff);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("snapshot {} ... ERROR {}", label, e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        println!(
+            "\n{} snapshot(s) failed; if the change is intended, rerun with -- --update-snapshots",
+            failed
+        );
+        std::process::exit(1);
+    }
+    println!("\nsnapshot result: ok. {} checked\n", snapshots.len());
+}